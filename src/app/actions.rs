@@ -1,9 +1,16 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    mpsc::{SendError, Sender},
+    Arc,
+};
+
 use btleplug::api::BDAddr;
 
 use super::config::Config;
 
+#[derive(Clone)]
 pub enum AppAction {
-    Init(Config),
+    Init(Box<Config>),
     TearDown,
 
     UpdateDevicesList,
@@ -12,6 +19,7 @@ pub enum AppAction {
     StartMoving(MovingDirection),
 }
 
+#[derive(Clone)]
 pub enum MovingDirection {
     Up,
     Down,
@@ -19,7 +27,79 @@ pub enum MovingDirection {
     ToHeight(u16),
 }
 
+/// Sends the actions to the `BackgroundJob`. Its queue is blocked while the
+/// desk moves to a height, so a stop also raises a flag that the move checks
+/// right away.
+#[derive(Clone)]
+pub struct ActionSender {
+    sx: Sender<AppAction>,
+    stop_requested: Arc<AtomicBool>,
+}
+
+impl ActionSender {
+    pub fn new(sx: Sender<AppAction>, stop_requested: Arc<AtomicBool>) -> Self {
+        Self { sx, stop_requested }
+    }
+
+    // note: a move queued after the stop clears the flag, one queued before
+    // it is stopped as soon as it starts
+    pub fn send(&self, action: AppAction) -> Result<(), SendError<AppAction>> {
+        match action {
+            AppAction::StartMoving(MovingDirection::Stop) => {
+                self.stop_requested.store(true, Ordering::SeqCst)
+            }
+            AppAction::StartMoving(MovingDirection::ToHeight(_)) => {
+                self.stop_requested.store(false, Ordering::SeqCst)
+            }
+            _ => (),
+        }
+        self.sx.send(action)
+    }
+}
+
 pub enum SelectionMove {
     Prev,
     Next,
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+
+    #[test]
+    fn requests_the_stop_before_it_is_dequeued() {
+        let (sx, rx) = mpsc::channel::<AppAction>();
+        let stop_requested = Arc::new(AtomicBool::new(false));
+        let sender = ActionSender::new(sx, Arc::clone(&stop_requested));
+
+        sender
+            .send(AppAction::StartMoving(MovingDirection::ToHeight(9000)))
+            .unwrap();
+        assert!(!stop_requested.load(Ordering::SeqCst));
+        sender
+            .send(AppAction::StartMoving(MovingDirection::Stop))
+            .unwrap();
+        assert!(stop_requested.load(Ordering::SeqCst));
+        sender
+            .send(AppAction::StartMoving(MovingDirection::Up))
+            .unwrap();
+        assert!(stop_requested.load(Ordering::SeqCst));
+
+        // note: the actions are still queued in order
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(AppAction::StartMoving(MovingDirection::ToHeight(9000)))
+        ));
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(AppAction::StartMoving(MovingDirection::Stop))
+        ));
+
+        sender
+            .send(AppAction::StartMoving(MovingDirection::ToHeight(7000)))
+            .unwrap();
+        assert!(!stop_requested.load(Ordering::SeqCst));
+    }
+}
@@ -1,14 +1,20 @@
 use std::{
+    env,
     error::Error,
+    io::{self, Write},
+    str::FromStr,
     sync::{atomic::AtomicBool, mpsc::Sender, Arc, Mutex},
     thread::JoinHandle,
+    time::{Duration, Instant},
 };
 
-use btleplug::api::Peripheral;
-use crossterm::event::KeyCode;
+use btleplug::api::{BDAddr, Peripheral};
+use chrono::Local;
+use crossterm::event::{KeyEvent, MouseButton, MouseEvent, MouseEventKind};
 use ratatui::{
     backend::Backend,
-    layout::{Alignment, Constraint, Direction, Layout},
+    layout::{Alignment, Rect},
+    style::Stylize,
     text::Text,
     widgets::{Block, BorderType, Borders, Clear, Padding, Paragraph},
     Frame, Terminal,
@@ -16,18 +22,44 @@ use ratatui::{
 
 use crate::{
     bluetooth::manager::BleManager,
-    tui::{centered_rect::centered_rect_text, notification_list::NotificationList},
+    metrics::Metrics,
+    tui::{
+        centered_rect::centered_rect_text,
+        list::ListableItem,
+        modal::{ConfirmDialog, InputDialog, ModalOutcome, Picker, PickerDialog, TextInput},
+        notification_list::NotificationList,
+        theme::Theme,
+    },
 };
 
+#[cfg(target_os = "linux")]
+use super::jobs::dbus_job::DbusJob;
 use super::{
-    actions::{AppAction, MovingDirection, SelectionMove},
+    actions::{ActionSender, AppAction, MovingDirection, SelectionMove},
     config::Config,
+    ergonomics::{self, Recommendation},
+    export::ExportRange,
+    height::{self, HeightUnit},
+    history::HeightSamples,
     jobs::{
         background_job::BackgroundJob,
+        export_job::ExportJob,
+        history_job::HistoryJob,
+        metrics_job::MetricsJob,
         movement_job,
-        notification_job::{Notification, NotificationManager},
+        notification_job::{
+            Notification, NotificationHistory, NotificationManager, NotificationType,
+        },
+        reminder_job::ReminderJob,
+        scheduler_job::SchedulerJob,
+    },
+    keymap::{Action, Keymap, KeymapScope},
+    layout::{LayoutMode, ScreenLayout},
+    palette::{self, Command, CommandPalette, PaletteEntry},
+    state::{
+        position_hotkeys, ErgonomicsWizard, HelpOverlay, Modal, ModalKind, NotificationCentre,
+        PositionEdit, PositionEditKind, ReminderStatus, SavedPosition, State, Status, WizardStep,
     },
-    state::{State, Status},
     ui_event_emitter::{EventEmitter, UIEvent},
 };
 
@@ -37,63 +69,260 @@ pub enum Views {
     Movement,
     SavedPositions,
     DeviceList,
+    Statistics,
+    HeightChart,
+}
+
+impl Views {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Views::Connection => "connection",
+            Views::Movement => "movement",
+            Views::SavedPositions => "saved positions",
+            Views::DeviceList => "devices",
+            Views::Statistics => "statistics",
+            Views::HeightChart => "height chart",
+        }
+    }
+
+    pub fn keymap_scope(&self) -> KeymapScope {
+        match self {
+            Views::Connection => KeymapScope::Connection,
+            Views::Movement => KeymapScope::Movement,
+            Views::SavedPositions => KeymapScope::Positions,
+            Views::DeviceList => KeymapScope::Devices,
+            Views::Statistics => KeymapScope::Statistics,
+            Views::HeightChart => KeymapScope::Chart,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MouseTarget {
+    View(Views),
+    // note: rows of the list of a view, with the first visible item
+    ListRows(Views, usize),
+    MoveButton(MoveButton),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveButton {
+    Up,
+    Stop,
+    Down,
 }
 
+const VIEWS_COUNT: usize = 6;
+const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(400);
+// note: reordering is typically a burst of J/K presses
+const POSITIONS_SAVE_DELAY: Duration = Duration::from_secs(1);
+// note: 2.5m, tenth of mm
+const MAX_BODY_HEIGHT: i32 = 25000;
+const DEFAULT_PROFILE_LABEL: &str = "default";
+const RECOMMENDED_SITTING_NAME: &str = "sitting (recommended)";
+const RECOMMENDED_STANDING_NAME: &str = "standing (recommended)";
+const TABS_GAP: u16 = 1;
+
 pub struct App {
     state: Arc<Mutex<State>>,
+    config: Config,
+    keymap: Keymap,
+    theme: Theme,
+
+    // note: filled while drawing, the last matching target is on top
+    mouse_targets: Vec<(MouseTarget, Rect)>,
+    // note: the mode of the last drawn frame
+    layout_mode: LayoutMode,
+    last_click: Option<(Instant, Views, usize)>,
+    is_move_button_held: bool,
 
-    notifications: Arc<Mutex<Vec<Notification>>>,
+    notifications: Arc<Mutex<NotificationHistory>>,
+    // note: kept out of the state, which is cloned for every frame
+    height_samples: Arc<Mutex<HeightSamples>>,
 
-    long_running_actions_sx: Sender<AppAction>,
+    long_running_actions_sx: ActionSender,
     _long_running_actions_handler: JoinHandle<()>,
 
-    _notification_sx: Sender<Notification>,
+    notification_sx: Sender<Notification>,
     _notifications_handler: JoinHandle<()>,
 
     _movement_handler: JoinHandle<()>,
+    _history_handler: JoinHandle<()>,
+    _metrics_handler: Option<JoinHandle<()>>,
+    _reminder_handler: Option<JoinHandle<()>>,
+    _scheduler_handler: Option<JoinHandle<()>>,
+
+    #[cfg(target_os = "linux")]
+    _dbus_handler: JoinHandle<()>,
+
+    // note: the reordered positions are written once the keys stop, see
+    // schedule_positions_save; the profile they belong to is kept along
+    pending_positions_save: Option<(Instant, Option<String>)>,
 
     exited: Arc<AtomicBool>,
 }
 
 impl App {
-    pub fn new(ble_manager: BleManager, config: Config) -> Self {
-        let state = Arc::new(Mutex::new(State::new(config.clone())));
-        let notifications = Arc::new(Mutex::new(Vec::new()));
+    pub fn new(
+        ble_manager: BleManager,
+        config: Config,
+        metrics: Arc<Mutex<Metrics>>,
+        profile: Option<String>,
+    ) -> Self {
+        let history_profile = profile.clone();
+        let state = Arc::new(Mutex::new(State::new(config.clone(), profile)));
+        let notifications = Arc::new(Mutex::new(NotificationHistory::default()));
+        let height_samples = Arc::new(Mutex::new(HeightSamples::default()));
         let idasen = Arc::new(Mutex::new(None));
         let exited = Arc::new(AtomicBool::new(false));
 
         let notification_manager = NotificationManager::new(Arc::clone(&notifications));
-        let _notification_sx = notification_manager.get_sender();
+        let notification_sx = notification_manager.get_sender();
         let background_notification_sx = notification_manager.get_sender();
+        let metrics_notification_sx = notification_manager.get_sender();
+        let reminder_notification_sx = notification_manager.get_sender();
+        let scheduler_notification_sx = notification_manager.get_sender();
+        let history_notification_sx = notification_manager.get_sender();
+        #[cfg(target_os = "linux")]
+        let dbus_notification_sx = notification_manager.get_sender();
         let _notifications_handler = notification_manager.run();
 
-        let _movement_handler =
-            movement_job::MovementJob::new(Arc::clone(&idasen), Arc::clone(&state)).run();
+        let history_job = HistoryJob::new(
+            history_profile,
+            config.standing_threshold,
+            Arc::clone(&state),
+            Arc::clone(&height_samples),
+            history_notification_sx,
+        );
+        let history_sx = history_job.get_sender();
+        let _history_handler = history_job.run();
+
+        let _metrics_handler = config.metrics_address.clone().map(|address| {
+            MetricsJob::new(Arc::clone(&metrics), address, metrics_notification_sx).run()
+        });
 
         let background_job_executor = BackgroundJob::new(
-            idasen,
+            Arc::clone(&idasen),
             Arc::clone(&state),
             ble_manager,
             background_notification_sx,
+            Arc::clone(&metrics),
             Arc::clone(&exited),
         );
         let action_sx = background_job_executor.get_sender();
         let _long_running_actions_handler = background_job_executor.run();
 
-        action_sx.send(AppAction::Init(config.clone())).unwrap();
+        #[cfg(target_os = "linux")]
+        let dbus_job = DbusJob::new(Arc::clone(&state), action_sx.clone(), dbus_notification_sx);
+
+        let _movement_handler = movement_job::MovementJob::new(
+            idasen,
+            Arc::clone(&state),
+            metrics,
+            vec![
+                history_sx,
+                #[cfg(target_os = "linux")]
+                dbus_job.get_sender(),
+            ],
+        )
+        .run();
+
+        let has_reminders =
+            config.reminders.is_some() || config.profiles.iter().any(|p| p.reminders.is_some());
+        let _reminder_handler = if has_reminders {
+            Some(
+                ReminderJob::new(
+                    config.clone(),
+                    config.standing_threshold,
+                    Arc::clone(&state),
+                    action_sx.clone(),
+                    reminder_notification_sx,
+                )
+                .run(),
+            )
+        } else {
+            None
+        };
+
+        let _scheduler_handler = if config.schedules.is_empty() {
+            None
+        } else {
+            Some(
+                SchedulerJob::new(
+                    config.schedules.clone(),
+                    config
+                        .predefined_mac
+                        .as_ref()
+                        .and_then(|addr| BDAddr::from_str(addr).ok()),
+                    Arc::clone(&state),
+                    action_sx.clone(),
+                    scheduler_notification_sx,
+                )
+                .run(),
+            )
+        };
+
+        #[cfg(target_os = "linux")]
+        let _dbus_handler = dbus_job.run();
+
+        action_sx
+            .send(AppAction::Init(Box::new(config.clone())))
+            .unwrap();
+
+        if let Some(ref error) = config.load_error {
+            let _ = notification_sx.send(Notification::new(
+                NotificationType::Error,
+                format!("Invalid configuration, nothing will be saved: {}", error),
+            ));
+        }
+
+        let (keymap, keymap_errors) = Keymap::new(&config.keymap);
+        for error in keymap_errors {
+            let _ = notification_sx.send(Notification::new(
+                NotificationType::Error,
+                format!("Invalid keymap, using the default one: {}", error),
+            ));
+        }
+
+        let no_color = Theme::is_no_color(env::var_os("NO_COLOR").as_deref());
+        let (theme, theme_errors) = Theme::new(&config.theme, no_color);
+        for error in theme_errors {
+            let _ = notification_sx.send(Notification::new(
+                NotificationType::Error,
+                format!("Invalid theme: {}", error),
+            ));
+        }
 
         Self {
             state,
+            config,
+            keymap,
+            theme,
+
+            mouse_targets: Vec::new(),
+            layout_mode: LayoutMode::Grid,
+            last_click: None,
+            is_move_button_held: false,
 
             notifications,
+            height_samples,
 
             long_running_actions_sx: action_sx,
             _long_running_actions_handler,
 
-            _notification_sx,
+            notification_sx,
             _notifications_handler,
 
             _movement_handler,
+            _history_handler,
+            _metrics_handler,
+            _reminder_handler,
+            _scheduler_handler,
+
+            #[cfg(target_os = "linux")]
+            _dbus_handler,
+
+            pending_positions_save: None,
 
             exited,
         }
@@ -106,6 +335,7 @@ impl App {
         let events = EventEmitter::new(250);
         loop {
             terminal.draw(|frame| self.draw(frame))?;
+            self.ring_pending_bell()?;
             let ev = events.next()?;
             let state = self.get_current_state();
 
@@ -113,16 +343,50 @@ impl App {
                 continue;
             }
 
-            if !self.is_global_event(&ev) {
+            // note: the modal on top gets the input, overlays let global keys through
+            if let Some(modal) = state.top_modal() {
+                match modal.kind() {
+                    ModalKind::CommandPalette => self.handle_command_palette_event(ev),
+                    ModalKind::Help => self.handle_help_event(ev),
+                    ModalKind::PositionEdit => self.handle_position_edit_event(ev),
+                    ModalKind::ProfilePicker => self.handle_profile_picker_event(ev),
+                    ModalKind::ErgonomicsWizard => self.handle_ergonomics_event(ev),
+                    ModalKind::HeightInput => self.handle_height_input_event(ev),
+                    ModalKind::ExportRange => self.handle_export_range_event(ev),
+                    ModalKind::NotificationCentre => {
+                        if !self.is_global_event(&ev, KeymapScope::Notifications) {
+                            self.handle_notification_centre_event(ev)
+                        }
+                    }
+                    ModalKind::Schedules => {
+                        if !self.is_global_event(&ev, KeymapScope::Schedules) {
+                            self.handle_schedules_event(ev)
+                        }
+                    }
+                }
+            } else if let UIEvent::Click(ev) = ev {
+                self.handle_mouse_event(ev)
+            } else if !self.is_global_event(&ev, state.current_view.keymap_scope()) {
                 match state.current_view {
-                    Views::Connection => (),
+                    Views::Connection => self.handle_connections_event(ev),
                     Views::Movement => self.handle_movement_event(ev),
                     Views::DeviceList => self.handle_device_list_event(ev),
                     Views::SavedPositions => self.handle_positions_event(ev),
+                    Views::Statistics => self.handle_statistics_event(ev),
+                    Views::HeightChart => self.handle_chart_event(ev),
                 }
             }
 
+            if self
+                .pending_positions_save
+                .as_ref()
+                .is_some_and(|(since, _)| since.elapsed() >= POSITIONS_SAVE_DELAY)
+            {
+                self.save_pending_positions()
+            }
+
             if self.exited.load(std::sync::atomic::Ordering::Relaxed) {
+                self.save_pending_positions();
                 // fix: this should not have unwrap
                 return Ok(());
             }
@@ -140,6 +404,36 @@ impl App {
         }
     }
 
+    pub fn move_to_selected_position(&self) {
+        let state = self.get_current_state();
+        if state.connected_device.is_none() {
+            return;
+        }
+
+        if let Some(position) = state.positions_list.get_selected() {
+            self.start_action(AppAction::StartMoving(MovingDirection::ToHeight(
+                position.height,
+            )))
+        }
+    }
+
+    pub fn move_to_position_hotkey(&self, hotkey: u8) {
+        let state = self.get_current_state();
+        if state.connected_device.is_none() {
+            return;
+        }
+
+        let positions = state.positions_list.items();
+        if let Some(idx) = position_hotkeys(positions)
+            .iter()
+            .position(|k| *k == Some(hotkey))
+        {
+            self.start_action(AppAction::StartMoving(MovingDirection::ToHeight(
+                positions[idx].height,
+            )))
+        }
+    }
+
     pub fn move_position_selection(&self, action: SelectionMove) {
         match action {
             SelectionMove::Prev => self.state.lock().unwrap().positions_list.select_prev(),
@@ -147,6 +441,453 @@ impl App {
         }
     }
 
+    pub fn start_position_edit(&self, kind: PositionEditKind) {
+        let mut state = self.state.lock().unwrap();
+        let input = match kind {
+            PositionEditKind::Rename(idx) => state
+                .positions_list
+                .items()
+                .get(idx)
+                .map(|p| p.name.clone())
+                .unwrap_or_default(),
+            _ => String::new(),
+        };
+        state.open_modal(Modal::PositionEdit(PositionEdit {
+            kind,
+            input: TextInput::new(input),
+        }));
+    }
+
+    pub fn cancel_position_edit(&self) {
+        self.state
+            .lock()
+            .unwrap()
+            .close_modal(ModalKind::PositionEdit)
+    }
+
+    // note: the input is edited in place, the caller acts on the outcome
+    pub fn update_position_edit(&self, ev: &UIEvent) -> ModalOutcome {
+        match self
+            .state
+            .lock()
+            .unwrap()
+            .modal_mut(ModalKind::PositionEdit)
+        {
+            Some(Modal::PositionEdit(edit)) if edit.accepts_text() => {
+                InputDialog::handle_event(&mut edit.input, ev)
+            }
+            Some(_) => ConfirmDialog::handle_event(ev),
+            None => ModalOutcome::Cancelled,
+        }
+    }
+
+    pub fn confirm_position_edit(&mut self) {
+        let edit = match self.get_current_state().modal(ModalKind::PositionEdit) {
+            Some(Modal::PositionEdit(edit)) => edit.clone(),
+            _ => return,
+        };
+        let input = edit.input.value().trim().to_string();
+
+        if edit.accepts_text() && input.is_empty() {
+            return self.notify(
+                NotificationType::Warning,
+                "name cannot be empty".to_string(),
+            );
+        }
+
+        // note: the edited position may keep its own name
+        let renamed = match edit.kind {
+            PositionEditKind::Rename(idx) => Some(idx),
+            _ => None,
+        };
+        let is_name_taken = matches!(
+            edit.kind,
+            PositionEditKind::AddName
+                | PositionEditKind::SaveCurrent(_)
+                | PositionEditKind::Rename(_)
+        ) && self
+            .get_current_state()
+            .positions_list
+            .items()
+            .iter()
+            .enumerate()
+            .any(|(idx, p)| p.name == input && Some(idx) != renamed);
+        if is_name_taken {
+            return self.notify(
+                NotificationType::Warning,
+                format!("a position named '{}' already exists", input),
+            );
+        }
+
+        {
+            let mut state = self.state.lock().unwrap();
+            state.close_modal(ModalKind::PositionEdit);
+            match edit.kind {
+                PositionEditKind::AddName => {
+                    state.open_modal(Modal::PositionEdit(PositionEdit {
+                        kind: PositionEditKind::AddHeight(input),
+                        input: TextInput::default(),
+                    }));
+                    return;
+                }
+                PositionEditKind::AddHeight(name) => match state.height_format().parse(&input) {
+                    Ok(height) => state.positions_list.push(SavedPosition::new(name, height)),
+                    Err(e) => {
+                        state.open_modal(Modal::PositionEdit(PositionEdit {
+                            kind: PositionEditKind::AddHeight(name),
+                            input: edit.input,
+                        }));
+                        drop(state);
+                        return self.notify(NotificationType::Warning, e.to_string());
+                    }
+                },
+                PositionEditKind::SaveCurrent(height) => {
+                    state.positions_list.push(SavedPosition::new(input, height))
+                }
+                PositionEditKind::Rename(idx) => {
+                    if let Some(position) = state.positions_list.items_mut().get_mut(idx) {
+                        position.name = input
+                    }
+                }
+                PositionEditKind::Delete(idx) => {
+                    state.positions_list.remove(idx);
+                }
+            }
+        }
+
+        self.persist_positions()
+    }
+
+    pub fn move_selected_position(&mut self, action: SelectionMove) {
+        {
+            let mut state = self.state.lock().unwrap();
+            let (idx, len) = match state.positions_list.selected_index() {
+                Some(idx) => (idx, state.positions_list.items().len()),
+                None => return,
+            };
+            match action {
+                SelectionMove::Prev if idx > 0 => state.positions_list.swap(idx, idx - 1),
+                SelectionMove::Next if idx + 1 < len => state.positions_list.swap(idx, idx + 1),
+                _ => return,
+            }
+        }
+
+        self.schedule_positions_save()
+    }
+
+    // note: the configuration is updated right away, for profile switches
+    fn schedule_positions_save(&mut self) {
+        let state = self.get_current_state();
+        if self
+            .pending_positions_save
+            .as_ref()
+            .is_some_and(|(_, profile)| *profile != state.profile)
+        {
+            self.save_pending_positions()
+        }
+
+        self.config.set_positions_for(
+            state.profile.as_deref(),
+            state.positions_list.items().to_vec(),
+        );
+        self.pending_positions_save = Some((Instant::now(), state.profile));
+    }
+
+    fn save_pending_positions(&mut self) {
+        let Some((_, profile)) = self.pending_positions_save.take() else {
+            return;
+        };
+        let positions = self.config.positions_for(profile.as_deref());
+        let saved = self
+            .config
+            .ensure_writable()
+            .and_then(|_| Config::save_positions(profile.as_deref(), &positions));
+        if let Err(e) = saved {
+            self.notify(
+                NotificationType::Error,
+                format!("impossible to save the positions: {}", e),
+            )
+        }
+    }
+
+    fn persist_positions(&mut self) {
+        let state = self.get_current_state();
+        if self
+            .pending_positions_save
+            .as_ref()
+            .is_some_and(|(_, profile)| *profile == state.profile)
+        {
+            self.pending_positions_save = None;
+        }
+        let positions = state.positions_list.items().to_vec();
+        let saved = self
+            .config
+            .ensure_writable()
+            .and_then(|_| Config::save_positions(state.profile.as_deref(), &positions));
+        match saved {
+            Ok(()) => self
+                .config
+                .set_positions_for(state.profile.as_deref(), positions),
+            Err(e) => self.notify(
+                NotificationType::Error,
+                format!("impossible to save the positions: {}", e),
+            ),
+        }
+    }
+
+    pub fn update_state<T>(&self, update: impl FnOnce(&mut State) -> T) -> T {
+        update(&mut self.state.lock().unwrap())
+    }
+
+    pub fn open_ergonomics_wizard(&self) {
+        self.state
+            .lock()
+            .unwrap()
+            .open_modal(Modal::ErgonomicsWizard(ErgonomicsWizard::new()))
+    }
+
+    pub fn close_ergonomics_wizard(&mut self) {
+        self.state
+            .lock()
+            .unwrap()
+            .close_modal(ModalKind::ErgonomicsWizard);
+        self.remember_wizard_dismissed()
+    }
+
+    // note: a configuration that failed to load is left as it is
+    fn remember_wizard_dismissed(&mut self) {
+        if self.config.wizard_dismissed || self.config.ensure_writable().is_err() {
+            return;
+        }
+        match Config::save_wizard_dismissed() {
+            Ok(()) => self.config.wizard_dismissed = true,
+            Err(e) => self.notify(
+                NotificationType::Error,
+                format!("impossible to save the configuration: {}", e),
+            ),
+        }
+    }
+
+    // note: the input is edited in place, the caller acts on the outcome
+    pub fn update_ergonomics_wizard(&self, ev: &UIEvent) -> ModalOutcome {
+        match self
+            .state
+            .lock()
+            .unwrap()
+            .modal_mut(ModalKind::ErgonomicsWizard)
+        {
+            Some(Modal::ErgonomicsWizard(ErgonomicsWizard {
+                step: WizardStep::Result(_),
+                ..
+            })) => ConfirmDialog::handle_event(ev),
+            Some(Modal::ErgonomicsWizard(wizard)) => {
+                InputDialog::handle_event(&mut wizard.input, ev)
+            }
+            _ => ModalOutcome::Cancelled,
+        }
+    }
+
+    pub fn confirm_ergonomics_step(&self) {
+        let state = self.get_current_state();
+        let mut wizard = match state.modal(ModalKind::ErgonomicsWizard) {
+            Some(Modal::ErgonomicsWizard(wizard)) => wizard.clone(),
+            _ => return,
+        };
+
+        let input = wizard.input.value().trim();
+        let length = if input.is_empty() {
+            None
+        } else {
+            match height::parse_length(input, state.unit) {
+                Ok(length) if length > 0 && length <= MAX_BODY_HEIGHT => Some(length as u16),
+                Ok(_) => {
+                    return self.notify(NotificationType::Warning, "implausible length".to_string())
+                }
+                Err(e) => return self.notify(NotificationType::Warning, e.to_string()),
+            }
+        };
+
+        wizard.step = match (wizard.step, length) {
+            (WizardStep::BodyHeight, None) => return,
+            (WizardStep::BodyHeight, Some(body)) => {
+                wizard.measurements.body = body;
+                WizardStep::ElbowHeight
+            }
+            (WizardStep::ElbowHeight, elbow) => {
+                wizard.measurements.elbow = elbow;
+                WizardStep::ChairHeight
+            }
+            (WizardStep::ChairHeight, chair) => {
+                wizard.measurements.chair = chair;
+                WizardStep::Result(Recommendation::compute(&wizard.measurements))
+            }
+            (step @ WizardStep::Result(_), _) => step,
+        };
+        wizard.input.clear();
+
+        if let Some(Modal::ErgonomicsWizard(current)) = self
+            .state
+            .lock()
+            .unwrap()
+            .modal_mut(ModalKind::ErgonomicsWizard)
+        {
+            *current = wizard
+        }
+    }
+
+    pub fn save_recommended_positions(&mut self) {
+        {
+            let mut state = self.state.lock().unwrap();
+            let recommendation = match state.modal(ModalKind::ErgonomicsWizard) {
+                Some(Modal::ErgonomicsWizard(ErgonomicsWizard {
+                    step: WizardStep::Result(recommendation),
+                    ..
+                })) => *recommendation,
+                _ => return,
+            };
+            if !state.is_offset_known() {
+                drop(state);
+                return self.notify(
+                    NotificationType::Warning,
+                    "connect to the desk first, its height offset is needed".to_string(),
+                );
+            }
+            state.close_modal(ModalKind::ErgonomicsWizard);
+            let offset = state.height_format().offset;
+
+            for (name, surface_height) in [
+                (RECOMMENDED_SITTING_NAME, recommendation.sitting),
+                (RECOMMENDED_STANDING_NAME, recommendation.standing),
+            ] {
+                let (height, _) = ergonomics::fit_desk_range(surface_height, offset);
+                let existing = state
+                    .positions_list
+                    .items_mut()
+                    .iter_mut()
+                    .find(|p| p.name == name);
+                match existing {
+                    Some(position) => position.height = height,
+                    None => state
+                        .positions_list
+                        .push(SavedPosition::new(name.to_string(), height)),
+                }
+            }
+        }
+
+        self.persist_positions();
+        self.remember_wizard_dismissed()
+    }
+
+    pub fn toggle_height_reference(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.height_reference = state.height_reference.toggle()
+    }
+
+    pub fn open_height_input(&self) {
+        self.state
+            .lock()
+            .unwrap()
+            .open_modal(Modal::HeightInput(TextInput::default()))
+    }
+
+    pub fn close_height_input(&self) {
+        self.state
+            .lock()
+            .unwrap()
+            .close_modal(ModalKind::HeightInput)
+    }
+
+    // note: the input is edited in place, the caller acts on the outcome
+    pub fn update_height_input(&self, ev: &UIEvent) -> ModalOutcome {
+        match self.state.lock().unwrap().modal_mut(ModalKind::HeightInput) {
+            Some(Modal::HeightInput(input)) => InputDialog::handle_event(input, ev),
+            _ => ModalOutcome::Cancelled,
+        }
+    }
+
+    pub fn confirm_height_input(&self) {
+        let state = self.get_current_state();
+        let input = match state.modal(ModalKind::HeightInput) {
+            Some(Modal::HeightInput(input)) => input,
+            _ => return,
+        };
+
+        match state.height_format().parse(input.value()) {
+            Ok(height) => {
+                self.close_height_input();
+                self.trigger_movement(MovingDirection::ToHeight(height))
+            }
+            Err(e) => self.notify(NotificationType::Warning, e.to_string()),
+        }
+    }
+
+    fn notify(&self, kind: NotificationType, message: String) {
+        let _ = self.notification_sx.send(Notification::new(kind, message));
+    }
+
+    pub fn notification_history(&self) -> NotificationHistory {
+        self.notifications.lock().unwrap().clone()
+    }
+
+    pub fn height_samples(&self) -> Arc<Mutex<HeightSamples>> {
+        Arc::clone(&self.height_samples)
+    }
+
+    pub fn dismiss_notifications(&self) {
+        self.notifications.lock().unwrap().dismiss_all()
+    }
+
+    pub fn clear_notifications(&self) {
+        self.notifications.lock().unwrap().clear();
+        self.set_notification_centre_scroll(0)
+    }
+
+    pub fn toggle_notification_centre(&self) {
+        self.state
+            .lock()
+            .unwrap()
+            .toggle_modal(Modal::NotificationCentre(NotificationCentre::default()))
+    }
+
+    // note: the upper bound depends on the overlay size, it is applied when drawing
+    pub fn scroll_notification_centre(&self, lines: isize) {
+        if let Some(Modal::NotificationCentre(centre)) = self
+            .state
+            .lock()
+            .unwrap()
+            .modal_mut(ModalKind::NotificationCentre)
+        {
+            centre.scroll = centre.scroll.saturating_add_signed(lines)
+        }
+    }
+
+    pub fn set_notification_centre_scroll(&self, scroll: usize) {
+        if let Some(Modal::NotificationCentre(centre)) = self
+            .state
+            .lock()
+            .unwrap()
+            .modal_mut(ModalKind::NotificationCentre)
+        {
+            centre.scroll = scroll
+        }
+    }
+
+    pub fn move_schedule_selection(&self, action: SelectionMove) {
+        match action {
+            SelectionMove::Prev => self.state.lock().unwrap().schedules.select_prev(),
+            SelectionMove::Next => self.state.lock().unwrap().schedules.select_next(),
+        }
+    }
+
+    pub fn toggle_selected_schedule_skip(&self) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(idx) = state.schedules.selected_index() {
+            if let Some(status) = state.schedules.items_mut().get_mut(idx) {
+                status.skip_next = !status.skip_next
+            }
+        }
+    }
+
     pub fn trigger_movement(&self, mov: MovingDirection) {
         self.start_action(AppAction::StartMoving(mov))
     }
@@ -160,7 +901,7 @@ impl App {
     pub fn move_view_focus_next(&self) {
         let mut state = self.state.lock().unwrap();
         state.current_view =
-            Self::get_view_by_index((Self::get_index_by_view(state.current_view) + 1) % 4)
+            Self::get_view_by_index((Self::get_index_by_view(state.current_view) + 1) % VIEWS_COUNT)
     }
 
     pub fn move_view_focus_prev(&self) {
@@ -169,44 +910,501 @@ impl App {
         state.current_view = Self::get_view_by_index(if current_index > 0 {
             current_index - 1
         } else {
-            3
+            VIEWS_COUNT - 1
         })
     }
 
+    pub fn move_statistics_period(&self, action: SelectionMove) {
+        let mut state = self.state.lock().unwrap();
+        state.statistics_period = match action {
+            SelectionMove::Prev => state.statistics_period.prev(),
+            SelectionMove::Next => state.statistics_period.next(),
+        }
+    }
+
+    pub fn zoom_chart_in(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.chart_window = state.chart_window.zoom_in()
+    }
+
+    pub fn zoom_chart_out(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.chart_window = state.chart_window.zoom_out()
+    }
+
+    pub fn toggle_export_format(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.export_format = state.export_format.toggle()
+    }
+
+    // note: the range of the selected period is proposed
+    pub fn open_export_range(&self) {
+        let mut state = self.state.lock().unwrap();
+        let today = Local::now().date_naive();
+        let range = ExportRange {
+            from: state.statistics_period.start_date(today),
+            to: today,
+        };
+        state.open_modal(Modal::ExportRange(TextInput::new(range.format())))
+    }
+
+    pub fn close_export_range(&self) {
+        self.state
+            .lock()
+            .unwrap()
+            .close_modal(ModalKind::ExportRange)
+    }
+
+    pub fn update_export_range(&self, ev: &UIEvent) -> ModalOutcome {
+        match self.state.lock().unwrap().modal_mut(ModalKind::ExportRange) {
+            Some(Modal::ExportRange(input)) => InputDialog::handle_event(input, ev),
+            _ => ModalOutcome::Cancelled,
+        }
+    }
+
+    pub fn confirm_export_range(&self) {
+        let state = self.get_current_state();
+        let input = match state.modal(ModalKind::ExportRange) {
+            Some(Modal::ExportRange(input)) => input,
+            _ => return,
+        };
+
+        match input.value().parse::<ExportRange>() {
+            Ok(range) => {
+                self.close_export_range();
+                self.export_statistics(range)
+            }
+            Err(e) => self.notify(NotificationType::Warning, e),
+        }
+    }
+
+    pub fn export_statistics(&self, range: ExportRange) {
+        let state = self.get_current_state();
+        ExportJob::new(
+            state.profile,
+            self.config.standing_threshold,
+            range,
+            state.export_format,
+            state.unit,
+            self.notification_sx.clone(),
+        )
+        .run();
+    }
+
+    /// Name of the active profile, None when no profile is configured at all.
+    pub fn profile_label(&self) -> Option<String> {
+        if self.config.profiles.is_empty() {
+            return None;
+        }
+        Some(
+            self.get_current_state()
+                .profile
+                .unwrap_or_else(|| DEFAULT_PROFILE_LABEL.to_string()),
+        )
+    }
+
+    // note: None, the default profile, comes first
+    fn profiles(&self) -> Vec<Option<String>> {
+        std::iter::once(None)
+            .chain(self.config.profile_names().into_iter().map(Some))
+            .collect()
+    }
+
+    fn current_profile_index(&self, profiles: &[Option<String>]) -> usize {
+        let state = self.state.lock().unwrap();
+        profiles
+            .iter()
+            .position(|p| *p == state.profile)
+            .unwrap_or(0)
+    }
+
+    pub fn switch_profile(&self, action: SelectionMove) {
+        let profiles = self.profiles();
+        let current = self.current_profile_index(&profiles);
+        self.select_profile(match action {
+            SelectionMove::Prev => (current + profiles.len() - 1) % profiles.len(),
+            SelectionMove::Next => (current + 1) % profiles.len(),
+        })
+    }
+
+    pub fn open_profile_picker(&self) {
+        let profiles = self.profiles();
+        let current = self.current_profile_index(&profiles);
+        let labels = profiles
+            .into_iter()
+            .map(|p| p.unwrap_or_else(|| DEFAULT_PROFILE_LABEL.to_string()))
+            .collect();
+        self.state
+            .lock()
+            .unwrap()
+            .open_modal(Modal::ProfilePicker(Picker::new(labels, current)))
+    }
+
+    pub fn close_profile_picker(&self) {
+        self.state
+            .lock()
+            .unwrap()
+            .close_modal(ModalKind::ProfilePicker)
+    }
+
+    // note: the selection is moved in place, the caller acts on the outcome
+    pub fn update_profile_picker(&self, ev: &UIEvent) -> ModalOutcome {
+        match self
+            .state
+            .lock()
+            .unwrap()
+            .modal_mut(ModalKind::ProfilePicker)
+        {
+            Some(Modal::ProfilePicker(picker)) => PickerDialog::handle_event(picker, ev),
+            _ => ModalOutcome::Cancelled,
+        }
+    }
+
+    pub fn confirm_profile_picker(&self) {
+        let selected = {
+            let mut state = self.state.lock().unwrap();
+            let selected = match state.modal(ModalKind::ProfilePicker) {
+                Some(Modal::ProfilePicker(picker)) => Some(picker.selected()),
+                _ => None,
+            };
+            state.close_modal(ModalKind::ProfilePicker);
+            selected
+        };
+        if let Some(selected) = selected {
+            self.select_profile(selected)
+        }
+    }
+
+    fn select_profile(&self, idx: usize) {
+        let profiles = self.profiles();
+        let mut state = self.state.lock().unwrap();
+        let profile = match profiles.get(idx) {
+            Some(profile) if *profile != state.profile => profile.clone(),
+            _ => return,
+        };
+
+        state
+            .positions_list
+            .set(self.config.positions_for(profile.as_deref()));
+        state.reminder = self
+            .config
+            .reminders_for(profile.as_deref())
+            .map(|_| ReminderStatus::new());
+        state.close_modal(ModalKind::PositionEdit);
+        state.statistics = None;
+        state.profile = profile;
+    }
+
+    pub fn toggle_schedules(&self) {
+        self.state.lock().unwrap().toggle_modal(Modal::Schedules)
+    }
+
+    pub fn toggle_zoom(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.is_zoomed = !state.is_zoomed
+    }
+
+    pub fn toggle_reminders(&self) {
+        if let Some(ref mut reminder) = self.state.lock().unwrap().reminder {
+            reminder.enabled = !reminder.enabled
+        }
+    }
+
+    pub fn open_help(&self) {
+        self.state
+            .lock()
+            .unwrap()
+            .open_modal(Modal::Help(HelpOverlay::default()))
+    }
+
+    pub fn close_help(&self) {
+        self.state.lock().unwrap().close_modal(ModalKind::Help)
+    }
+
+    pub fn edit_help_search(&self, ev: &KeyEvent) {
+        if let Some(Modal::Help(help)) = self.state.lock().unwrap().modal_mut(ModalKind::Help) {
+            let before = help.search.value().to_string();
+            help.search.handle_key(ev);
+            if help.search.value() != before {
+                help.scroll = 0
+            }
+        }
+    }
+
+    pub fn clear_help_search(&self) {
+        if let Some(Modal::Help(help)) = self.state.lock().unwrap().modal_mut(ModalKind::Help) {
+            help.search.clear();
+            help.scroll = 0
+        }
+    }
+
+    // note: the upper bound depends on the overlay size, it is applied when drawing
+    pub fn scroll_help(&self, lines: isize) {
+        if let Some(Modal::Help(help)) = self.state.lock().unwrap().modal_mut(ModalKind::Help) {
+            help.scroll = help.scroll.saturating_add_signed(lines)
+        }
+    }
+
+    pub fn set_help_scroll(&self, scroll: usize) {
+        if let Some(Modal::Help(help)) = self.state.lock().unwrap().modal_mut(ModalKind::Help) {
+            help.scroll = scroll
+        }
+    }
+
+    pub fn set_unit(&self, unit: HeightUnit) {
+        self.state.lock().unwrap().unit = unit
+    }
+
+    pub fn open_command_palette(&self) {
+        self.state
+            .lock()
+            .unwrap()
+            .open_modal(Modal::CommandPalette(CommandPalette::default()))
+    }
+
+    pub fn close_command_palette(&self) {
+        self.state
+            .lock()
+            .unwrap()
+            .close_modal(ModalKind::CommandPalette)
+    }
+
+    pub fn edit_command_palette_input(&self, ev: &KeyEvent) {
+        if let Some(Modal::CommandPalette(palette)) = self
+            .state
+            .lock()
+            .unwrap()
+            .modal_mut(ModalKind::CommandPalette)
+        {
+            let before = palette.input.value().to_string();
+            palette.input.handle_key(ev);
+            if palette.input.value() != before {
+                palette.selected = 0
+            }
+        }
+    }
+
+    pub fn move_command_palette_selection(&self, action: SelectionMove) {
+        let state = self.get_current_state();
+        let Some(Modal::CommandPalette(palette)) = state.modal(ModalKind::CommandPalette) else {
+            return;
+        };
+        let count = palette::filter(self.palette_entries(&state), palette.input.value()).len();
+        if count == 0 {
+            return;
+        }
+
+        let selected = match action {
+            SelectionMove::Prev => (palette.selected + count - 1) % count,
+            SelectionMove::Next => (palette.selected + 1) % count,
+        };
+        if let Some(Modal::CommandPalette(palette)) = self
+            .state
+            .lock()
+            .unwrap()
+            .modal_mut(ModalKind::CommandPalette)
+        {
+            palette.selected = selected
+        }
+    }
+
+    pub fn confirm_command_palette(&mut self) {
+        let state = self.get_current_state();
+        let Some(Modal::CommandPalette(palette)) = state.modal(ModalKind::CommandPalette) else {
+            return;
+        };
+        let entry = palette::filter(self.palette_entries(&state), palette.input.value())
+            .into_iter()
+            .nth(palette.selected);
+
+        self.close_command_palette();
+        if let Some(entry) = entry {
+            self.run_command(entry.command)
+        }
+    }
+
+    /// Everything the command palette offers in the given state.
+    pub fn palette_entries(&self, state: &State) -> Vec<PaletteEntry> {
+        let mut entries = Vec::new();
+        let format = state.height_format();
+
+        if state.connected_device.is_some() {
+            entries.extend(state.positions_list.items().iter().map(|p| {
+                PaletteEntry::new(
+                    format!("move to {} ({})", p.name, format.format(p.height)),
+                    Command::Background(AppAction::StartMoving(MovingDirection::ToHeight(
+                        p.height,
+                    ))),
+                )
+            }));
+            entries.push(PaletteEntry::new("move to height...", Command::GoToHeight));
+            entries.push(PaletteEntry::new(
+                "stop the desk",
+                Command::Background(AppAction::StartMoving(MovingDirection::Stop)),
+            ));
+            if let Some(ref position) = state.position {
+                entries.push(PaletteEntry::new(
+                    "save the current height as a position",
+                    Command::SaveCurrent(position.position),
+                ));
+            }
+        }
+
+        entries.extend(state.devices_list.items().iter().map(|device| {
+            PaletteEntry::new(
+                format!("connect to {}", device.render_row()),
+                Command::Background(AppAction::ConnectDevice(device.peripheral.address())),
+            )
+        }));
+        entries.push(PaletteEntry::new(
+            "rescan devices",
+            Command::Background(AppAction::UpdateDevicesList),
+        ));
+
+        entries.extend((0..VIEWS_COUNT).map(|idx| {
+            let view = Self::get_view_by_index(idx);
+            PaletteEntry::new(format!("open {}", view.label()), Command::Focus(view))
+        }));
+        entries.push(PaletteEntry::new(
+            "open schedules",
+            Command::ToggleSchedules,
+        ));
+        entries.push(PaletteEntry::new(
+            if state.is_zoomed {
+                "show all sections"
+            } else {
+                "zoom the current section"
+            },
+            Command::ToggleZoom,
+        ));
+
+        entries.push(PaletteEntry::new("add a position", Command::AddPosition));
+        entries.push(PaletteEntry::new("ergonomic wizard", Command::Wizard));
+        entries.push(PaletteEntry::new(
+            format!("export statistics ({})...", state.export_format.extension()),
+            Command::Export,
+        ));
+
+        entries.extend(
+            [
+                HeightUnit::Centimeters,
+                HeightUnit::Millimeters,
+                HeightUnit::Inches,
+            ]
+            .into_iter()
+            .filter(|unit| *unit != state.unit)
+            .map(|unit| {
+                PaletteEntry::new(
+                    format!("use {} units", unit.suffix()),
+                    Command::SetUnit(unit),
+                )
+            }),
+        );
+        entries.push(PaletteEntry::new(
+            format!("show {} heights", state.height_reference.toggle().label()),
+            Command::ToggleReference,
+        ));
+
+        if let Some(ref reminder) = state.reminder {
+            entries.push(PaletteEntry::new(
+                if reminder.enabled {
+                    "pause reminders"
+                } else {
+                    "start reminders"
+                },
+                Command::ToggleReminders,
+            ));
+        }
+        if !self.config.profiles.is_empty() {
+            entries.push(PaletteEntry::new("switch profile...", Command::PickProfile));
+        }
+
+        entries.push(PaletteEntry::new(
+            "quit",
+            Command::Background(AppAction::TearDown),
+        ));
+        entries
+    }
+
+    pub fn run_command(&mut self, command: Command) {
+        match command {
+            Command::Background(action) => self.start_action(action),
+            Command::Focus(view) => {
+                self.state.lock().unwrap().close_modal(ModalKind::Schedules);
+                self.focus_view(view)
+            }
+            Command::ToggleSchedules => self.toggle_schedules(),
+            Command::ToggleZoom => self.toggle_zoom(),
+            Command::GoToHeight => self.open_height_input(),
+            Command::SetUnit(unit) => self.set_unit(unit),
+            Command::ToggleReference => self.toggle_height_reference(),
+            Command::ToggleReminders => self.toggle_reminders(),
+            Command::PickProfile => self.open_profile_picker(),
+            Command::AddPosition => self.start_position_edit(PositionEditKind::AddName),
+            Command::SaveCurrent(height) => {
+                self.start_position_edit(PositionEditKind::SaveCurrent(height))
+            }
+            Command::Wizard => self.open_ergonomics_wizard(),
+            Command::Export => self.open_export_range(),
+        }
+    }
+
+    fn ring_pending_bell(&self) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        if state.pending_bell {
+            state.pending_bell = false;
+            let mut stdout = io::stdout();
+            stdout.write_all(b"\x07")?;
+            stdout.flush()?;
+        }
+        Ok(())
+    }
+
     pub fn get_current_state(&self) -> State {
         self.state.lock().unwrap().clone()
     }
 
     fn draw(&mut self, frame: &mut Frame) {
-        let layout = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([Constraint::Min(1), Constraint::Max(3)].as_ref())
-            .split(frame.size());
-
-        let (view_area, bar_area) = (layout[0], layout[1]);
-
-        let layout = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-            .split(view_area);
-        let (view_left_area, view_right_area) = (layout[0], layout[1]);
-
-        let layout = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-            .split(view_left_area);
-        let (view_left_top_area, view_left_bottom_area) = (layout[0], layout[1]);
-
-        let layout = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-            .split(view_right_area);
-        let (view_right_top_area, view_right_bottom_area) = (layout[0], layout[1]);
-
-        self.draw_connections(frame, view_left_top_area, bar_area);
-        self.draw_movement(frame, view_right_top_area, bar_area);
-        self.draw_positions(frame, view_left_bottom_area, bar_area);
-        self.draw_device_list(frame, view_right_bottom_area, bar_area);
+        self.mouse_targets.clear();
+
+        let state = self.get_current_state();
+        let layout = ScreenLayout::new(frame.size(), state.current_view, state.is_zoomed);
+        let (view_area, bar_area) = (layout.view_area, layout.bar_area);
+        self.layout_mode = layout.mode;
+
+        if let Some(tabs_area) = layout.tabs_area {
+            self.draw_tabs(frame, tabs_area, state.current_view);
+        }
+
+        for (view, area) in layout.sections {
+            match view {
+                Views::Connection => self.draw_connections(frame, area, bar_area),
+                Views::Movement => self.draw_movement(frame, area, bar_area),
+                Views::SavedPositions => self.draw_positions(frame, area, bar_area),
+                Views::DeviceList => self.draw_device_list(frame, area, bar_area),
+                Views::Statistics => self.draw_statistics(frame, area, bar_area),
+                Views::HeightChart => self.draw_chart(frame, area, bar_area),
+            }
+        }
+
+        // note: the bottom one first, so the one on top is drawn over the others
+        for modal in &state.modals {
+            match modal {
+                Modal::Schedules => self.draw_schedules(frame, view_area, bar_area),
+                Modal::PositionEdit(edit) => self.draw_position_edit(frame, bar_area, edit),
+                Modal::HeightInput(input) => self.draw_height_input(frame, bar_area, input),
+                Modal::ExportRange(input) => self.draw_export_range(frame, bar_area, input),
+                Modal::ErgonomicsWizard(wizard) => self.draw_ergonomics(frame, bar_area, wizard),
+                Modal::ProfilePicker(picker) => self.draw_profile_picker(frame, bar_area, picker),
+                Modal::NotificationCentre(centre) => {
+                    self.draw_notification_centre(frame, view_area, bar_area, centre)
+                }
+                Modal::Help(help) => self.draw_help(frame, view_area, bar_area, help),
+                Modal::CommandPalette(palette) => {
+                    self.draw_command_palette(frame, view_area, bar_area, palette)
+                }
+            }
+        }
 
         if let Status::Freezed(reason) | Status::Background(reason) =
             self.get_current_state().status
@@ -214,13 +1412,40 @@ impl App {
             self.draw_freezing_message(frame, reason)
         }
 
-        let notifications = if let Ok(list) = self.notifications.try_lock() {
-            list.clone()
+        let notifications = if let Ok(history) = self.notifications.try_lock() {
+            history.visible()
         } else {
             Vec::new()
         };
 
-        NotificationList::draw(frame, notifications)
+        NotificationList::draw(frame, notifications, &self.theme)
+    }
+
+    // note: tabs that do not fit are left out
+    fn draw_tabs(&mut self, frame: &mut Frame, area: Rect, current_view: Views) {
+        let mut x = area.x;
+        for idx in 0..VIEWS_COUNT {
+            let view = Self::get_view_by_index(idx);
+            let label = format!(" {} ", view.label());
+            let width = label.chars().count() as u16;
+            if area.height == 0 || x + width > area.right() {
+                break;
+            }
+
+            let tab_area = Rect::new(x, area.y, width, 1);
+            let style = if view == current_view {
+                self.theme.focus_border().reversed()
+            } else {
+                self.theme.accent().dim()
+            };
+            frame.render_widget(Paragraph::new(label).style(style), tab_area);
+            self.register_mouse_target(MouseTarget::View(view), tab_area);
+            x += width + TABS_GAP;
+        }
+    }
+
+    pub fn is_compact(&self) -> bool {
+        self.layout_mode == LayoutMode::Compact
     }
 
     fn draw_freezing_message(&self, frame: &mut Frame, reason: String) {
@@ -237,17 +1462,141 @@ impl App {
         );
     }
 
-    // fix: this is ugly since it has side effects
-    fn is_global_event(&mut self, ev: &UIEvent) -> bool {
-        if let UIEvent::KeyPress(ev) = ev {
-            match ev.code {
-                KeyCode::Char('q') => self.start_action(AppAction::TearDown),
-                KeyCode::Tab => self.move_view_focus_next(),
-                KeyCode::BackTab => self.move_view_focus_prev(),
-                _ => (),
+    pub fn register_mouse_target(&mut self, target: MouseTarget, area: Rect) {
+        self.mouse_targets.push((target, area))
+    }
+
+    fn mouse_target_at(&self, column: u16, row: u16) -> Option<(MouseTarget, Rect)> {
+        self.mouse_targets
+            .iter()
+            .rev()
+            .find(|(_, area)| {
+                column >= area.x && column < area.right() && row >= area.y && row < area.bottom()
+            })
+            .copied()
+    }
+
+    fn handle_mouse_event(&mut self, ev: MouseEvent) {
+        let target = self.mouse_target_at(ev.column, ev.row);
+        match (ev.kind, target) {
+            (MouseEventKind::Down(MouseButton::Left), Some((target, area))) => {
+                self.handle_click(target, area, ev.row)
+            }
+            // note: the release can happen outside of the button
+            (MouseEventKind::Up(MouseButton::Left), _) if self.is_move_button_held => {
+                self.is_move_button_held = false;
+                self.trigger_movement(MovingDirection::Stop)
+            }
+            (
+                MouseEventKind::ScrollUp,
+                Some((MouseTarget::View(view) | MouseTarget::ListRows(view, _), _)),
+            ) => self.scroll_list(view, SelectionMove::Prev),
+            (
+                MouseEventKind::ScrollDown,
+                Some((MouseTarget::View(view) | MouseTarget::ListRows(view, _), _)),
+            ) => self.scroll_list(view, SelectionMove::Next),
+            _ => (),
+        }
+    }
+
+    fn handle_click(&mut self, target: MouseTarget, area: Rect, row: u16) {
+        match target {
+            MouseTarget::View(view) => self.focus_view(view),
+            MouseTarget::ListRows(view, offset) => {
+                self.focus_view(view);
+                let idx = offset + (row - area.y) as usize;
+                if !self.select_list_item(view, idx) {
+                    return;
+                }
+
+                let now = Instant::now();
+                let is_double_click = matches!(
+                    self.last_click,
+                    Some((at, v, i)) if v == view && i == idx
+                        && now.duration_since(at) <= DOUBLE_CLICK_INTERVAL
+                );
+                if is_double_click {
+                    self.last_click = None;
+                    match view {
+                        Views::DeviceList => self.connect_selected(),
+                        Views::SavedPositions => self.move_to_selected_position(),
+                        _ => (),
+                    }
+                } else {
+                    self.last_click = Some((now, view, idx));
+                }
             }
+            MouseTarget::MoveButton(button) => {
+                self.focus_view(Views::Movement);
+                match button {
+                    MoveButton::Up => {
+                        self.is_move_button_held = true;
+                        self.trigger_movement(MovingDirection::Up)
+                    }
+                    MoveButton::Down => {
+                        self.is_move_button_held = true;
+                        self.trigger_movement(MovingDirection::Down)
+                    }
+                    MoveButton::Stop => self.trigger_movement(MovingDirection::Stop),
+                }
+            }
+        }
+    }
+
+    fn focus_view(&self, view: Views) {
+        self.state.lock().unwrap().current_view = view
+    }
+
+    fn select_list_item(&self, view: Views, idx: usize) -> bool {
+        let mut state = self.state.lock().unwrap();
+        match view {
+            Views::DeviceList => state.devices_list.select(idx),
+            Views::SavedPositions => state.positions_list.select(idx),
+            _ => false,
+        }
+    }
+
+    fn scroll_list(&self, view: Views, action: SelectionMove) {
+        match view {
+            Views::DeviceList => self.move_device_selection(action),
+            Views::SavedPositions => self.move_position_selection(action),
+            // note: scrolling up zooms in
+            Views::HeightChart => match action {
+                SelectionMove::Prev => self.zoom_chart_in(),
+                SelectionMove::Next => self.zoom_chart_out(),
+            },
+            _ => (),
+        }
+    }
+
+    pub fn keymap(&self) -> &Keymap {
+        &self.keymap
+    }
+
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    // fix: this is ugly since it has side effects
+    fn is_global_event(&mut self, ev: &UIEvent, scope: KeymapScope) -> bool {
+        let UIEvent::KeyPress(ev) = ev else {
+            return false;
+        };
+
+        match self.keymap.global_action(scope, ev) {
+            Some(Action::Quit) => self.start_action(AppAction::TearDown),
+            Some(Action::NextView) => self.move_view_focus_next(),
+            Some(Action::PrevView) => self.move_view_focus_prev(),
+            Some(Action::ToggleSchedules) => self.toggle_schedules(),
+            Some(Action::CommandPalette) => self.open_command_palette(),
+            Some(Action::Help) => self.open_help(),
+            Some(Action::Notifications) => self.toggle_notification_centre(),
+            Some(Action::DismissNotifications) => self.dismiss_notifications(),
+            Some(Action::ToggleZoom) => self.toggle_zoom(),
+            Some(Action::GoToPosition(hotkey)) => self.move_to_position_hotkey(hotkey),
+            _ => return false,
         }
-        false
+        true
     }
 
     fn get_view_by_index(idx: usize) -> Views {
@@ -256,6 +1605,8 @@ impl App {
             1 => Views::Movement,
             2 => Views::SavedPositions,
             3 => Views::DeviceList,
+            4 => Views::Statistics,
+            5 => Views::HeightChart,
             _ => unreachable!(),
         }
     }
@@ -266,6 +1617,8 @@ impl App {
             Views::Movement => 1,
             Views::SavedPositions => 2,
             Views::DeviceList => 3,
+            Views::Statistics => 4,
+            Views::HeightChart => 5,
         }
     }
 }
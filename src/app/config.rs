@@ -1,17 +1,224 @@
-use std::fs::File;
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 use dirs::home_dir;
 use serde::{Deserialize, Serialize};
+use serde_json::{ser::PrettyFormatter, Map, Serializer, Value};
 
-use super::state::SavedPosition;
+use crate::tui::theme::ThemeConfig;
+
+use super::{
+    height::{deserialize_height, deserialize_offsets, HeightReference, HeightUnit},
+    keymap::KeyList,
+    state::SavedPosition,
+    zone::{HeightZone, DEFAULT_STANDING_THRESHOLD},
+};
 
 const CONFIG_PATH: &str = ".idasen-tui.json";
+const DEFAULT_INDENT: &str = "  ";
+const MAX_SYMLINKS: usize = 40;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("home directory not found")]
+    HomeNotFound,
+
+    #[error("configuration file is not a JSON object")]
+    NotAnObject,
+
+    #[error("profile '{0}' not found")]
+    ProfileNotFound(String),
+
+    #[error("saved position '{0}' is defined twice")]
+    DuplicatePosition(String),
+
+    #[error("profile name '{0}' must only contain letters, digits, '-' and '_'")]
+    InvalidProfileName(String),
+
+    #[error("profile '{0}' is defined twice (names are compared ignoring case)")]
+    DuplicateProfile(String),
+
+    #[error("schedule position '{0}' is not a saved position")]
+    UnknownSchedulePosition(String),
+
+    #[error("reminder '{0}' must be greater than 0")]
+    ZeroReminderDuration(&'static str),
+
+    #[error("the configuration file could not be loaded, changes are not saved")]
+    ReadOnly,
+
+    #[error("invalid configuration file: {0}")]
+    Parse(#[from] serde_json::Error),
+
+    #[error("io error: {0}")]
+    Io(#[from] io::Error),
+}
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct Config {
     // note: this is needed because of the way Serde is used in btleplug
     pub predefined_mac: Option<String>,
+    // note: positions and reminders of the default profile
+    #[serde(default)]
+    pub saved_positions: Vec<SavedPosition>,
+    // note: used to display heights and as default for typed ones
+    #[serde(default)]
+    pub unit: HeightUnit,
+    // note: "desk" or "surface", the latter adds the offset of the connected desk
+    #[serde(default)]
+    pub height_reference: HeightReference,
+    // note: desk MAC address to the distance between the desk and the working surface
+    #[serde(default, deserialize_with = "deserialize_offsets")]
+    pub height_offsets: HashMap<String, i16>,
+    // note: heights from this value on count as standing
+    #[serde(
+        default = "default_standing_threshold",
+        deserialize_with = "deserialize_height"
+    )]
+    pub standing_threshold: u16,
+    // note: e.g. "127.0.0.1:9877", metrics are not exposed when missing
+    #[serde(default)]
+    pub metrics_address: Option<String>,
+    // note: reminders are disabled when this section is missing
+    #[serde(default)]
+    pub reminders: Option<ReminderConfig>,
+    #[serde(default)]
+    pub schedules: Vec<ScheduleConfig>,
+    #[serde(default)]
+    pub profiles: Vec<ProfileConfig>,
+    // note: profile used when --profile is missing, the default one when not set
+    #[serde(default)]
+    pub default_profile: Option<String>,
+    // note: section ("global", "movement", ...) to action to key(s), e.g. {"movement": {"up": "k"}}
+    #[serde(default)]
+    pub keymap: HashMap<String, HashMap<String, KeyList>>,
+    // note: colors are disabled anyway when NO_COLOR is set
+    #[serde(default)]
+    pub theme: ThemeConfig,
+    // note: disabling it gives the terminal text selection back
+    #[serde(default = "default_mouse")]
+    pub mouse: bool,
+    // note: the ergonomics wizard opens on launch while no position is saved, until dismissed
+    #[serde(default)]
+    pub wizard_dismissed: bool,
+    // note: set when the file could not be loaded, nothing is written back then
+    #[serde(skip)]
+    pub load_error: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ProfileConfig {
+    pub name: String,
+    #[serde(default)]
     pub saved_positions: Vec<SavedPosition>,
+    #[serde(default)]
+    pub reminders: Option<ReminderConfig>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ScheduleConfig {
+    // note: "daily", "weekdays", "weekends" or e.g. "mon,wed,fri"
+    pub days: String,
+    // note: local time, HH:MM
+    pub time: String,
+    // note: name of the saved position to move to
+    pub position: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ReminderConfig {
+    #[serde(default = "default_sitting_minutes")]
+    pub sitting_minutes: u64,
+    #[serde(default = "default_standing_minutes")]
+    pub standing_minutes: u64,
+    // note: ring the terminal bell together with the notification
+    #[serde(default)]
+    pub bell: bool,
+    // note: move to the matching saved position once the grace period is over
+    #[serde(default)]
+    pub auto_move: bool,
+    #[serde(default = "default_grace_seconds")]
+    pub grace_seconds: u64,
+    // note: when missing, the first saved position in the right zone is used
+    #[serde(default)]
+    pub sitting_position: Option<String>,
+    #[serde(default)]
+    pub standing_position: Option<String>,
+}
+
+impl ReminderConfig {
+    pub fn limit_for(&self, zone: HeightZone) -> Duration {
+        let minutes = match zone {
+            HeightZone::Sitting => self.sitting_minutes,
+            HeightZone::Standing => self.standing_minutes,
+        };
+        // note: a huge value just never reminds
+        Duration::from_secs(minutes.saturating_mul(60))
+    }
+
+    pub fn grace_period(&self) -> Duration {
+        Duration::from_secs(self.grace_seconds)
+    }
+
+    // note: a zero duration would remind or move the desk every second
+    fn validate(&self) -> Result<(), Error> {
+        [
+            ("sitting_minutes", self.sitting_minutes),
+            ("standing_minutes", self.standing_minutes),
+            ("grace_seconds", self.grace_seconds),
+        ]
+        .into_iter()
+        .find(|(_, value)| *value == 0)
+        .map_or(Ok(()), |(name, _)| Err(Error::ZeroReminderDuration(name)))
+    }
+
+    pub fn position_name_for(&self, zone: HeightZone) -> Option<&String> {
+        match zone {
+            HeightZone::Sitting => self.sitting_position.as_ref(),
+            HeightZone::Standing => self.standing_position.as_ref(),
+        }
+    }
+}
+
+fn default_sitting_minutes() -> u64 {
+    45
+}
+
+fn default_standing_minutes() -> u64 {
+    15
+}
+
+fn default_grace_seconds() -> u64 {
+    60
+}
+
+fn default_standing_threshold() -> u16 {
+    DEFAULT_STANDING_THRESHOLD
+}
+
+fn default_mouse() -> bool {
+    true
+}
+
+fn duplicate_name(positions: &[SavedPosition]) -> Option<&str> {
+    positions
+        .iter()
+        .enumerate()
+        .find(|(idx, p)| positions[..*idx].iter().any(|other| other.name == p.name))
+        .map(|(_, p)| p.name.as_str())
+}
+
+// note: the name is used as is in the history file name
+fn is_valid_profile_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
 }
 
 impl Config {
@@ -19,21 +226,442 @@ impl Config {
         Self {
             predefined_mac: None,
             saved_positions: Vec::new(),
+            unit: HeightUnit::default(),
+            height_reference: HeightReference::default(),
+            height_offsets: HashMap::new(),
+            standing_threshold: DEFAULT_STANDING_THRESHOLD,
+            metrics_address: None,
+            reminders: None,
+            schedules: Vec::new(),
+            profiles: Vec::new(),
+            default_profile: None,
+            keymap: HashMap::new(),
+            theme: ThemeConfig::default(),
+            mouse: default_mouse(),
+            wizard_dismissed: false,
+            load_error: None,
+        }
+    }
+
+    // note: a missing file is an empty configuration
+    pub fn load_from(path: String) -> Result<Self, Error> {
+        let config: Self = match File::open(path) {
+            Ok(f) => serde_json::from_reader(f)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e.into()),
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn load() -> Result<Self, Error> {
+        if let Some(path) = Self::path() {
+            Self::load_from(path.to_string_lossy().into_owned())
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    /// Empty configuration used when the file could not be loaded, which
+    /// keeps the file on disk untouched.
+    pub fn fallback(error: &Error) -> Self {
+        Self {
+            load_error: Some(error.to_string()),
+            ..Self::default()
+        }
+    }
+
+    pub fn ensure_writable(&self) -> Result<(), Error> {
+        match self.load_error {
+            Some(_) => Err(Error::ReadOnly),
+            None => Ok(()),
+        }
+    }
+
+    // note: D-Bus, schedules and reminders look positions up by name, while
+    // profile names are part of the history file names
+    fn validate(&self) -> Result<(), Error> {
+        for (idx, profile) in self.profiles.iter().enumerate() {
+            if !is_valid_profile_name(&profile.name) {
+                return Err(Error::InvalidProfileName(profile.name.clone()));
+            }
+            let lowercase = profile.name.to_lowercase();
+            if self.profiles[..idx]
+                .iter()
+                .any(|other| other.name.to_lowercase() == lowercase)
+            {
+                return Err(Error::DuplicateProfile(profile.name.clone()));
+            }
+        }
+
+        std::iter::once(&self.reminders)
+            .chain(self.profiles.iter().map(|p| &p.reminders))
+            .flatten()
+            .try_for_each(ReminderConfig::validate)?;
+
+        std::iter::once(&self.saved_positions)
+            .chain(self.profiles.iter().map(|p| &p.saved_positions))
+            .try_for_each(|positions| match duplicate_name(positions) {
+                Some(name) => Err(Error::DuplicatePosition(name.to_string())),
+                None => Ok(()),
+            })?;
+
+        // note: schedules run in every profile, the position has to exist in one of them
+        match self.schedules.iter().find(|schedule| {
+            !std::iter::once(&self.saved_positions)
+                .chain(self.profiles.iter().map(|p| &p.saved_positions))
+                .flatten()
+                .any(|p| p.name == schedule.position)
+        }) {
+            Some(schedule) => Err(Error::UnknownSchedulePosition(schedule.position.clone())),
+            None => Ok(()),
+        }
+    }
+
+    pub fn path() -> Option<PathBuf> {
+        home_dir().map(|home| home.join(CONFIG_PATH))
+    }
+
+    fn find_profile(&self, profile: Option<&str>) -> Option<&ProfileConfig> {
+        profile.and_then(|name| self.profiles.iter().find(|p| p.name == name))
+    }
+
+    pub fn has_profile(&self, name: &str) -> bool {
+        self.find_profile(Some(name)).is_some()
+    }
+
+    pub fn profile_names(&self) -> Vec<String> {
+        self.profiles.iter().map(|p| p.name.clone()).collect()
+    }
+
+    // note: None stands for the default profile, i.e. the top level fields
+    pub fn positions_for(&self, profile: Option<&str>) -> Vec<SavedPosition> {
+        match self.find_profile(profile) {
+            Some(p) => p.saved_positions.clone(),
+            None => self.saved_positions.clone(),
+        }
+    }
+
+    pub fn set_positions_for(&mut self, profile: Option<&str>, positions: Vec<SavedPosition>) {
+        match profile.and_then(|name| self.profiles.iter_mut().find(|p| p.name == name)) {
+            Some(p) => p.saved_positions = positions,
+            None => self.saved_positions = positions,
         }
     }
 
-    pub fn load_from(path: String) -> Self {
-        match File::open(path) {
-            Ok(f) => serde_json::from_reader(f).map_or_else(|_| Self::default(), |c| c),
-            Err(_) => Self::default(),
+    pub fn reminders_for(&self, profile: Option<&str>) -> Option<ReminderConfig> {
+        match self.find_profile(profile) {
+            Some(p) => p.reminders.clone(),
+            None => self.reminders.clone(),
         }
     }
 
-    pub fn load() -> Self {
-        if let Some(home) = home_dir() {
-            Self::load_from(home.join(CONFIG_PATH).to_string_lossy().into_owned())
+    /// Writes the positions of a profile. Entries left untouched keep their
+    /// original JSON, so a height written as `"104.5cm"` is not rewritten.
+    pub fn save_positions(profile: Option<&str>, positions: &[SavedPosition]) -> Result<(), Error> {
+        Self::update_file(|root| {
+            let target = match profile {
+                None => root,
+                Some(name) => root
+                    .get_mut("profiles")
+                    .and_then(Value::as_array_mut)
+                    .and_then(|profiles| {
+                        profiles
+                            .iter_mut()
+                            .filter_map(Value::as_object_mut)
+                            .find(|p| p.get("name").and_then(Value::as_str) == Some(name))
+                    })
+                    .ok_or_else(|| Error::ProfileNotFound(name.to_string()))?,
+            };
+            let positions = Self::merge_positions(target.get("saved_positions"), positions)?;
+            target.insert("saved_positions".to_string(), positions);
+            Ok(())
+        })
+    }
+
+    // note: an entry is untouched when it still reads as one of the positions
+    fn merge_positions(
+        original: Option<&Value>,
+        positions: &[SavedPosition],
+    ) -> Result<Value, serde_json::Error> {
+        let original = match original {
+            Some(Value::Array(entries)) => entries.as_slice(),
+            _ => &[],
+        };
+        positions
+            .iter()
+            .map(|position| {
+                let untouched = original
+                    .iter()
+                    .find(|entry| SavedPosition::deserialize(*entry).is_ok_and(|p| p == *position));
+                match untouched {
+                    Some(entry) => Ok(entry.clone()),
+                    None => serde_json::to_value(position),
+                }
+            })
+            .collect()
+    }
+
+    pub fn save_wizard_dismissed() -> Result<(), Error> {
+        Self::update_file(|root| {
+            root.insert("wizard_dismissed".to_string(), Value::Bool(true));
+            Ok(())
+        })
+    }
+
+    /// Applies the given change to the configuration file as it is on disk,
+    /// so that unknown fields, key order, indentation and external edits are
+    /// preserved, then atomically replaces it.
+    pub fn update_file(
+        change: impl FnOnce(&mut Map<String, Value>) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        let path = Self::path().ok_or(Error::HomeNotFound)?;
+        let original = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut root = if original.trim().is_empty() {
+            Value::Object(Map::new())
         } else {
-            Self::default()
+            serde_json::from_str(&original)?
+        };
+        change(root.as_object_mut().ok_or(Error::NotAnObject)?)?;
+
+        let mut content = match Self::detect_indent(&original) {
+            Some(indent) => {
+                let mut buffer = Vec::new();
+                let formatter = PrettyFormatter::with_indent(indent.as_bytes());
+                root.serialize(&mut Serializer::with_formatter(&mut buffer, formatter))?;
+                String::from_utf8_lossy(&buffer).into_owned()
+            }
+            None => serde_json::to_string(&root)?,
+        };
+        if original.is_empty() || original.ends_with('\n') {
+            content.push('\n');
+        }
+
+        Self::write_atomically(&path, content.as_bytes())
+    }
+
+    // note: None means that the file is written on a single line
+    fn detect_indent(content: &str) -> Option<String> {
+        if content.trim().is_empty() {
+            return Some(DEFAULT_INDENT.to_string());
         }
+
+        content
+            .lines()
+            .skip(1)
+            .map(|line| {
+                line.chars()
+                    .take_while(|c| *c == ' ' || *c == '\t')
+                    .collect::<String>()
+            })
+            .find(|indent| !indent.is_empty())
+            .or_else(|| {
+                content
+                    .trim()
+                    .contains('\n')
+                    .then(|| DEFAULT_INDENT.to_string())
+            })
+    }
+
+    // note: a symlinked file (e.g. from a dotfiles repository) is replaced at
+    // its target, so that the link is kept; the target may not exist yet
+    fn resolve_symlinks(path: &Path) -> Result<PathBuf, Error> {
+        let mut path = path.to_path_buf();
+        for _ in 0..MAX_SYMLINKS {
+            match fs::symlink_metadata(&path) {
+                Ok(metadata) if metadata.file_type().is_symlink() => {
+                    let target = fs::read_link(&path)?;
+                    path = match path.parent() {
+                        Some(parent) => parent.join(target),
+                        None => target,
+                    };
+                }
+                _ => return Ok(path),
+            }
+        }
+        Err(io::Error::other("too many levels of symbolic links").into())
+    }
+
+    fn write_atomically(path: &Path, content: &[u8]) -> Result<(), Error> {
+        let path = Self::resolve_symlinks(path)?;
+        let mut tmp_path = path.clone().into_os_string();
+        tmp_path.push(".tmp");
+        {
+            let mut tmp = File::create(&tmp_path)?;
+            // note: the file may hold private data, its permissions are kept
+            if let Ok(metadata) = fs::metadata(&path) {
+                tmp.set_permissions(metadata.permissions())?;
+            }
+            tmp.write_all(content)?;
+            tmp.sync_all()?;
+        }
+        fs::rename(&tmp_path, &path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn keeps_untouched_positions_as_written() {
+        let original = json!([
+            { "name": "sitting", "height": "72.5cm" },
+            { "name": "standing", "height": "41in", "hotkey": 5 },
+        ]);
+        let mut positions: Vec<SavedPosition> = serde_json::from_value(original.clone()).unwrap();
+
+        let merged = Config::merge_positions(Some(&original), &positions).unwrap();
+        assert_eq!(merged, original);
+
+        positions[0].height += 100;
+        positions.push(SavedPosition::new("focus".to_string(), 9000));
+        let merged = Config::merge_positions(Some(&original), &positions).unwrap();
+        assert_eq!(
+            merged,
+            json!([
+                { "name": "sitting", "height": 7350 },
+                { "name": "standing", "height": "41in", "hotkey": 5 },
+                { "name": "focus", "height": 9000 },
+            ])
+        );
+    }
+
+    #[test]
+    fn writes_every_position_without_original_entries() {
+        let positions = vec![SavedPosition::new("sitting".to_string(), 7250)];
+        let merged = Config::merge_positions(None, &positions).unwrap();
+        assert_eq!(merged, json!([{ "name": "sitting", "height": 7250 }]));
+    }
+
+    #[test]
+    fn rejects_profile_names_sharing_a_history_file() {
+        let config = |names: &[&str]| -> Config {
+            serde_json::from_value(json!({
+                "predefined_mac": null,
+                "profiles": names.iter().map(|name| json!({ "name": name })).collect::<Vec<_>>(),
+            }))
+            .unwrap()
+        };
+
+        assert!(config(&["alice", "bob-2", "bob_2"]).validate().is_ok());
+        assert!(matches!(
+            config(&["alice", "Alice"]).validate(),
+            Err(Error::DuplicateProfile(name)) if name == "Alice"
+        ));
+        assert!(matches!(
+            config(&["a.b"]).validate(),
+            Err(Error::InvalidProfileName(_))
+        ));
+        assert!(config(&["../alice"]).validate().is_err());
+        assert!(config(&[""]).validate().is_err());
+    }
+
+    #[test]
+    fn rejects_zero_reminder_durations() {
+        let config = |reminders: Value| -> Config {
+            serde_json::from_value(json!({
+                "predefined_mac": null,
+                "reminders": {},
+                "profiles": [{ "name": "alice", "reminders": reminders }],
+            }))
+            .unwrap()
+        };
+
+        assert!(config(json!({ "grace_seconds": 1 })).validate().is_ok());
+        assert!(matches!(
+            config(json!({ "sitting_minutes": 0 })).validate(),
+            Err(Error::ZeroReminderDuration("sitting_minutes"))
+        ));
+        assert!(matches!(
+            config(json!({ "standing_minutes": 0 })).validate(),
+            Err(Error::ZeroReminderDuration("standing_minutes"))
+        ));
+        assert!(matches!(
+            config(json!({ "grace_seconds": 0 })).validate(),
+            Err(Error::ZeroReminderDuration("grace_seconds"))
+        ));
+    }
+
+    #[test]
+    fn saturates_huge_reminder_durations() {
+        let reminders: ReminderConfig =
+            serde_json::from_value(json!({ "sitting_minutes": u64::MAX, "standing_minutes": 20 }))
+                .unwrap();
+        assert_eq!(
+            reminders.limit_for(HeightZone::Sitting),
+            Duration::from_secs(u64::MAX)
+        );
+        assert_eq!(
+            reminders.limit_for(HeightZone::Standing),
+            Duration::from_secs(20 * 60)
+        );
+    }
+
+    #[test]
+    fn rejects_schedules_to_unknown_positions() {
+        let config = |position: &str| -> Config {
+            serde_json::from_value(json!({
+                "predefined_mac": null,
+                "saved_positions": [{ "name": "sitting", "height": 7000 }],
+                "profiles": [{
+                    "name": "alice",
+                    "saved_positions": [{ "name": "standing", "height": 11000 }],
+                }],
+                "schedules": [{ "days": "daily", "time": "09:00", "position": position }],
+            }))
+            .unwrap()
+        };
+
+        assert!(config("sitting").validate().is_ok());
+        assert!(config("standing").validate().is_ok());
+        assert!(matches!(
+            config("Standing").validate(),
+            Err(Error::UnknownSchedulePosition(name)) if name == "Standing"
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn writes_through_symlinks_keeping_the_permissions() {
+        use std::os::unix::fs::{symlink, PermissionsExt};
+
+        let dir = std::env::temp_dir().join(format!("idasen-tui-config-{}", std::process::id()));
+        fs::create_dir_all(dir.join("dotfiles")).unwrap();
+        let target = dir.join("dotfiles").join("idasen-tui.json");
+        let link = dir.join(".idasen-tui.json");
+        fs::write(&target, "{}").unwrap();
+        fs::set_permissions(&target, fs::Permissions::from_mode(0o600)).unwrap();
+        symlink("dotfiles/idasen-tui.json", &link).unwrap();
+
+        Config::write_atomically(&link, b"{ \"mouse\": false }").unwrap();
+        assert!(fs::symlink_metadata(&link)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert_eq!(fs::read_to_string(&target).unwrap(), "{ \"mouse\": false }");
+        let mode = fs::metadata(&target).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        // note: a link to a missing file creates it
+        let missing = dir.join("dotfiles").join("missing.json");
+        let dangling = dir.join("dangling.json");
+        symlink(&missing, &dangling).unwrap();
+        Config::write_atomically(&dangling, b"{}").unwrap();
+        assert!(fs::symlink_metadata(&dangling)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert_eq!(fs::read_to_string(&missing).unwrap(), "{}");
+
+        fs::remove_dir_all(&dir).unwrap();
     }
 }
@@ -0,0 +1,115 @@
+use crate::idasen::idasen::{MAX_HEIGHT, MIN_HEIGHT};
+
+// note: ratios of the body height, from common anthropometric tables
+const STANDING_ELBOW_RATIO: f32 = 0.63;
+const SEAT_HEIGHT_RATIO: f32 = 0.25;
+const SITTING_ELBOW_RATIO: f32 = 0.14;
+
+/// Body measurements, in tenth of mm.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Measurements {
+    pub body: u16,
+    // note: standing elbow height, measured from the floor
+    pub elbow: Option<u16>,
+    // note: seat height of the chair
+    pub chair: Option<u16>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangeFit {
+    Fits,
+    BelowMin,
+    AboveMax,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Recommendation {
+    // note: surface heights, before being clamped to the desk range
+    pub sitting: u16,
+    pub standing: u16,
+}
+
+impl Recommendation {
+    /// Computes the working surface heights that keep the elbows at about 90°
+    /// with relaxed shoulders, both sitting and standing.
+    pub fn compute(measurements: &Measurements) -> Self {
+        let body = measurements.body as f32;
+        let ratio_of_body = |ratio: f32| (body * ratio).round() as u16;
+
+        let standing = measurements
+            .elbow
+            .unwrap_or_else(|| ratio_of_body(STANDING_ELBOW_RATIO));
+        let chair = measurements
+            .chair
+            .unwrap_or_else(|| ratio_of_body(SEAT_HEIGHT_RATIO));
+        let sitting = chair.saturating_add(ratio_of_body(SITTING_ELBOW_RATIO));
+
+        Self { sitting, standing }
+    }
+}
+
+/// Converts a surface height into the closest height the desk can reach,
+/// given the offset between desk and surface.
+pub fn fit_desk_range(surface_height: u16, offset: i16) -> (u16, RangeFit) {
+    let height = surface_height as i32 - offset as i32;
+    if height < MIN_HEIGHT as i32 {
+        (MIN_HEIGHT, RangeFit::BelowMin)
+    } else if height > MAX_HEIGHT as i32 {
+        (MAX_HEIGHT, RangeFit::AboveMax)
+    } else {
+        (height as u16, RangeFit::Fits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recommendation(measurements: Measurements) -> (u16, u16) {
+        let recommendation = Recommendation::compute(&measurements);
+        (recommendation.sitting, recommendation.standing)
+    }
+
+    #[test]
+    fn estimates_the_heights_from_the_body_height() {
+        // note: sitting is 0.25 + 0.14 of the body height, standing 0.63
+        for (body, sitting, standing) in [
+            (15500, 3875 + 2170, 9765),
+            (17000, 4250 + 2380, 10710),
+            (18500, 4625 + 2590, 11655),
+            (20000, 5000 + 2800, 12600),
+        ] {
+            assert_eq!(
+                recommendation(Measurements {
+                    body,
+                    ..Default::default()
+                }),
+                (sitting, standing),
+                "{}",
+                body
+            );
+        }
+    }
+
+    #[test]
+    fn prefers_the_measured_heights() {
+        let measurements = Measurements {
+            body: 17000,
+            elbow: Some(10500),
+            chair: Some(4800),
+        };
+        assert_eq!(recommendation(measurements), (4800 + 2380, 10500));
+    }
+
+    #[test]
+    fn fits_the_surface_heights_in_the_desk_range() {
+        assert_eq!(fit_desk_range(9000, 0), (9000, RangeFit::Fits));
+        assert_eq!(fit_desk_range(9000, 500), (8500, RangeFit::Fits));
+        assert_eq!(fit_desk_range(6000, 0), (MIN_HEIGHT, RangeFit::BelowMin));
+        assert_eq!(
+            fit_desk_range(MAX_HEIGHT, -1),
+            (MAX_HEIGHT, RangeFit::AboveMax)
+        );
+        assert_eq!(fit_desk_range(MIN_HEIGHT, 0), (MIN_HEIGHT, RangeFit::Fits));
+    }
+}
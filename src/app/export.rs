@@ -0,0 +1,343 @@
+use std::{
+    fs::OpenOptions,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use chrono::{Duration, Local, NaiveDate};
+use serde_json::json;
+
+use super::{
+    height::HeightUnit,
+    history::{self, HistoryRecord, UsageStats},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    JsonLines,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::JsonLines => "jsonl",
+        }
+    }
+
+    pub fn toggle(&self) -> Self {
+        match self {
+            ExportFormat::Csv => ExportFormat::JsonLines,
+            ExportFormat::JsonLines => ExportFormat::Csv,
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(ExportFormat::Csv),
+            "jsonl" | "json" => Ok(ExportFormat::JsonLines),
+            _ => Err(format!("unknown export format '{}'", s)),
+        }
+    }
+}
+
+/// Inclusive range of local days.
+#[derive(Debug, Clone, Copy)]
+pub struct ExportRange {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+impl ExportRange {
+    pub fn new(from: NaiveDate, to: NaiveDate) -> Result<Self, String> {
+        if from > to {
+            return Err(format!(
+                "the range starts ({}) after it ends ({})",
+                from, to
+            ));
+        }
+        Ok(Self { from, to })
+    }
+
+    pub fn format(&self) -> String {
+        format!("{} {}", self.from, self.to)
+    }
+
+    fn contains(&self, date: NaiveDate) -> bool {
+        self.from <= date && date <= self.to
+    }
+
+    fn days(&self) -> impl Iterator<Item = NaiveDate> + '_ {
+        self.from.iter_days().take_while(|d| *d <= self.to)
+    }
+}
+
+impl FromStr for ExportRange {
+    type Err = String;
+
+    /// Parses two dates such as `2024-05-01 2024-05-31`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_date = |value: &str| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .map_err(|_| format!("invalid date '{}'", value))
+        };
+
+        match s.split_whitespace().collect::<Vec<_>>()[..] {
+            [from, to] => ExportRange::new(parse_date(from)?, parse_date(to)?),
+            _ => Err("expected two dates, e.g. 2024-05-01 2024-05-31".to_string()),
+        }
+    }
+}
+
+pub fn write_history(
+    records: &[HistoryRecord],
+    range: ExportRange,
+    format: ExportFormat,
+    unit: HeightUnit,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let column = format!("height_{}", unit.suffix());
+    if format == ExportFormat::Csv {
+        writeln!(out, "timestamp,{}", column)?;
+    }
+
+    for record in records {
+        if let HistoryRecord::Height { at, height } = record {
+            if !range.contains(at.date_naive()) {
+                continue;
+            }
+            let value = unit.value_of(*height);
+            match format {
+                ExportFormat::Csv => writeln!(out, "{},{:.2}", at.to_rfc3339(), value)?,
+                ExportFormat::JsonLines => writeln!(
+                    out,
+                    "{}",
+                    json!({ "timestamp": at.to_rfc3339(), column.as_str(): value })
+                )?,
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub fn write_daily_totals(
+    records: &[HistoryRecord],
+    standing_threshold: u16,
+    range: ExportRange,
+    format: ExportFormat,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let now = Local::now();
+    let intervals = history::zone_intervals(records, standing_threshold, now);
+
+    if format == ExportFormat::Csv {
+        writeln!(
+            out,
+            "date,sitting_minutes,standing_minutes,transitions,longest_sitting_minutes"
+        )?;
+    }
+
+    for day in range.days() {
+        let stats = UsageStats::compute(
+            &intervals,
+            history::start_of_day(day),
+            history::start_of_day(day + Duration::days(1)).min(now),
+        );
+        let (sitting, standing, longest_sitting) = (
+            stats.sitting.as_secs() / 60,
+            stats.standing.as_secs() / 60,
+            stats.longest_sitting.as_secs() / 60,
+        );
+
+        match format {
+            ExportFormat::Csv => writeln!(
+                out,
+                "{},{},{},{},{}",
+                day, sitting, standing, stats.transitions, longest_sitting
+            )?,
+            ExportFormat::JsonLines => writeln!(
+                out,
+                "{}",
+                json!({
+                    "date": day.to_string(),
+                    "sitting_minutes": sitting,
+                    "standing_minutes": standing,
+                    "transitions": stats.transitions,
+                    "longest_sitting_minutes": longest_sitting,
+                })
+            )?,
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes both the height history and the daily totals next to each other,
+/// returning the paths of the created files. The export time is part of the
+/// names and existing files are never overwritten.
+pub fn export_to_dir(
+    dir: &Path,
+    records: &[HistoryRecord],
+    standing_threshold: u16,
+    range: ExportRange,
+    format: ExportFormat,
+    unit: HeightUnit,
+) -> io::Result<(PathBuf, PathBuf)> {
+    let prefix = format!(
+        "idasen-tui-{}_{}-{}",
+        range.from,
+        range.to,
+        Local::now().format("%Y%m%dT%H%M%S")
+    );
+    let history_path = dir.join(format!("{}-history.{}", prefix, format.extension()));
+    let daily_path = dir.join(format!("{}-daily.{}", prefix, format.extension()));
+    let create = |path: &Path| OpenOptions::new().write(true).create_new(true).open(path);
+
+    let mut out = BufWriter::new(create(&history_path)?);
+    write_history(records, range, format, unit, &mut out)?;
+    out.flush()?;
+
+    let mut out = BufWriter::new(create(&daily_path)?);
+    write_daily_totals(records, standing_threshold, range, format, &mut out)?;
+    out.flush()?;
+
+    Ok((history_path, daily_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 5, day).unwrap()
+    }
+
+    fn height(day: u32, height: u16) -> HistoryRecord {
+        HistoryRecord::Height {
+            at: Local.with_ymd_and_hms(2024, 5, day, 9, 0, 0).unwrap(),
+            height,
+        }
+    }
+
+    fn at(day: u32, time: (u32, u32)) -> chrono::DateTime<Local> {
+        Local
+            .with_ymd_and_hms(2024, 5, day, time.0, time.1, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn range_is_parsed_from_two_dates() {
+        let range = "2024-05-01  2024-05-31".parse::<ExportRange>().unwrap();
+        assert_eq!((range.from, range.to), (date(1), date(31)));
+        assert_eq!(range.format(), "2024-05-01 2024-05-31");
+
+        assert!("2024-05-01".parse::<ExportRange>().is_err());
+        assert!("2024-05-01 tomorrow".parse::<ExportRange>().is_err());
+    }
+
+    #[test]
+    fn range_ending_before_it_starts_is_rejected() {
+        assert!("2024-05-31 2024-05-01".parse::<ExportRange>().is_err());
+        assert!(ExportRange::new(date(2), date(1)).is_err());
+        assert!(ExportRange::new(date(1), date(1)).is_ok());
+    }
+
+    #[test]
+    fn history_is_written_in_the_given_unit_and_range() {
+        let records = [height(1, 7000), height(2, 10450), height(3, 11000)];
+        let range = ExportRange::new(date(2), date(3)).unwrap();
+
+        let mut out = Vec::new();
+        write_history(
+            &records,
+            range,
+            ExportFormat::Csv,
+            HeightUnit::Millimeters,
+            &mut out,
+        )
+        .unwrap();
+        let csv = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "timestamp,height_mm");
+        assert!(lines[1].ends_with(",1045.00"));
+
+        let mut out = Vec::new();
+        write_history(
+            &records,
+            range,
+            ExportFormat::JsonLines,
+            HeightUnit::Centimeters,
+            &mut out,
+        )
+        .unwrap();
+        let first: serde_json::Value =
+            serde_json::from_str(String::from_utf8(out).unwrap().lines().next().unwrap()).unwrap();
+        assert_eq!(first["height_cm"], 104.5);
+    }
+
+    #[test]
+    fn daily_totals_are_written_for_every_day_of_the_range() {
+        let height_at = |day, time, height| HistoryRecord::Height {
+            at: at(day, time),
+            height,
+        };
+        let records = [
+            height_at(1, (9, 0), 7000),
+            height_at(1, (9, 5), 7000),
+            height_at(1, (9, 10), 11000),
+            height_at(1, (9, 15), 11000),
+            HistoryRecord::Disconnected { at: at(1, (9, 20)) },
+            height_at(3, (14, 0), 7000),
+            height_at(3, (14, 5), 7000),
+            height_at(3, (14, 10), 7000),
+            HistoryRecord::Disconnected {
+                at: at(3, (14, 12)),
+            },
+            height_at(3, (15, 0), 11000),
+            height_at(3, (15, 4), 7000),
+            HistoryRecord::Disconnected { at: at(3, (15, 7)) },
+        ];
+        let range = ExportRange::new(date(1), date(3)).unwrap();
+
+        let mut out = Vec::new();
+        write_daily_totals(&records, 10000, range, ExportFormat::Csv, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "date,sitting_minutes,standing_minutes,transitions,longest_sitting_minutes\n\
+             2024-05-01,10,10,1,10\n\
+             2024-05-02,0,0,0,0\n\
+             2024-05-03,15,4,2,12\n"
+        );
+
+        let mut out = Vec::new();
+        write_daily_totals(&records, 10000, range, ExportFormat::JsonLines, &mut out).unwrap();
+        let days: Vec<serde_json::Value> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(days.len(), 3);
+        assert_eq!(
+            days[2],
+            json!({
+                "date": "2024-05-03",
+                "sitting_minutes": 15,
+                "standing_minutes": 4,
+                "transitions": 2,
+                "longest_sitting_minutes": 12,
+            })
+        );
+        assert_eq!(days[1]["sitting_minutes"], 0);
+        assert_eq!(days[1]["transitions"], 0);
+    }
+}
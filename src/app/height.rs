@@ -0,0 +1,286 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::idasen::idasen::{MAX_HEIGHT, MIN_HEIGHT};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("'{0}' is not a valid height")]
+    Invalid(String),
+
+    #[error("height must be between {0} and {1}")]
+    OutOfRange(String, String),
+
+    #[error("offset '{0}' must be between -327.68cm and 327.67cm")]
+    OffsetOutOfRange(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum HeightUnit {
+    #[default]
+    #[serde(rename = "cm")]
+    Centimeters,
+    #[serde(rename = "mm")]
+    Millimeters,
+    #[serde(rename = "in")]
+    Inches,
+}
+
+impl HeightUnit {
+    fn from_suffix(suffix: &str) -> Option<Self> {
+        match suffix {
+            "cm" => Some(HeightUnit::Centimeters),
+            "mm" => Some(HeightUnit::Millimeters),
+            "in" | "\"" => Some(HeightUnit::Inches),
+            _ => None,
+        }
+    }
+
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            HeightUnit::Centimeters => "cm",
+            HeightUnit::Millimeters => "mm",
+            HeightUnit::Inches => "in",
+        }
+    }
+
+    // note: heights are stored in tenth of mm
+    fn tenth_mm_per_unit(&self) -> f32 {
+        match self {
+            HeightUnit::Centimeters => 100.0,
+            HeightUnit::Millimeters => 10.0,
+            HeightUnit::Inches => 254.0,
+        }
+    }
+
+    pub fn value_of(&self, height: u16) -> f32 {
+        height as f32 / self.tenth_mm_per_unit()
+    }
+
+    pub fn format(&self, height: u16) -> String {
+        let value = self.value_of(height);
+        match self {
+            HeightUnit::Millimeters => format!("{:.1}{}", value, self.suffix()),
+            _ => format!("{:.2}{}", value, self.suffix()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HeightReference {
+    // note: the height reported by the desk controller
+    #[default]
+    Desk,
+    // note: the desk height plus the offset configured for the connected desk
+    Surface,
+}
+
+impl HeightReference {
+    pub fn toggle(&self) -> Self {
+        match self {
+            HeightReference::Desk => HeightReference::Surface,
+            HeightReference::Surface => HeightReference::Desk,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            HeightReference::Desk => "desk",
+            HeightReference::Surface => "surface",
+        }
+    }
+}
+
+/// Longest height `HeightFormat::format` can give, in chars. Heights in a
+/// reference are clamped to `u16`, so that the widest ones are `6553.5mm`,
+/// `655.35cm` and `258.01in`, whatever the unit, reference and offset.
+pub const MAX_FORMATTED_HEIGHT_LEN: u16 = 8;
+
+/// How heights are shown to and typed by the user.
+#[derive(Debug, Clone, Copy)]
+pub struct HeightFormat {
+    pub unit: HeightUnit,
+    pub reference: HeightReference,
+    // note: tenth of mm between the desk frame and the working surface
+    pub offset: i16,
+}
+
+impl HeightFormat {
+    fn applied_offset(&self) -> i32 {
+        match self.reference {
+            HeightReference::Desk => 0,
+            HeightReference::Surface => self.offset as i32,
+        }
+    }
+
+    fn in_reference(&self, height: u16) -> u16 {
+        (height as i32 + self.applied_offset()).clamp(0, u16::MAX as i32) as u16
+    }
+
+    pub fn format(&self, height: u16) -> String {
+        self.unit.format(self.in_reference(height))
+    }
+
+    /// Parses a height typed in this reference into a desk height.
+    pub fn parse(&self, input: &str) -> Result<u16, Error> {
+        parse_height(input, self.unit, self.applied_offset())
+    }
+}
+
+/// Parses a length with an optional unit suffix into tenth of mm, without
+/// checking it against the desk range.
+pub fn parse_length(input: &str, default_unit: HeightUnit) -> Result<i32, Error> {
+    let normalized = input.trim().to_lowercase().replace(',', ".");
+    let number_end = normalized
+        .char_indices()
+        .find(|(idx, c)| !(c.is_ascii_digit() || *c == '.' || (*idx == 0 && *c == '-')))
+        .map_or(normalized.len(), |(idx, _)| idx);
+    let (number, suffix) = normalized.split_at(number_end);
+
+    let unit = match suffix.trim() {
+        "" => default_unit,
+        suffix => {
+            HeightUnit::from_suffix(suffix).ok_or_else(|| Error::Invalid(input.to_string()))?
+        }
+    };
+    let value = number
+        .parse::<f32>()
+        .map_err(|_| Error::Invalid(input.to_string()))?;
+
+    let length = (value * unit.tenth_mm_per_unit()).round();
+    if length.abs() <= u16::MAX as f32 {
+        Ok(length as i32)
+    } else {
+        Err(Error::Invalid(input.to_string()))
+    }
+}
+
+/// Parses a height such as `104.5cm`, `41in` or `1045mm` into tenth of mm,
+/// using `default_unit` when the suffix is missing. `offset` is subtracted
+/// to get back to the desk height.
+pub fn parse_height(input: &str, default_unit: HeightUnit, offset: i32) -> Result<u16, Error> {
+    let height = parse_length(input, default_unit)? - offset;
+    if (MIN_HEIGHT as i32..=MAX_HEIGHT as i32).contains(&height) {
+        Ok(height as u16)
+    } else {
+        let bound = |height: u16| default_unit.format((height as i32 + offset).max(0) as u16);
+        Err(Error::OutOfRange(bound(MIN_HEIGHT), bound(MAX_HEIGHT)))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ConfigHeight {
+    TenthMm(u16),
+    Text(String),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ConfigOffset {
+    TenthMm(i16),
+    Text(String),
+}
+
+/// Reads a height from the configuration file: plain numbers are tenth of mm,
+/// while strings may carry a unit suffix (centimetres when missing).
+pub fn deserialize_height<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u16, D::Error> {
+    match ConfigHeight::deserialize(deserializer)? {
+        ConfigHeight::TenthMm(height) => Ok(height),
+        ConfigHeight::Text(text) => {
+            parse_height(&text, HeightUnit::Centimeters, 0).map_err(serde::de::Error::custom)
+        }
+    }
+}
+
+/// Same as `deserialize_height`, for a map of (possibly negative) offsets,
+/// which must fit in an `i16`.
+pub fn deserialize_offsets<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<HashMap<String, i16>, D::Error> {
+    HashMap::<String, ConfigOffset>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(key, offset)| {
+            let offset = match offset {
+                ConfigOffset::TenthMm(offset) => offset,
+                ConfigOffset::Text(text) => {
+                    let offset = parse_length(&text, HeightUnit::Centimeters)
+                        .map_err(serde::de::Error::custom)?;
+                    i16::try_from(offset)
+                        .map_err(|_| serde::de::Error::custom(Error::OffsetOutOfRange(text)))?
+                }
+            };
+            Ok((key, offset))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UNITS: [HeightUnit; 3] = [
+        HeightUnit::Centimeters,
+        HeightUnit::Millimeters,
+        HeightUnit::Inches,
+    ];
+
+    #[test]
+    fn formats_every_height_within_the_longest_length() {
+        for unit in UNITS {
+            let longest = (0..=u16::MAX)
+                .map(|height| unit.format(height).chars().count())
+                .max();
+            assert_eq!(
+                longest,
+                Some(MAX_FORMATTED_HEIGHT_LEN as usize),
+                "{:?}",
+                unit
+            );
+        }
+
+        for (unit, reference, offset) in UNITS.into_iter().flat_map(|unit| {
+            [HeightReference::Desk, HeightReference::Surface]
+                .into_iter()
+                .flat_map(move |r| [i16::MIN, -1, 0, 1, i16::MAX].map(|o| (unit, r, o)))
+        }) {
+            let format = HeightFormat {
+                unit,
+                reference,
+                offset,
+            };
+            for height in [0, MIN_HEIGHT, MAX_HEIGHT, u16::MAX] {
+                assert!(format.format(height).chars().count() <= MAX_FORMATTED_HEIGHT_LEN as usize);
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_offsets_out_of_range() {
+        #[derive(Debug, Deserialize)]
+        struct Offsets {
+            #[serde(deserialize_with = "deserialize_offsets")]
+            offsets: HashMap<String, i16>,
+        }
+        let offsets = |offset: serde_json::Value| {
+            serde_json::from_value::<Offsets>(serde_json::json!({ "offsets": { "desk": offset } }))
+                .map(|o| o.offsets["desk"])
+        };
+
+        assert_eq!(offsets(serde_json::json!(-250)).unwrap(), -250);
+        assert_eq!(offsets(serde_json::json!("2.5cm")).unwrap(), 250);
+        assert_eq!(offsets(serde_json::json!("-327.68cm")).unwrap(), i16::MIN);
+        assert_eq!(offsets(serde_json::json!("327.67cm")).unwrap(), i16::MAX);
+
+        let error = offsets(serde_json::json!("327.68cm")).unwrap_err();
+        assert!(
+            error.to_string().contains("offset '327.68cm' must be"),
+            "{}",
+            error
+        );
+        assert!(offsets(serde_json::json!("-130in")).is_err());
+        assert!(offsets(serde_json::json!(40000)).is_err());
+    }
+}
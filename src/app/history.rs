@@ -0,0 +1,664 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use chrono::{DateTime, Datelike, Local, NaiveDate, TimeZone};
+use dirs::home_dir;
+use serde::{Deserialize, Serialize};
+
+use super::zone::HeightZone;
+
+const HISTORY_PATH_STEM: &str = ".idasen-tui-history";
+const HISTORY_PATH_EXTENSION: &str = "jsonl";
+const HISTORY_PATH: &str = ".idasen-tui-history.jsonl";
+
+// note: a height record is written at least this often while connected, so
+// that a crash does not count the time until the next launch
+pub const HISTORY_HEARTBEAT: Duration = Duration::new(5 * 60, 0);
+const HISTORY_MAX_GAP: Duration = Duration::new(2 * HISTORY_HEARTBEAT.as_secs(), 0);
+
+// note: the file is rolled over once this big, which is years of use since
+// heights are only written once the desk settles
+const HISTORY_MAX_SIZE: u64 = 4 * 1024 * 1024;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum HistoryRecord {
+    Height {
+        at: DateTime<Local>,
+        height: u16,
+    },
+    Movement {
+        started_at: DateTime<Local>,
+        ended_at: DateTime<Local>,
+        from: u16,
+        to: u16,
+    },
+    Disconnected {
+        at: DateTime<Local>,
+    },
+}
+
+impl HistoryRecord {
+    pub fn at(&self) -> DateTime<Local> {
+        match self {
+            HistoryRecord::Height { at, .. } => *at,
+            HistoryRecord::Movement { ended_at, .. } => *ended_at,
+            HistoryRecord::Disconnected { at } => *at,
+        }
+    }
+}
+
+/// Every profile has its own history, the default one keeps the original file.
+/// Profile names are checked when the configuration is loaded, so that they
+/// can be used as is.
+pub fn history_path(profile: Option<&str>) -> Option<PathBuf> {
+    let file_name = match profile {
+        None => HISTORY_PATH.to_string(),
+        Some(name) => format!("{}-{}.{}", HISTORY_PATH_STEM, name, HISTORY_PATH_EXTENSION),
+    };
+    home_dir().map(|home| home.join(file_name))
+}
+
+// note: the previous records, once the history file is rolled over
+fn rolled_path(path: &Path) -> PathBuf {
+    let mut rolled = path.as_os_str().to_owned();
+    rolled.push(".1");
+    PathBuf::from(rolled)
+}
+
+fn load_file(path: &Path) -> Vec<HistoryRecord> {
+    match File::open(path) {
+        Ok(f) => BufReader::new(f)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| serde_json::from_str(&line).ok())
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Reads every record of the history file, and of the one it rolled over
+/// before, skipping the malformed lines.
+pub fn load(path: &Path) -> Vec<HistoryRecord> {
+    let mut records = load_file(&rolled_path(path));
+    records.extend(load_file(path));
+    records
+}
+
+/// Appends a record, rolling the file over first when it is too big. Only
+/// one rolled file is kept, replacing the previous one.
+pub fn append(path: &Path, record: &HistoryRecord) -> io::Result<()> {
+    if fs::metadata(path).is_ok_and(|m| m.len() >= HISTORY_MAX_SIZE) {
+        fs::rename(path, rolled_path(path))?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(record)?)
+}
+
+/// Earliest time looked at by the statistics and the chart.
+pub fn window_start(now: DateTime<Local>) -> DateTime<Local> {
+    let today = now.date_naive();
+    [StatisticsPeriod::Week, StatisticsPeriod::Month]
+        .into_iter()
+        .map(|period| start_of_day(period.start_date(today)))
+        .chain([now - ChartWindow::LONGEST.duration()])
+        .min()
+        .unwrap_or(now)
+}
+
+/// Drops the records before `from`, but the last of them, which gives the
+/// state of the desk at that time.
+pub fn trim_records(records: &mut Vec<HistoryRecord>, from: DateTime<Local>) {
+    let first_kept = records.partition_point(|r| r.at() < from);
+    if first_kept > 1 {
+        records.drain(..first_kept - 1);
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ZoneInterval {
+    pub zone: HeightZone,
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+}
+
+impl ZoneInterval {
+    fn clipped(&self, from: DateTime<Local>, to: DateTime<Local>) -> Duration {
+        let (start, end) = (self.start.max(from), self.end.min(to));
+        (end - start).to_std().unwrap_or_default()
+    }
+}
+
+/// Turns the height records into contiguous intervals spent in each zone.
+pub fn zone_intervals(
+    records: &[HistoryRecord],
+    standing_threshold: u16,
+    now: DateTime<Local>,
+) -> Vec<ZoneInterval> {
+    let mut intervals: Vec<ZoneInterval> = Vec::new();
+    let mut current: Option<(HeightZone, DateTime<Local>)> = None;
+
+    let close = |intervals: &mut Vec<ZoneInterval>, zone, start, end: DateTime<Local>| {
+        let max_end = start + chrono::Duration::from_std(HISTORY_MAX_GAP).unwrap();
+        let end = end.min(max_end);
+        if end <= start {
+            return;
+        }
+        match intervals.last_mut() {
+            Some(last) if last.zone == zone && last.end == start => last.end = end,
+            _ => intervals.push(ZoneInterval { zone, start, end }),
+        }
+    };
+
+    for record in records {
+        if let Some((zone, start)) = current.take() {
+            close(&mut intervals, zone, start, record.at());
+        }
+
+        current = match record {
+            HistoryRecord::Height { at, height } => Some((
+                HeightZone::from_height(*height, standing_threshold),
+                *at,
+            )),
+            HistoryRecord::Movement { ended_at, to, .. } => Some((
+                HeightZone::from_height(*to, standing_threshold),
+                *ended_at,
+            )),
+            HistoryRecord::Disconnected { .. } => None,
+        };
+    }
+
+    if let Some((zone, start)) = current {
+        close(&mut intervals, zone, start, now);
+    }
+
+    intervals
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct HeightSample {
+    pub at: DateTime<Local>,
+    // note: None when the height is unknown from here on
+    pub height: Option<u16>,
+}
+
+/// Desk heights recorded since `from`, starting with the height the desk had
+/// at that time. Disconnections and gaps in the history end the known height.
+pub fn height_samples(records: &[HistoryRecord], from: DateTime<Local>) -> Vec<HeightSample> {
+    let max_gap = chrono::Duration::from_std(HISTORY_MAX_GAP).unwrap();
+    let mut samples: Vec<HeightSample> = Vec::new();
+    let mut last: Option<HeightSample> = None;
+
+    for record in records {
+        let sample = match record {
+            HistoryRecord::Height { at, height } => HeightSample {
+                at: *at,
+                height: Some(*height),
+            },
+            HistoryRecord::Movement { .. } => continue,
+            HistoryRecord::Disconnected { at } => HeightSample {
+                at: *at,
+                height: None,
+            },
+        };
+
+        if let Some(previous) = last {
+            if previous.height.is_some() && sample.at - previous.at > max_gap {
+                let gap = HeightSample {
+                    at: previous.at + max_gap,
+                    height: None,
+                };
+                if gap.at >= from {
+                    samples.push(gap);
+                }
+                last = Some(gap);
+            }
+        }
+
+        if sample.at >= from {
+            if samples.is_empty() {
+                if let Some(previous) = last {
+                    samples.push(HeightSample {
+                        at: from,
+                        ..previous
+                    });
+                }
+            }
+            samples.push(sample);
+        }
+        last = Some(sample);
+    }
+
+    if samples.is_empty() {
+        if let Some(previous) = last {
+            let is_stale = Local::now() - previous.at > max_gap;
+            if !is_stale {
+                samples.push(HeightSample {
+                    at: from,
+                    ..previous
+                });
+            }
+        }
+    }
+
+    samples
+}
+
+/// Heights shown by the chart, loaded from the history once and then
+/// appended as the desk reports its position.
+#[derive(Debug, Default)]
+pub struct HeightSamples {
+    samples: Vec<HeightSample>,
+}
+
+impl HeightSamples {
+    pub fn load(&mut self, records: &[HistoryRecord], from: DateTime<Local>) {
+        self.samples = height_samples(records, from)
+    }
+
+    pub fn push(&mut self, record: &HistoryRecord) {
+        let height = match record {
+            HistoryRecord::Height { height, .. } => Some(*height),
+            HistoryRecord::Movement { .. } => return,
+            HistoryRecord::Disconnected { .. } => None,
+        };
+        self.samples.push(HeightSample {
+            at: record.at(),
+            height,
+        })
+    }
+
+    /// Drops the samples before `from`, the last of them being moved to `from`
+    /// since it gives the height at that time.
+    pub fn trim(&mut self, from: DateTime<Local>) {
+        let first_kept = self.samples.partition_point(|s| s.at < from);
+        if first_kept > 0 {
+            self.samples.drain(..first_kept - 1);
+            self.samples[0].at = from;
+        }
+    }
+
+    pub fn samples(&self) -> &[HeightSample] {
+        &self.samples
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChartWindow {
+    QuarterHour,
+    Hour,
+    FourHours,
+    TwelveHours,
+    Day,
+}
+
+impl ChartWindow {
+    pub const LONGEST: ChartWindow = ChartWindow::Day;
+
+    pub fn duration(&self) -> chrono::Duration {
+        match self {
+            ChartWindow::QuarterHour => chrono::Duration::minutes(15),
+            ChartWindow::Hour => chrono::Duration::hours(1),
+            ChartWindow::FourHours => chrono::Duration::hours(4),
+            ChartWindow::TwelveHours => chrono::Duration::hours(12),
+            ChartWindow::Day => chrono::Duration::days(1),
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ChartWindow::QuarterHour => "15m",
+            ChartWindow::Hour => "1h",
+            ChartWindow::FourHours => "4h",
+            ChartWindow::TwelveHours => "12h",
+            ChartWindow::Day => "24h",
+        }
+    }
+
+    pub fn zoom_in(&self) -> Self {
+        match self {
+            ChartWindow::QuarterHour | ChartWindow::Hour => ChartWindow::QuarterHour,
+            ChartWindow::FourHours => ChartWindow::Hour,
+            ChartWindow::TwelveHours => ChartWindow::FourHours,
+            ChartWindow::Day => ChartWindow::TwelveHours,
+        }
+    }
+
+    pub fn zoom_out(&self) -> Self {
+        match self {
+            ChartWindow::QuarterHour => ChartWindow::Hour,
+            ChartWindow::Hour => ChartWindow::FourHours,
+            ChartWindow::FourHours => ChartWindow::TwelveHours,
+            ChartWindow::TwelveHours | ChartWindow::Day => ChartWindow::Day,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct UsageStats {
+    pub sitting: Duration,
+    pub standing: Duration,
+    pub transitions: usize,
+    pub longest_sitting: Duration,
+}
+
+impl UsageStats {
+    pub fn compute(intervals: &[ZoneInterval], from: DateTime<Local>, to: DateTime<Local>) -> Self {
+        let mut stats = Self::default();
+        let mut previous_zone = None;
+
+        for interval in intervals
+            .iter()
+            .filter(|i| i.end > from && i.start < to)
+        {
+            let duration = interval.clipped(from, to);
+            match interval.zone {
+                HeightZone::Sitting => {
+                    stats.sitting += duration;
+                    stats.longest_sitting = stats.longest_sitting.max(duration);
+                }
+                HeightZone::Standing => stats.standing += duration,
+            }
+
+            if previous_zone.is_some_and(|z| z != interval.zone) {
+                stats.transitions += 1;
+            }
+            previous_zone = Some(interval.zone);
+        }
+
+        stats
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatisticsPeriod {
+    Today,
+    Week,
+    Month,
+}
+
+impl StatisticsPeriod {
+    pub fn start_date(&self, today: NaiveDate) -> NaiveDate {
+        match self {
+            StatisticsPeriod::Today => today,
+            StatisticsPeriod::Week => {
+                today - chrono::Duration::days(today.weekday().num_days_from_monday() as i64)
+            }
+            StatisticsPeriod::Month => today.with_day(1).unwrap_or(today),
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            StatisticsPeriod::Today => StatisticsPeriod::Week,
+            StatisticsPeriod::Week => StatisticsPeriod::Month,
+            StatisticsPeriod::Month => StatisticsPeriod::Today,
+        }
+    }
+
+    pub fn prev(&self) -> Self {
+        match self {
+            StatisticsPeriod::Today => StatisticsPeriod::Month,
+            StatisticsPeriod::Week => StatisticsPeriod::Today,
+            StatisticsPeriod::Month => StatisticsPeriod::Week,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct StatisticsSummary {
+    pub today: UsageStats,
+    pub week: UsageStats,
+    pub month: UsageStats,
+}
+
+impl StatisticsSummary {
+    pub fn compute(records: &[HistoryRecord], standing_threshold: u16) -> Self {
+        let now = Local::now();
+        let intervals = zone_intervals(records, standing_threshold, now);
+        let today = now.date_naive();
+
+        let since = |period: StatisticsPeriod| {
+            UsageStats::compute(&intervals, start_of_day(period.start_date(today)), now)
+        };
+
+        Self {
+            today: since(StatisticsPeriod::Today),
+            week: since(StatisticsPeriod::Week),
+            month: since(StatisticsPeriod::Month),
+        }
+    }
+}
+
+pub fn start_of_day(date: NaiveDate) -> DateTime<Local> {
+    let midnight = date.and_hms_opt(0, 0, 0).unwrap();
+    Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        .unwrap_or_else(|| Local.from_utc_datetime(&midnight))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const THRESHOLD: u16 = 9000;
+    const SITTING: u16 = 7000;
+    const STANDING: u16 = 11000;
+
+    fn at(minutes: i64) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 1, 15, 9, 0, 0).unwrap() + chrono::Duration::minutes(minutes)
+    }
+
+    fn height(minutes: i64, height: u16) -> HistoryRecord {
+        HistoryRecord::Height {
+            at: at(minutes),
+            height,
+        }
+    }
+
+    fn disconnected(minutes: i64) -> HistoryRecord {
+        HistoryRecord::Disconnected { at: at(minutes) }
+    }
+
+    fn spans(intervals: &[ZoneInterval]) -> Vec<(HeightZone, DateTime<Local>, DateTime<Local>)> {
+        intervals.iter().map(|i| (i.zone, i.start, i.end)).collect()
+    }
+
+    fn heights(samples: &[HeightSample]) -> Vec<(DateTime<Local>, Option<u16>)> {
+        samples.iter().map(|s| (s.at, s.height)).collect()
+    }
+
+    #[test]
+    fn merges_records_in_the_same_zone() {
+        let records = [height(0, SITTING), height(5, SITTING + 100)];
+        let intervals = zone_intervals(&records, THRESHOLD, at(8));
+        assert_eq!(spans(&intervals), vec![(HeightZone::Sitting, at(0), at(8))]);
+    }
+
+    #[test]
+    fn splits_intervals_on_zone_changes() {
+        let records = [
+            height(0, SITTING),
+            HistoryRecord::Movement {
+                started_at: at(4),
+                ended_at: at(5),
+                from: SITTING,
+                to: STANDING,
+            },
+        ];
+        let intervals = zone_intervals(&records, THRESHOLD, at(9));
+        assert_eq!(
+            spans(&intervals),
+            vec![
+                (HeightZone::Sitting, at(0), at(5)),
+                (HeightZone::Standing, at(5), at(9)),
+            ]
+        );
+    }
+
+    #[test]
+    fn counts_a_gap_up_to_the_longest_one_expected() {
+        let records = [height(0, SITTING), height(30, SITTING)];
+        let intervals = zone_intervals(&records, THRESHOLD, at(35));
+        assert_eq!(
+            spans(&intervals),
+            vec![
+                (HeightZone::Sitting, at(0), at(10)),
+                (HeightZone::Sitting, at(30), at(35)),
+            ]
+        );
+    }
+
+    #[test]
+    fn ends_intervals_on_disconnection() {
+        let records = [height(0, STANDING), disconnected(3), height(20, STANDING)];
+        let intervals = zone_intervals(&records, THRESHOLD, at(25));
+        assert_eq!(
+            spans(&intervals),
+            vec![
+                (HeightZone::Standing, at(0), at(3)),
+                (HeightZone::Standing, at(20), at(25)),
+            ]
+        );
+    }
+
+    #[test]
+    fn clips_the_statistics_to_the_range() {
+        let records = [height(0, SITTING), height(6, STANDING)];
+        let intervals = zone_intervals(&records, THRESHOLD, at(10));
+        let stats = UsageStats::compute(&intervals, at(2), at(8));
+        assert_eq!(stats.sitting, Duration::from_secs(4 * 60));
+        assert_eq!(stats.standing, Duration::from_secs(2 * 60));
+        assert_eq!(stats.transitions, 1);
+    }
+
+    #[test]
+    fn starts_samples_with_the_height_at_the_range_start() {
+        let records = [height(0, SITTING), height(8, STANDING)];
+        let samples = height_samples(&records, at(5));
+        assert_eq!(
+            heights(&samples),
+            vec![(at(5), Some(SITTING)), (at(8), Some(STANDING))]
+        );
+    }
+
+    #[test]
+    fn marks_gaps_and_disconnections_as_unknown() {
+        let records = [height(0, SITTING), height(30, STANDING), disconnected(33)];
+        let samples = height_samples(&records, at(0));
+        assert_eq!(
+            heights(&samples),
+            vec![
+                (at(0), Some(SITTING)),
+                (at(10), None),
+                (at(30), Some(STANDING)),
+                (at(33), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn leaves_out_a_gap_before_the_range() {
+        let records = [height(0, SITTING), height(30, STANDING)];
+        let samples = height_samples(&records, at(20));
+        assert_eq!(
+            heights(&samples),
+            vec![(at(20), None), (at(30), Some(STANDING))]
+        );
+    }
+
+    #[test]
+    fn appends_live_heights_and_trims_them_to_the_window() {
+        let mut samples = HeightSamples::default();
+        samples.load(&[height(0, SITTING)], at(0));
+        samples.push(&height(4, STANDING));
+        samples.push(&HistoryRecord::Movement {
+            started_at: at(3),
+            ended_at: at(4),
+            from: SITTING,
+            to: STANDING,
+        });
+        samples.push(&disconnected(6));
+
+        samples.trim(at(5));
+        assert_eq!(
+            heights(samples.samples()),
+            vec![(at(5), Some(STANDING)), (at(6), None)]
+        );
+
+        samples.trim(at(5));
+        assert_eq!(heights(samples.samples()).len(), 2);
+    }
+
+    #[test]
+    fn keeps_the_records_of_the_window() {
+        let mut records = vec![
+            height(0, SITTING),
+            height(5, STANDING),
+            disconnected(8),
+            height(20, SITTING),
+        ];
+        trim_records(&mut records, at(10));
+        let kept: Vec<_> = records.iter().map(HistoryRecord::at).collect();
+        assert_eq!(kept, vec![at(8), at(20)]);
+
+        trim_records(&mut records, at(0));
+        assert_eq!(records.len(), 2);
+    }
+
+    #[test]
+    fn looks_back_to_the_earliest_period_start() {
+        // note: 2024-01-15 is a Monday, 2024-03-03 a Sunday
+        let monday = Local.with_ymd_and_hms(2024, 1, 15, 9, 0, 0).unwrap();
+        assert_eq!(
+            window_start(monday),
+            start_of_day(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap())
+        );
+
+        let sunday = Local.with_ymd_and_hms(2024, 3, 3, 9, 0, 0).unwrap();
+        assert_eq!(
+            window_start(sunday),
+            start_of_day(NaiveDate::from_ymd_opt(2024, 2, 26).unwrap())
+        );
+
+        let first = Local.with_ymd_and_hms(2024, 4, 1, 9, 0, 0).unwrap();
+        assert_eq!(window_start(first), first - chrono::Duration::days(1));
+    }
+
+    #[test]
+    fn rolls_the_file_over_and_reads_both() {
+        let dir = std::env::temp_dir().join(format!("idasen-tui-history-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("history.jsonl");
+
+        append(&path, &height(0, SITTING)).unwrap();
+        let file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.set_len(HISTORY_MAX_SIZE).unwrap();
+        writeln!(&file).unwrap();
+        writeln!(
+            &file,
+            "{}",
+            serde_json::to_string(&height(1, STANDING)).unwrap()
+        )
+        .unwrap();
+
+        append(&path, &disconnected(2)).unwrap();
+        assert!(fs::metadata(&path).unwrap().len() < HISTORY_MAX_SIZE);
+        let kept: Vec<_> = load(&path).iter().map(HistoryRecord::at).collect();
+        assert_eq!(kept, vec![at(0), at(1), at(2)]);
+
+        // note: only one rolled file is kept
+        let file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.set_len(HISTORY_MAX_SIZE).unwrap();
+        append(&path, &height(3, SITTING)).unwrap();
+        let kept: Vec<_> = load(&path).iter().map(HistoryRecord::at).collect();
+        assert_eq!(kept, vec![at(2), at(3)]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -6,17 +6,19 @@ use std::{
         Arc, Mutex,
     },
     thread,
+    time::Instant,
 };
 
 use btleplug::api::BDAddr;
 
 use crate::{
     app::{
-        actions::{AppAction, MovingDirection},
-        state::{State, Status},
+        actions::{ActionSender, AppAction, MovingDirection},
+        state::{MoveOutcome, MoveProgress, State, Status},
     },
     bluetooth::manager::BleManager,
     idasen::idasen::Idasen,
+    metrics::Metrics,
 };
 
 use super::notification_job::{Notification, NotificationType};
@@ -26,7 +28,10 @@ pub struct BackgroundJob {
     state: Arc<Mutex<State>>,
     ble_manager: BleManager,
     notification_sx: Sender<Notification>,
+    metrics: Arc<Mutex<Metrics>>,
     exit: Arc<AtomicBool>,
+    // note: raised by the `ActionSender` to stop a move to a height in progress
+    stop_requested: Arc<AtomicBool>,
 
     requests_rx: Receiver<AppAction>,
     requests_sx: Sender<AppAction>,
@@ -38,6 +43,7 @@ impl BackgroundJob {
         state: Arc<Mutex<State>>,
         ble_manager: BleManager,
         notification_sx: Sender<Notification>,
+        metrics: Arc<Mutex<Metrics>>,
         exit: Arc<AtomicBool>,
     ) -> Self {
         let (requests_sx, requests_rx) = mpsc::channel::<AppAction>();
@@ -46,15 +52,17 @@ impl BackgroundJob {
             state,
             ble_manager,
             notification_sx,
+            metrics,
             exit,
+            stop_requested: Arc::new(AtomicBool::new(false)),
 
             requests_rx,
             requests_sx,
         }
     }
 
-    pub fn get_sender(&self) -> Sender<AppAction> {
-        self.requests_sx.clone()
+    pub fn get_sender(&self) -> ActionSender {
+        ActionSender::new(self.requests_sx.clone(), Arc::clone(&self.stop_requested))
     }
 
     pub fn run(self) -> thread::JoinHandle<()> {
@@ -123,6 +131,10 @@ impl BackgroundJob {
             *self.idasen.lock().unwrap() = _idasen_val;
             self.state.lock().unwrap().connected_device = addr;
 
+            if addr.is_some() {
+                self.metrics.lock().unwrap().observe_connection();
+            }
+
             self.show_notification(NotificationType::Info, "device succesfully connected")
         } else {
             self.show_error("impossible to find the given device");
@@ -135,20 +147,72 @@ impl BackgroundJob {
     async fn move_desk_to(&mut self, direction: MovingDirection) {
         if let Some(ref _idasen) = *self.idasen.lock().unwrap() {
             match direction {
-                MovingDirection::ToHeight(height) => _idasen.move_to(height).await.unwrap(),
+                MovingDirection::ToHeight(height) => {
+                    let id = {
+                        let mut state = self.state.lock().unwrap();
+                        let from = state.position.as_ref().map_or(height, |p| p.position);
+                        state.move_progress = Some(MoveProgress::new(from, height));
+                        state.moves_started += 1;
+                        state.moves_started
+                    };
+                    let started_at = Instant::now();
+                    let outcome = match _idasen.move_to(height, &self.stop_requested).await {
+                        Ok(_) => match _idasen.position().await {
+                            Ok(position) => MoveOutcome::Reached {
+                                id,
+                                target: height,
+                                error: position.abs_diff(height),
+                            },
+                            Err(e) => MoveOutcome::Aborted {
+                                id,
+                                target: height,
+                                reason: e.to_string(),
+                            },
+                        },
+                        Err(e) => MoveOutcome::Aborted {
+                            id,
+                            target: height,
+                            reason: e.to_string(),
+                        },
+                    };
+                    let final_error = match outcome {
+                        MoveOutcome::Reached { error, .. } => Some(error),
+                        MoveOutcome::Aborted { .. } => None,
+                    };
+                    self.metrics
+                        .lock()
+                        .unwrap()
+                        .observe_move_to(started_at.elapsed(), final_error);
+
+                    if let MoveOutcome::Aborted { ref reason, .. } = outcome {
+                        self.show_error(&format!("move aborted: {}", reason))
+                    }
+                    let mut state = self.state.lock().unwrap();
+                    state.move_progress = None;
+                    state.last_move = Some(outcome);
+                }
                 MovingDirection::Up => {
                     {
                         self.state.lock().unwrap().status =
                             Status::Freezed("desk is moving...".to_string());
                     }
-                    _idasen.up().await.unwrap();
+                    if _idasen.up().await.is_err() {
+                        self.show_error("impossible to move the desk up")
+                    }
                     self.state.lock().unwrap().status = Status::Running;
                 }
-                MovingDirection::Down => _idasen.down().await.unwrap(),
-                // fix: this never takes place because the up/down ops are sync
-                // note: it would be nice if these operations occurred without awaiting
-                // them
-                MovingDirection::Stop => _idasen.stop().await.unwrap(),
+                MovingDirection::Down => {
+                    if _idasen.down().await.is_err() {
+                        self.show_error("impossible to move the desk down")
+                    }
+                }
+                // note: a move to a height in progress is stopped by the flag
+                // the `ActionSender` raises, this one stops the manual moves
+                MovingDirection::Stop => {
+                    if _idasen.stop().await.is_err() {
+                        self.show_error("impossible to stop the desk")
+                    }
+                }
             }
         }
     }
@@ -0,0 +1,464 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+};
+
+use dbus::{
+    arg::{RefArg, Variant},
+    blocking::stdintf::org_freedesktop_dbus::PropertiesPropertiesChanged,
+    channel::{Channel, MatchingReceiver, Sender as _},
+    message::{MatchRule, SignalArgs},
+    nonblock::{stdintf::org_freedesktop_dbus::RequestNameReply, SyncConnection},
+    MethodErr,
+};
+use dbus_crossroads::Crossroads;
+use dbus_tokio::connection::{self, IOResource};
+
+use crate::{
+    app::{
+        actions::{ActionSender, AppAction, MovingDirection},
+        height::HeightReference,
+        state::State,
+    },
+    idasen::idasen::{PositionSpeed, MAX_HEIGHT, MIN_HEIGHT},
+};
+
+use super::notification_job::{Notification, NotificationType};
+
+const DBUS_NAME: &str = "org.idasen.Desk";
+const DBUS_INTERFACE: &str = "org.idasen.Desk";
+const DBUS_PATH: &str = "/org/idasen/Desk";
+
+/// Object exposed on the session bus: every call is forwarded to the
+/// `BackgroundJob` through the same channel the TUI uses.
+struct DeskObject {
+    state: Arc<Mutex<State>>,
+    action_sx: ActionSender,
+}
+
+impl DeskObject {
+    // note: D-Bus has no optional values, 0 means that no desk is connected
+    fn height(&self, reference: HeightReference) -> u16 {
+        let state = self.state.lock().unwrap();
+        let [desk, surface] = heights(state.position.as_ref(), state.height_format().offset);
+        match reference {
+            HeightReference::Desk => desk,
+            HeightReference::Surface => surface,
+        }
+    }
+
+    // note: the offset of the connected desk is subtracted from surface heights
+    fn desk_height(&self, height: u16, reference: HeightReference) -> Result<u16, MethodErr> {
+        let offset = match reference {
+            HeightReference::Desk => 0,
+            HeightReference::Surface => self.state.lock().unwrap().height_format().offset as i32,
+        };
+        let height = height as i32 - offset;
+        if (MIN_HEIGHT as i32..=MAX_HEIGHT as i32).contains(&height) {
+            Ok(height as u16)
+        } else {
+            Err(MethodErr::invalid_arg(&height))
+        }
+    }
+
+    fn start_moving(&self, direction: MovingDirection) -> Result<(), MethodErr> {
+        if self.state.lock().unwrap().connected_device.is_none() {
+            return Err(MethodErr::failed("no desk connected"));
+        }
+
+        self.action_sx
+            .send(AppAction::StartMoving(direction))
+            .map_err(|_| MethodErr::failed("background job is not running"))
+    }
+
+    fn positions(&self) -> Vec<(String, u16)> {
+        self.state
+            .lock()
+            .unwrap()
+            .positions_list
+            .items()
+            .iter()
+            .map(|p| (p.name.clone(), p.height))
+            .collect()
+    }
+}
+
+// note: the desk and surface heights, 0 when no desk is connected
+fn heights(position: Option<&PositionSpeed>, offset: i16) -> [u16; 2] {
+    match position {
+        Some(p) => {
+            let surface = (p.position as i32 + offset as i32).clamp(0, u16::MAX as i32) as u16;
+            [p.position, surface]
+        }
+        None => [0, 0],
+    }
+}
+
+pub struct DbusJob {
+    state: Arc<Mutex<State>>,
+    action_sx: ActionSender,
+    notification_sx: Sender<Notification>,
+    // note: the session bus when missing
+    address: Option<String>,
+
+    positions_rx: Receiver<Option<PositionSpeed>>,
+    positions_sx: Sender<Option<PositionSpeed>>,
+}
+
+impl DbusJob {
+    pub fn new(
+        state: Arc<Mutex<State>>,
+        action_sx: ActionSender,
+        notification_sx: Sender<Notification>,
+    ) -> Self {
+        let (positions_sx, positions_rx) = mpsc::channel::<Option<PositionSpeed>>();
+        Self {
+            state,
+            action_sx,
+            notification_sx,
+            address: None,
+
+            positions_rx,
+            positions_sx,
+        }
+    }
+
+    #[cfg(test)]
+    fn with_address(mut self, address: String) -> Self {
+        self.address = Some(address);
+        self
+    }
+
+    // note: the positions streamed by the desk, to signal the height changes
+    pub fn get_sender(&self) -> Sender<Option<PositionSpeed>> {
+        self.positions_sx.clone()
+    }
+
+    pub fn run(self) -> JoinHandle<()> {
+        thread::spawn(|| self.main_logic())
+    }
+
+    fn connect(&self) -> Result<(IOResource<SyncConnection>, Arc<SyncConnection>), dbus::Error> {
+        match self.address {
+            Some(ref address) => {
+                let mut channel = Channel::open_private(address)?;
+                channel.register()?;
+                connection::from_channel(channel)
+            }
+            None => connection::new_session_sync(),
+        }
+    }
+
+    #[tokio::main]
+    async fn main_logic(self) {
+        let (resource, conn) = match self.connect() {
+            Ok(c) => c,
+            Err(_) => {
+                return self.show_notification(
+                    NotificationType::Warning,
+                    "session bus not available, D-Bus interface disabled",
+                )
+            }
+        };
+
+        let notification_sx = self.notification_sx.clone();
+        let _resource_handle = tokio::spawn(async move {
+            let _ = resource.await;
+            let _ = notification_sx.send(Notification::new(
+                NotificationType::Error,
+                "lost connection to the session bus".to_string(),
+            ));
+        });
+
+        // note: another owner of the name is reported as a reply, not an error
+        if !matches!(
+            conn.request_name(DBUS_NAME, false, true, true).await,
+            Ok(RequestNameReply::PrimaryOwner)
+        ) {
+            return self.show_notification(
+                NotificationType::Warning,
+                "D-Bus name already taken, is another instance running?",
+            );
+        }
+
+        let mut cr = Crossroads::new();
+        let iface = cr.register(DBUS_INTERFACE, |b| {
+            b.property::<u16, _>("Height")
+                .get(|_, desk: &mut DeskObject| Ok(desk.height(HeightReference::Desk)))
+                .emits_changed_true();
+            b.property::<u16, _>("SurfaceHeight")
+                .get(|_, desk: &mut DeskObject| Ok(desk.height(HeightReference::Surface)))
+                .emits_changed_true();
+            b.method("MoveTo", ("name",), (), |_, desk: &mut DeskObject, (name,): (String,)| {
+                let height = desk
+                    .positions()
+                    .into_iter()
+                    .find(|(n, _)| *n == name)
+                    .map(|(_, h)| h)
+                    .ok_or_else(|| MethodErr::invalid_arg(&name))?;
+                desk.start_moving(MovingDirection::ToHeight(height))
+            });
+            b.method(
+                "MoveToHeight",
+                ("height",),
+                (),
+                |_, desk: &mut DeskObject, (height,): (u16,)| {
+                    let height = desk.desk_height(height, HeightReference::Desk)?;
+                    desk.start_moving(MovingDirection::ToHeight(height))
+                },
+            );
+            b.method(
+                "MoveToSurfaceHeight",
+                ("height",),
+                (),
+                |_, desk: &mut DeskObject, (height,): (u16,)| {
+                    let height = desk.desk_height(height, HeightReference::Surface)?;
+                    desk.start_moving(MovingDirection::ToHeight(height))
+                },
+            );
+            b.method("Stop", (), (), |_, desk: &mut DeskObject, _: ()| {
+                desk.start_moving(MovingDirection::Stop)
+            });
+            b.method(
+                "ListPositions",
+                (),
+                ("positions",),
+                |_, desk: &mut DeskObject, _: ()| Ok((desk.positions(),)),
+            );
+        });
+        cr.insert(
+            DBUS_PATH,
+            &[iface],
+            DeskObject {
+                state: Arc::clone(&self.state),
+                action_sx: self.action_sx.clone(),
+            },
+        );
+
+        conn.start_receive(
+            MatchRule::new_method_call(),
+            Box::new(move |msg, conn| {
+                let _ = cr.handle_message(msg, conn);
+                true
+            }),
+        );
+
+        // note: the runtime keeps serving calls while waiting for the next position
+        let mut last_heights = None;
+        while let Ok(position) = tokio::task::block_in_place(|| self.positions_rx.recv()) {
+            let offset = self.state.lock().unwrap().height_format().offset;
+            let heights = heights(position.as_ref(), offset);
+            if last_heights == Some(heights) {
+                continue;
+            }
+            last_heights = Some(heights);
+
+            let [height, surface_height] = heights;
+            let mut changed_properties: HashMap<String, Variant<Box<dyn RefArg>>> = HashMap::new();
+            changed_properties.insert("Height".to_string(), Variant(Box::new(height)));
+            changed_properties.insert(
+                "SurfaceHeight".to_string(),
+                Variant(Box::new(surface_height)),
+            );
+            let signal = PropertiesPropertiesChanged {
+                interface_name: DBUS_INTERFACE.to_string(),
+                changed_properties,
+                invalidated_properties: Vec::new(),
+            };
+            let _ = conn.send(signal.to_emit_message(&DBUS_PATH.into()));
+        }
+    }
+
+    fn show_notification(&self, kind: NotificationType, msg: &str) {
+        let _ = self
+            .notification_sx
+            .send(Notification::new(kind, msg.to_string()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader},
+        process::{Child, Command, Stdio},
+        str::FromStr,
+        sync::{atomic::AtomicBool, mpsc::Receiver},
+        time::{Duration, Instant},
+    };
+
+    use btleplug::api::BDAddr;
+    use dbus::{
+        blocking::{stdintf::org_freedesktop_dbus::Properties, Connection, Proxy},
+        Message,
+    };
+
+    use super::*;
+    use crate::app::config::Config;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    // note: a private bus, killed with the test
+    struct Bus {
+        daemon: Child,
+        address: String,
+    }
+
+    impl Bus {
+        fn start() -> Option<Self> {
+            let mut daemon = Command::new("dbus-daemon")
+                .args(["--session", "--nofork", "--print-address"])
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()
+                .ok()?;
+            let mut address = String::new();
+            BufReader::new(daemon.stdout.take()?)
+                .read_line(&mut address)
+                .ok()?;
+            Some(Self {
+                daemon,
+                address: address.trim().to_string(),
+            })
+        }
+
+        fn connect(&self) -> Connection {
+            let mut channel = Channel::open_private(&self.address).unwrap();
+            channel.register().unwrap();
+            Connection::from(channel)
+        }
+    }
+
+    impl Drop for Bus {
+        fn drop(&mut self) {
+            let _ = self.daemon.kill();
+            let _ = self.daemon.wait();
+        }
+    }
+
+    fn state() -> State {
+        let config: Config = serde_json::from_value(serde_json::json!({
+            "predefined_mac": null,
+            "height_offsets": { "AA:BB:CC:DD:EE:FF": 500 },
+            "saved_positions": [
+                { "name": "sitting", "height": 7000 },
+                { "name": "standing", "height": 11000 },
+            ],
+        }))
+        .unwrap();
+        let mut state = State::new(config, None);
+        state.connected_device = Some(BDAddr::from_str("AA:BB:CC:DD:EE:FF").unwrap());
+        state
+    }
+
+    // note: the job on the private bus, once its name is owned
+    fn start_job(
+        bus: &Bus,
+        client: &Connection,
+    ) -> (Sender<Option<PositionSpeed>>, Receiver<AppAction>) {
+        let (action_sx, action_rx) = mpsc::channel::<AppAction>();
+        let (notification_sx, _) = mpsc::channel::<Notification>();
+        let action_sx = ActionSender::new(action_sx, Arc::new(AtomicBool::new(false)));
+        let job = DbusJob::new(Arc::new(Mutex::new(state())), action_sx, notification_sx)
+            .with_address(bus.address.clone());
+        let positions_sx = job.get_sender();
+        job.run();
+
+        let started = Instant::now();
+        while proxy(client).get::<u16>(DBUS_INTERFACE, "Height").is_err() {
+            assert!(started.elapsed() < TIMEOUT, "the job never took its name");
+            thread::sleep(Duration::from_millis(10));
+        }
+        (positions_sx, action_rx)
+    }
+
+    fn proxy(client: &Connection) -> Proxy<'_, &Connection> {
+        client.with_proxy(DBUS_NAME, DBUS_PATH, TIMEOUT)
+    }
+
+    fn moved_to(action_rx: &Receiver<AppAction>) -> Option<u16> {
+        match action_rx.recv_timeout(TIMEOUT) {
+            Ok(AppAction::StartMoving(MovingDirection::ToHeight(height))) => Some(height),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn signals_the_height_changes_and_forwards_the_calls() {
+        let Some(bus) = Bus::start() else {
+            eprintln!("dbus-daemon not available, skipping");
+            return;
+        };
+        let client = bus.connect();
+        let (positions_sx, action_rx) = start_job(&bus, &client);
+
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        let signalled = Arc::clone(&changes);
+        proxy(&client)
+            .match_signal(
+                move |signal: PropertiesPropertiesChanged, _: &Connection, _: &Message| {
+                    let height = |name: &str| signal.changed_properties[name].0.as_u64();
+                    signalled
+                        .lock()
+                        .unwrap()
+                        .push((height("Height"), height("SurfaceHeight")));
+                    true
+                },
+            )
+            .unwrap();
+
+        for position in [9000, 9000, 9500] {
+            let _ = positions_sx.send(Some(PositionSpeed { position, speed: 0 }));
+        }
+        let started = Instant::now();
+        while changes.lock().unwrap().len() < 2 && started.elapsed() < TIMEOUT {
+            client.process(Duration::from_millis(100)).unwrap();
+        }
+        assert_eq!(
+            *changes.lock().unwrap(),
+            [(Some(9000), Some(9500)), (Some(9500), Some(10000))]
+        );
+
+        let desk = proxy(&client);
+        desk.method_call::<(), _, _, _>(DBUS_INTERFACE, "MoveTo", ("standing",))
+            .unwrap();
+        assert_eq!(moved_to(&action_rx), Some(11000));
+
+        desk.method_call::<(), _, _, _>(DBUS_INTERFACE, "MoveToHeight", (8000u16,))
+            .unwrap();
+        assert_eq!(moved_to(&action_rx), Some(8000));
+
+        desk.method_call::<(), _, _, _>(DBUS_INTERFACE, "MoveToSurfaceHeight", (8000u16,))
+            .unwrap();
+        assert_eq!(moved_to(&action_rx), Some(7500));
+
+        desk.method_call::<(), _, _, _>(DBUS_INTERFACE, "Stop", ())
+            .unwrap();
+        assert!(matches!(
+            action_rx.recv_timeout(TIMEOUT),
+            Ok(AppAction::StartMoving(MovingDirection::Stop))
+        ));
+
+        let (positions,): (Vec<(String, u16)>,) = desk
+            .method_call(DBUS_INTERFACE, "ListPositions", ())
+            .unwrap();
+        assert_eq!(
+            positions,
+            [
+                ("sitting".to_string(), 7000),
+                ("standing".to_string(), 11000)
+            ]
+        );
+
+        assert!(desk
+            .method_call::<(), _, _, _>(DBUS_INTERFACE, "MoveTo", ("unknown",))
+            .is_err());
+        assert!(desk
+            .method_call::<(), _, _, _>(DBUS_INTERFACE, "MoveToHeight", (MAX_HEIGHT + 1,))
+            .is_err());
+        assert!(action_rx.try_recv().is_err());
+    }
+}
@@ -0,0 +1,80 @@
+use std::{
+    path::PathBuf,
+    sync::mpsc::Sender,
+    thread::{self, JoinHandle},
+};
+
+use dirs::home_dir;
+
+use crate::app::{
+    export::{self, ExportFormat, ExportRange},
+    height::HeightUnit,
+    history,
+};
+
+use super::notification_job::{Notification, NotificationType};
+
+/// Writes the statistics export of the given range in the home directory,
+/// away from the UI thread since the whole history is read.
+pub struct ExportJob {
+    profile: Option<String>,
+    standing_threshold: u16,
+    range: ExportRange,
+    format: ExportFormat,
+    unit: HeightUnit,
+    notification_sx: Sender<Notification>,
+}
+
+impl ExportJob {
+    pub fn new(
+        profile: Option<String>,
+        standing_threshold: u16,
+        range: ExportRange,
+        format: ExportFormat,
+        unit: HeightUnit,
+        notification_sx: Sender<Notification>,
+    ) -> Self {
+        Self {
+            profile,
+            standing_threshold,
+            range,
+            format,
+            unit,
+            notification_sx,
+        }
+    }
+
+    pub fn run(self) -> JoinHandle<()> {
+        thread::spawn(move || {
+            let notification = match self.export() {
+                Ok((history_path, daily_path)) => Notification::new(
+                    NotificationType::Info,
+                    format!(
+                        "history exported to {} and {}",
+                        history_path.display(),
+                        daily_path.display()
+                    ),
+                ),
+                Err(e) => {
+                    Notification::new(NotificationType::Error, format!("export failed: {}", e))
+                }
+            };
+            let _ = self.notification_sx.send(notification);
+        })
+    }
+
+    fn export(&self) -> Result<(PathBuf, PathBuf), String> {
+        match (history::history_path(self.profile.as_deref()), home_dir()) {
+            (Some(history_path), Some(dir)) => export::export_to_dir(
+                &dir,
+                &history::load(&history_path),
+                self.standing_threshold,
+                self.range,
+                self.format,
+                self.unit,
+            )
+            .map_err(|e| e.to_string()),
+            _ => Err("home directory not found".to_string()),
+        }
+    }
+}
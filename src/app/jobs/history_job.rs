@@ -0,0 +1,276 @@
+use std::{
+    path::PathBuf,
+    sync::{
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Local};
+
+use crate::{
+    app::{
+        history::{
+            self, ChartWindow, HeightSamples, HistoryRecord, StatisticsSummary, HISTORY_HEARTBEAT,
+        },
+        state::State,
+    },
+    idasen::idasen::PositionSpeed,
+};
+
+use super::notification_job::{Notification, NotificationType};
+
+const STATISTICS_REFRESH_DELAY: Duration = Duration::new(5, 0);
+
+pub struct HistoryJob {
+    profile: Option<String>,
+    path: Option<PathBuf>,
+    standing_threshold: u16,
+    state: Arc<Mutex<State>>,
+    height_samples: Arc<Mutex<HeightSamples>>,
+    notification_sx: Sender<Notification>,
+
+    records: Vec<HistoryRecord>,
+    last_position: Option<PositionSpeed>,
+    // note: heights are only written once the desk stops moving
+    last_settled_height: Option<u16>,
+    last_written_at: Instant,
+    movement_start: Option<(DateTime<Local>, u16)>,
+    write_failed: bool,
+
+    requests_rx: Receiver<Option<PositionSpeed>>,
+    requests_sx: Sender<Option<PositionSpeed>>,
+}
+
+impl HistoryJob {
+    pub fn new(
+        profile: Option<String>,
+        standing_threshold: u16,
+        state: Arc<Mutex<State>>,
+        height_samples: Arc<Mutex<HeightSamples>>,
+        notification_sx: Sender<Notification>,
+    ) -> Self {
+        let (requests_sx, requests_rx) = mpsc::channel::<Option<PositionSpeed>>();
+        Self {
+            path: history::history_path(profile.as_deref()),
+            profile,
+            standing_threshold,
+            state,
+            height_samples,
+            notification_sx,
+
+            records: Vec::new(),
+            last_position: None,
+            last_settled_height: None,
+            last_written_at: Instant::now(),
+            movement_start: None,
+            write_failed: false,
+
+            requests_rx,
+            requests_sx,
+        }
+    }
+
+    pub fn get_sender(&self) -> Sender<Option<PositionSpeed>> {
+        self.requests_sx.clone()
+    }
+
+    pub fn run(mut self) -> JoinHandle<()> {
+        thread::spawn(move || {
+            if let Some(ref path) = self.path {
+                self.records = history::load(path);
+            }
+            history::trim_records(&mut self.records, history::window_start(Local::now()));
+            self.load_height_samples();
+
+            let mut last_refresh: Option<Instant> = None;
+            loop {
+                match self.requests_rx.recv_timeout(STATISTICS_REFRESH_DELAY) {
+                    Ok(Some(ps)) => self.observe_position(ps),
+                    Ok(None) => self.observe_disconnection(),
+                    Err(RecvTimeoutError::Timeout) => self.write_heartbeat(),
+                    Err(RecvTimeoutError::Disconnected) => return,
+                }
+
+                let profile = self.state.lock().unwrap().profile.clone();
+                if profile != self.profile {
+                    self.switch_profile(profile);
+                    last_refresh = None;
+                }
+
+                if last_refresh.is_none_or(|r| r.elapsed() >= STATISTICS_REFRESH_DELAY) {
+                    last_refresh = Some(Instant::now());
+                    // note: the records older than the statistics periods are not needed anymore
+                    history::trim_records(&mut self.records, history::window_start(Local::now()));
+                    let statistics =
+                        StatisticsSummary::compute(&self.records, self.standing_threshold);
+                    self.state.lock().unwrap().statistics = Some(statistics);
+                    self.height_samples
+                        .lock()
+                        .unwrap()
+                        .trim(Local::now() - ChartWindow::LONGEST.duration());
+                }
+            }
+        })
+    }
+
+    // note: the desk is handed over, so the previous history is closed and
+    // the next one starts from the current height
+    fn switch_profile(&mut self, profile: Option<String>) {
+        let last_position = self.last_position.clone();
+        self.observe_disconnection();
+
+        self.path = history::history_path(profile.as_deref());
+        self.profile = profile;
+        self.records = self
+            .path
+            .as_ref()
+            .map(|path| history::load(path))
+            .unwrap_or_default();
+        history::trim_records(&mut self.records, history::window_start(Local::now()));
+        self.write_failed = false;
+        self.load_height_samples();
+
+        if let Some(ps) = last_position {
+            self.observe_position(ps);
+        }
+    }
+
+    // note: the chart is then fed with every record, as the desk reports its position
+    fn load_height_samples(&self) {
+        self.height_samples.lock().unwrap().load(
+            &self.records,
+            Local::now() - ChartWindow::LONGEST.duration(),
+        )
+    }
+
+    fn observe_position(&mut self, ps: PositionSpeed) {
+        let now = Local::now();
+        let previous_height = self.last_position.as_ref().map(|p| p.position);
+
+        if ps.speed == 0 && self.last_settled_height != Some(ps.position) {
+            self.last_settled_height = Some(ps.position);
+            self.write(HistoryRecord::Height {
+                at: now,
+                height: ps.position,
+            });
+        }
+
+        match (ps.speed, self.movement_start) {
+            (0, Some((started_at, from))) => {
+                self.movement_start = None;
+                self.write(HistoryRecord::Movement {
+                    started_at,
+                    ended_at: now,
+                    from,
+                    to: ps.position,
+                });
+            }
+            (speed, None) if speed != 0 => {
+                self.movement_start = Some((now, previous_height.unwrap_or(ps.position)))
+            }
+            _ => (),
+        }
+
+        self.last_position = Some(ps);
+    }
+
+    fn observe_disconnection(&mut self) {
+        if self.last_position.take().is_some() {
+            self.movement_start = None;
+            self.last_settled_height = None;
+            self.write(HistoryRecord::Disconnected { at: Local::now() });
+        }
+    }
+
+    fn write_heartbeat(&mut self) {
+        if let Some(height) = self
+            .last_settled_height
+            .filter(|_| self.movement_start.is_none())
+        {
+            if self.last_written_at.elapsed() >= HISTORY_HEARTBEAT {
+                self.write(HistoryRecord::Height {
+                    at: Local::now(),
+                    height,
+                });
+            }
+        }
+    }
+
+    fn write(&mut self, record: HistoryRecord) {
+        self.last_written_at = Instant::now();
+
+        if let Some(ref path) = self.path {
+            if history::append(path, &record).is_err() && !self.write_failed {
+                self.write_failed = true;
+                let _ = self.notification_sx.send(Notification::new(
+                    NotificationType::Error,
+                    "impossible to write the usage history".to_string(),
+                ));
+            }
+        }
+
+        self.height_samples.lock().unwrap().push(&record);
+        self.records.push(record);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::app::config::Config;
+
+    // note: without a history file, the records are only kept in memory
+    fn history_job() -> HistoryJob {
+        let config: Config = serde_json::from_value(json!({ "predefined_mac": null })).unwrap();
+        let (notification_sx, _) = mpsc::channel::<Notification>();
+        let mut job = HistoryJob::new(
+            None,
+            9000,
+            Arc::new(Mutex::new(State::new(config, None))),
+            Arc::new(Mutex::new(HeightSamples::default())),
+            notification_sx,
+        );
+        job.path = None;
+        job
+    }
+
+    fn kinds(records: &[HistoryRecord]) -> Vec<String> {
+        records
+            .iter()
+            .map(|record| match record {
+                HistoryRecord::Height { height, .. } => format!("height {}", height),
+                HistoryRecord::Movement { from, to, .. } => format!("movement {}-{}", from, to),
+                HistoryRecord::Disconnected { .. } => "disconnected".to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn writes_the_heights_once_the_desk_settles() {
+        let mut job = history_job();
+        for (position, speed) in [(7000, 0), (7000, 0), (7100, 20), (8500, 40), (9000, 0)] {
+            job.observe_position(PositionSpeed { position, speed });
+        }
+        job.observe_disconnection();
+        job.observe_position(PositionSpeed {
+            position: 9000,
+            speed: 0,
+        });
+
+        assert_eq!(
+            kinds(&job.records),
+            vec![
+                "height 7000",
+                "height 9000",
+                "movement 7000-9000",
+                "disconnected",
+                "height 9000",
+            ]
+        );
+    }
+}
@@ -0,0 +1,135 @@
+use std::{
+    sync::{mpsc::Sender, Arc, Mutex},
+    thread::{self, JoinHandle},
+};
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+use crate::metrics::Metrics;
+
+use super::notification_job::{Notification, NotificationType};
+
+const METRICS_PATH: &str = "/metrics";
+const MAX_REQUEST_SIZE: usize = 4096;
+
+pub struct MetricsJob {
+    metrics: Arc<Mutex<Metrics>>,
+    address: String,
+    notification_sx: Sender<Notification>,
+}
+
+impl MetricsJob {
+    pub fn new(
+        metrics: Arc<Mutex<Metrics>>,
+        address: String,
+        notification_sx: Sender<Notification>,
+    ) -> Self {
+        Self {
+            metrics,
+            address,
+            notification_sx,
+        }
+    }
+
+    pub fn run(self) -> JoinHandle<()> {
+        thread::spawn(|| self.main_logic())
+    }
+
+    #[tokio::main]
+    async fn main_logic(self) {
+        let listener = match TcpListener::bind(&self.address).await {
+            Ok(l) => l,
+            Err(_) => {
+                let _ = self.notification_sx.send(Notification::new(
+                    NotificationType::Error,
+                    format!("impossible to expose metrics on {}", self.address),
+                ));
+                return;
+            }
+        };
+
+        Self::listen(listener, self.metrics).await
+    }
+
+    async fn listen(listener: TcpListener, metrics: Arc<Mutex<Metrics>>) {
+        loop {
+            if let Ok((stream, _)) = listener.accept().await {
+                let metrics = Arc::clone(&metrics);
+                tokio::spawn(async move { Self::serve(stream, metrics).await });
+            }
+        }
+    }
+
+    // note: this is a tiny subset of HTTP/1.1, just enough for Prometheus scrapers
+    async fn serve(mut stream: TcpStream, metrics: Arc<Mutex<Metrics>>) {
+        let mut buffer = vec![0; MAX_REQUEST_SIZE];
+        let read = match stream.read(&mut buffer).await {
+            Ok(n) => n,
+            Err(_) => return,
+        };
+        let request = String::from_utf8_lossy(&buffer[..read]);
+        let mut request_line = request.lines().next().unwrap_or("").split_whitespace();
+
+        let response = match (request_line.next(), request_line.next()) {
+            (Some("GET"), Some(METRICS_PATH)) => {
+                let body = metrics.lock().unwrap().render();
+                format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                )
+            }
+            (Some("GET"), Some(_)) => {
+                "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                    .to_string()
+            }
+            _ => "HTTP/1.1 405 Method Not Allowed\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                .to_string(),
+        };
+
+        let _ = stream.write_all(response.as_bytes()).await;
+        let _ = stream.shutdown().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn get(address: std::net::SocketAddr, path: &str) -> String {
+        let mut stream = TcpStream::connect(address).await.unwrap();
+        stream
+            .write_all(format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).as_bytes())
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn serves_the_metrics_over_http() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let metrics = Arc::new(Mutex::new(Metrics::new(10000)));
+        metrics.lock().unwrap().observe_position(Some(7250), 0);
+        tokio::spawn(MetricsJob::listen(listener, metrics));
+
+        let response = get(address, METRICS_PATH).await;
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let mut head = head.lines();
+        assert_eq!(head.next(), Some("HTTP/1.1 200 OK"));
+        let headers: Vec<&str> = head.collect();
+        assert!(headers.contains(&"Content-Type: text/plain; version=0.0.4"));
+        assert!(headers.contains(&format!("Content-Length: {}", body.len()).as_str()));
+        assert!(body.contains("\nidasen_height_meters 0.725\n"));
+
+        let response = get(address, "/").await;
+        assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"));
+        let response = get(address, "/metrics/other").await;
+        assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"));
+    }
+}
@@ -1,3 +1,10 @@
 pub mod background_job;
+#[cfg(target_os = "linux")]
+pub mod dbus_job;
+pub mod export_job;
+pub mod history_job;
+pub mod metrics_job;
 pub mod movement_job;
 pub mod notification_job;
+pub mod reminder_job;
+pub mod scheduler_job;
@@ -1,23 +1,40 @@
 use std::{
-    sync::{Arc, Mutex},
+    sync::{mpsc::Sender, Arc, Mutex},
     thread::{self, JoinHandle},
     time::Duration,
 };
 
 use tokio_stream::StreamExt;
 
-use crate::{app::state::State, idasen::idasen::Idasen};
+use crate::{
+    app::state::State,
+    idasen::idasen::{Idasen, PositionSpeed},
+    metrics::Metrics,
+};
 
 pub struct MovementJob {
     idasen: Arc<Mutex<Option<Idasen>>>,
     state: Arc<Mutex<State>>,
+    metrics: Arc<Mutex<Metrics>>,
+    // note: the jobs following the position, such as the history
+    position_sxs: Vec<Sender<Option<PositionSpeed>>>,
 }
 
 const MOVEMENT_UPDATE_DELAY: Duration = Duration::new(2, 0);
 
 impl MovementJob {
-    pub fn new(idasen: Arc<Mutex<Option<Idasen>>>, state: Arc<Mutex<State>>) -> Self {
-        Self { idasen, state }
+    pub fn new(
+        idasen: Arc<Mutex<Option<Idasen>>>,
+        state: Arc<Mutex<State>>,
+        metrics: Arc<Mutex<Metrics>>,
+        position_sxs: Vec<Sender<Option<PositionSpeed>>>,
+    ) -> Self {
+        Self {
+            idasen,
+            state,
+            metrics,
+            position_sxs,
+        }
     }
 
     pub fn run(self) -> JoinHandle<()> {
@@ -30,22 +47,33 @@ impl MovementJob {
             let mut stream = None;
             if let Some(ref idasen) = *self.idasen.try_lock().unwrap() {
                 if let Ok(ps) = idasen.position_and_speed().await {
-                    self.state.lock().unwrap().position = Some(ps)
+                    self.update_position(Some(ps))
                 }
                 if let Ok(s) = idasen.position_and_speed_stream().await {
                     stream = Some(s);
                 }
             } else {
-                self.state.lock().unwrap().position = None
+                self.update_position(None)
             }
 
             if let Some(mut stream) = stream {
                 while let Some(ps) = stream.next().await {
-                    self.state.lock().unwrap().position = Some(ps)
+                    self.update_position(Some(ps))
                 }
             }
 
             thread::sleep(MOVEMENT_UPDATE_DELAY);
         }
     }
+
+    fn update_position(&self, position: Option<PositionSpeed>) {
+        self.metrics.lock().unwrap().observe_position(
+            position.as_ref().map(|p| p.position),
+            position.as_ref().map_or(0, |p| p.speed),
+        );
+        for position_sx in self.position_sxs.iter() {
+            let _ = position_sx.send(position.clone());
+        }
+        self.state.lock().unwrap().position = position
+    }
 }
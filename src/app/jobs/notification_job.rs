@@ -1,4 +1,5 @@
 use std::{
+    collections::VecDeque,
     sync::{
         mpsc::{self, Receiver, Sender},
         Arc, Mutex,
@@ -7,14 +8,34 @@ use std::{
     time::{Duration, Instant},
 };
 
-#[derive(Clone, Debug)]
+use chrono::{DateTime, Local};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum NotificationType {
     Info,
-    #[allow(dead_code)]
     Warning,
     Error,
 }
 
+impl NotificationType {
+    // note: errors stay until dismissed
+    fn ttl(&self) -> Option<Duration> {
+        match self {
+            NotificationType::Info => Some(INFO_TTL),
+            NotificationType::Warning => Some(WARNING_TTL),
+            NotificationType::Error => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            NotificationType::Info => "info",
+            NotificationType::Warning => "warning",
+            NotificationType::Error => "error",
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Notification {
     pub kind: NotificationType,
@@ -28,42 +49,134 @@ impl Notification {
 }
 
 #[derive(Clone, Debug)]
-pub struct NotificationWrapper {
-    payload: Notification,
-    created_at: Instant,
+pub struct HistoryEntry {
+    pub notification: Notification,
+    // note: identical notifications are merged into a single entry
+    pub count: usize,
+    pub first_at: DateTime<Local>,
+    pub last_at: DateTime<Local>,
+    pub is_dismissed: bool,
+    shown_at: Instant,
 }
 
-impl NotificationWrapper {
-    fn new(n: Notification) -> Self {
+impl HistoryEntry {
+    fn new(notification: Notification) -> Self {
         Self {
-            created_at: Instant::now(),
-            payload: n,
+            notification,
+            count: 1,
+            first_at: Local::now(),
+            last_at: Local::now(),
+            is_dismissed: false,
+            shown_at: Instant::now(),
         }
     }
 
     fn is_expired(&self, now: Instant) -> bool {
-        now.duration_since(self.created_at) > NOTIFICATION_TTL
+        self.notification
+            .kind
+            .ttl()
+            .is_some_and(|ttl| now.duration_since(self.shown_at) > ttl)
+    }
+
+    fn is_visible(&self, now: Instant) -> bool {
+        !self.is_dismissed && !self.is_expired(now)
+    }
+
+    fn repeats(&self, notification: &Notification) -> bool {
+        self.notification.kind == notification.kind
+            && self.notification.message == notification.message
+    }
+}
+
+/// The last notifications, oldest first, shown as toasts until they expire or
+/// are dismissed.
+#[derive(Clone, Debug, Default)]
+pub struct NotificationHistory {
+    entries: VecDeque<HistoryEntry>,
+}
+
+impl NotificationHistory {
+    // note: a repeat of a visible or of the last notification shows it again,
+    // as the most recent one
+    pub fn push(&mut self, notification: Notification) {
+        let now = Instant::now();
+        let last_idx = self.entries.len().checked_sub(1);
+        let repeated = self.entries.iter().enumerate().position(|(idx, entry)| {
+            entry.repeats(&notification) && (entry.is_visible(now) || Some(idx) == last_idx)
+        });
+
+        let entry = match repeated.and_then(|idx| self.entries.remove(idx)) {
+            Some(mut entry) => {
+                entry.count += 1;
+                entry.last_at = Local::now();
+                entry.shown_at = now;
+                entry.is_dismissed = false;
+                entry
+            }
+            None => HistoryEntry::new(notification),
+        };
+        self.entries.push_back(entry);
+
+        while self.entries.len() > MAX_HISTORY_LEN {
+            self.entries.pop_front();
+        }
+    }
+
+    pub fn entries(&self) -> &VecDeque<HistoryEntry> {
+        &self.entries
+    }
+
+    /// The notifications to show as toasts, the most recent last.
+    pub fn visible(&self) -> Vec<Notification> {
+        let now = Instant::now();
+        let visible: Vec<&HistoryEntry> = self
+            .entries
+            .iter()
+            .filter(|entry| entry.is_visible(now))
+            .collect();
+
+        visible[visible.len().saturating_sub(MAX_VISIBLE)..]
+            .iter()
+            .map(|entry| match entry.count {
+                1 => entry.notification.clone(),
+                count => Notification::new(
+                    entry.notification.kind.clone(),
+                    format!("{} (x{})", entry.notification.message, count),
+                ),
+            })
+            .collect()
+    }
+
+    pub fn dismiss_all(&mut self) {
+        for entry in self.entries.iter_mut() {
+            entry.is_dismissed = true
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear()
     }
 }
 
 pub struct NotificationManager {
-    visible_notifications: Arc<Mutex<Vec<Notification>>>,
-    internal_queue: Vec<NotificationWrapper>,
+    history: Arc<Mutex<NotificationHistory>>,
 
     requests_rx: Receiver<Notification>,
     _requests_sx: Sender<Notification>,
 }
 
-const NOTIFICATION_VALIDATION_DELAY: Duration = Duration::new(2, 0);
-const NOTIFICATION_TTL: Duration = Duration::new(5, 0);
+const MAX_HISTORY_LEN: usize = 100;
+// note: older toasts stay in the history
+const MAX_VISIBLE: usize = 4;
+const INFO_TTL: Duration = Duration::new(5, 0);
+const WARNING_TTL: Duration = Duration::new(10, 0);
 
 impl NotificationManager {
-    pub fn new(visible_notifications: Arc<Mutex<Vec<Notification>>>) -> Self {
+    pub fn new(history: Arc<Mutex<NotificationHistory>>) -> Self {
         let (sx, rx) = mpsc::channel::<Notification>();
 
         Self {
-            visible_notifications,
-            internal_queue: Vec::new(),
+            history,
 
             requests_rx: rx,
             _requests_sx: sx,
@@ -74,31 +187,94 @@ impl NotificationManager {
         self._requests_sx.clone()
     }
 
-    pub fn run(mut self) -> JoinHandle<()> {
-        thread::spawn(move || loop {
-            let now = Instant::now();
-
-            while let Ok(new_notification) = self.requests_rx.try_recv() {
-                self.internal_queue
-                    .push(NotificationWrapper::new(new_notification));
+    // note: expiration is checked by the readers, this only records
+    pub fn run(self) -> JoinHandle<()> {
+        thread::spawn(move || {
+            while let Ok(notification) = self.requests_rx.recv() {
+                self.history.lock().unwrap().push(notification);
             }
+        })
+    }
+}
 
-            self.internal_queue = self
-                .internal_queue
-                .into_iter()
-                .filter(|elem| !elem.is_expired(now))
-                .collect();
-
-            if let Ok(mut visible_notifications) = self.visible_notifications.try_lock() {
-                *visible_notifications = self
-                    .internal_queue
-                    .clone()
-                    .into_iter()
-                    .map(|wrapper| wrapper.payload)
-                    .collect();
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-            thread::sleep(NOTIFICATION_VALIDATION_DELAY);
-        })
+    fn info(message: &str) -> Notification {
+        Notification::new(NotificationType::Info, message.to_string())
+    }
+
+    // note: moves every toast back in time, past the longest ttl
+    fn age(history: &mut NotificationHistory) {
+        let shown_at = Instant::now().checked_sub(WARNING_TTL * 2).unwrap();
+        for entry in history.entries.iter_mut() {
+            entry.shown_at = shown_at
+        }
+    }
+
+    fn messages(history: &NotificationHistory) -> Vec<String> {
+        history.visible().into_iter().map(|n| n.message).collect()
+    }
+
+    #[test]
+    fn merges_a_repeat_of_a_visible_entry_as_the_most_recent() {
+        let mut history = NotificationHistory::default();
+        history.push(info("connected"));
+        history.push(info("moving"));
+        history.push(info("connected"));
+
+        assert_eq!(history.entries().len(), 2);
+        assert_eq!(history.entries()[1].count, 2);
+        assert_eq!(messages(&history), vec!["moving", "connected (x2)"]);
+    }
+
+    #[test]
+    fn merges_a_repeat_of_the_last_entry_even_once_expired() {
+        let mut history = NotificationHistory::default();
+        history.push(info("moving"));
+        history.push(info("connected"));
+        age(&mut history);
+
+        history.push(info("connected"));
+        assert_eq!(history.entries().len(), 2);
+        assert_eq!(messages(&history), vec!["connected (x2)"]);
+
+        // note: an expired entry that is not the last one is not merged
+        history.push(info("moving"));
+        assert_eq!(history.entries().len(), 3);
+        assert_eq!(history.entries()[2].count, 1);
+    }
+
+    #[test]
+    fn keeps_errors_until_dismissed() {
+        let mut history = NotificationHistory::default();
+        history.push(Notification::new(
+            NotificationType::Error,
+            "disconnected".to_string(),
+        ));
+        history.push(Notification::new(
+            NotificationType::Warning,
+            "time to switch".to_string(),
+        ));
+        age(&mut history);
+
+        assert_eq!(messages(&history), vec!["disconnected"]);
+
+        history.dismiss_all();
+        assert!(history.visible().is_empty());
+        assert_eq!(history.entries().len(), 2);
+    }
+
+    #[test]
+    fn evicts_the_oldest_entries_beyond_the_maximum() {
+        let mut history = NotificationHistory::default();
+        for idx in 0..MAX_HISTORY_LEN + 5 {
+            history.push(info(&idx.to_string()));
+        }
+
+        assert_eq!(history.entries().len(), MAX_HISTORY_LEN);
+        assert_eq!(history.entries()[0].notification.message, "5");
+        assert_eq!(history.visible().len(), MAX_VISIBLE);
     }
 }
@@ -0,0 +1,303 @@
+use std::{
+    sync::{mpsc::Sender, Arc, Mutex},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use crate::app::{
+    actions::{ActionSender, AppAction, MovingDirection},
+    config::{Config, ReminderConfig},
+    state::{SavedPosition, State},
+    zone::HeightZone,
+};
+
+use super::notification_job::{Notification, NotificationType};
+
+const REMINDER_VALIDATION_DELAY: Duration = Duration::new(1, 0);
+
+struct Stint {
+    zone: HeightZone,
+    since: Instant,
+    // note: the last reminder, the next one is due a zone limit later
+    reminded_at: Option<Instant>,
+    // note: the desk is moved at most once per reminder
+    auto_moved: bool,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum StintCheck {
+    Remind,
+    AutoMove,
+    Wait,
+}
+
+impl Stint {
+    fn new(zone: HeightZone, now: Instant) -> Self {
+        Self {
+            zone,
+            since: now,
+            reminded_at: None,
+            auto_moved: false,
+        }
+    }
+
+    // note: a reminder is due a zone limit after the start of the stint or
+    // the previous reminder, the desk is moved a grace period after it
+    fn check(&self, now: Instant, config: &ReminderConfig) -> StintCheck {
+        let last_reminder = self.reminded_at.unwrap_or(self.since);
+
+        if now.duration_since(last_reminder) >= config.limit_for(self.zone) {
+            StintCheck::Remind
+        } else if config.auto_move
+            && !self.auto_moved
+            && self
+                .reminded_at
+                .is_some_and(|reminded_at| now.duration_since(reminded_at) >= config.grace_period())
+        {
+            StintCheck::AutoMove
+        } else {
+            StintCheck::Wait
+        }
+    }
+}
+
+// note: the stint goes on while the zone is kept, unless `restart` is set
+fn next_stint(
+    stint: Option<Stint>,
+    zone: Option<HeightZone>,
+    restart: bool,
+    now: Instant,
+) -> Option<Stint> {
+    match (zone, stint) {
+        (None, _) => None,
+        (Some(zone), Some(s)) if s.zone == zone && !restart => Some(s),
+        (Some(zone), _) => Some(Stint::new(zone, now)),
+    }
+}
+
+pub struct ReminderJob {
+    // note: the reminder settings follow the active profile
+    config: Config,
+    standing_threshold: u16,
+    state: Arc<Mutex<State>>,
+    action_sx: ActionSender,
+    notification_sx: Sender<Notification>,
+}
+
+impl ReminderJob {
+    pub fn new(
+        config: Config,
+        standing_threshold: u16,
+        state: Arc<Mutex<State>>,
+        action_sx: ActionSender,
+        notification_sx: Sender<Notification>,
+    ) -> Self {
+        Self {
+            config,
+            standing_threshold,
+            state,
+            action_sx,
+            notification_sx,
+        }
+    }
+
+    pub fn run(self) -> JoinHandle<()> {
+        thread::spawn(move || {
+            let mut stint: Option<Stint> = None;
+            let mut last_profile: Option<String> = None;
+            let mut was_enabled = true;
+            loop {
+                let (zone, enabled, positions, profile) = {
+                    let state = self.state.lock().unwrap();
+                    (
+                        state
+                            .position
+                            .as_ref()
+                            .map(|p| HeightZone::from_height(p.position, self.standing_threshold)),
+                        state.reminder.as_ref().is_some_and(|r| r.enabled),
+                        state.positions_list.items().to_vec(),
+                        state.profile.clone(),
+                    )
+                };
+
+                // note: a different person, or reminders started again, start a new stint
+                let restart = profile != last_profile || (enabled && !was_enabled);
+                last_profile = profile.clone();
+                was_enabled = enabled;
+
+                stint = next_stint(stint, zone, restart, Instant::now());
+
+                if let Some(config) = self.config.reminders_for(profile.as_deref()) {
+                    if let Some(ref mut s) = stint {
+                        if enabled {
+                            self.check_stint(&config, s, &positions);
+                        }
+                    }
+
+                    if let Some(ref mut status) = self.state.lock().unwrap().reminder {
+                        status.zone = stint.as_ref().map(|s| s.zone);
+                        status.in_zone_for =
+                            stint.as_ref().map_or(Duration::ZERO, |s| s.since.elapsed());
+                        status.switch_in = stint.as_ref().map_or(Duration::ZERO, |s| {
+                            config.limit_for(s.zone).saturating_sub(s.since.elapsed())
+                        });
+                    }
+                }
+
+                thread::sleep(REMINDER_VALIDATION_DELAY);
+            }
+        })
+    }
+
+    fn check_stint(&self, config: &ReminderConfig, stint: &mut Stint, positions: &[SavedPosition]) {
+        let now = Instant::now();
+        let target_zone = stint.zone.opposite();
+
+        match stint.check(now, config) {
+            StintCheck::Remind => {
+                stint.reminded_at = Some(now);
+                stint.auto_moved = false;
+                self.show_notification(
+                    NotificationType::Warning,
+                    format!(
+                        "you have been {} for {} minutes, time to switch!",
+                        stint.zone.label(),
+                        now.duration_since(stint.since).as_secs() / 60
+                    ),
+                );
+                if config.bell {
+                    self.state.lock().unwrap().pending_bell = true;
+                }
+            }
+            StintCheck::AutoMove => {
+                stint.auto_moved = true;
+                match self.target_position(config, target_zone, positions) {
+                    Some(position) => {
+                        self.show_notification(
+                            NotificationType::Info,
+                            format!("moving to {}", position.name),
+                        );
+                        let _ =
+                            self.action_sx
+                                .send(AppAction::StartMoving(MovingDirection::ToHeight(
+                                    position.height,
+                                )));
+                    }
+                    None => self.show_notification(
+                        NotificationType::Error,
+                        format!("no saved position to move to for {}", target_zone.label()),
+                    ),
+                }
+            }
+            StintCheck::Wait => (),
+        }
+    }
+
+    fn target_position(
+        &self,
+        config: &ReminderConfig,
+        zone: HeightZone,
+        positions: &[SavedPosition],
+    ) -> Option<SavedPosition> {
+        match config.position_name_for(zone) {
+            Some(name) => positions.iter().find(|p| p.name == *name),
+            None => positions
+                .iter()
+                .find(|p| HeightZone::from_height(p.height, self.standing_threshold) == zone),
+        }
+        .cloned()
+    }
+
+    fn show_notification(&self, kind: NotificationType, msg: String) {
+        let _ = self.notification_sx.send(Notification::new(kind, msg));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE: Duration = Duration::from_secs(60);
+
+    fn config() -> ReminderConfig {
+        serde_json::from_value(serde_json::json!({
+            "sitting_minutes": 45,
+            "standing_minutes": 15,
+            "auto_move": true,
+            "grace_seconds": 60,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn reminds_once_the_zone_limit_is_reached() {
+        let start = Instant::now();
+        let stint = Stint::new(HeightZone::Sitting, start);
+
+        assert_eq!(stint.check(start, &config()), StintCheck::Wait);
+        assert_eq!(
+            stint.check(start + 44 * MINUTE, &config()),
+            StintCheck::Wait
+        );
+        assert_eq!(
+            stint.check(start + 45 * MINUTE, &config()),
+            StintCheck::Remind
+        );
+
+        let stint = Stint::new(HeightZone::Standing, start);
+        assert_eq!(
+            stint.check(start + 15 * MINUTE, &config()),
+            StintCheck::Remind
+        );
+    }
+
+    #[test]
+    fn moves_then_reminds_again_while_the_zone_is_kept() {
+        let start = Instant::now();
+        let reminded_at = start + 45 * MINUTE;
+        let mut stint = Stint::new(HeightZone::Sitting, start);
+        stint.reminded_at = Some(reminded_at);
+
+        assert_eq!(stint.check(reminded_at, &config()), StintCheck::Wait);
+        assert_eq!(
+            stint.check(reminded_at + MINUTE, &config()),
+            StintCheck::AutoMove
+        );
+
+        stint.auto_moved = true;
+        assert_eq!(
+            stint.check(reminded_at + 2 * MINUTE, &config()),
+            StintCheck::Wait
+        );
+        assert_eq!(
+            stint.check(reminded_at + 45 * MINUTE, &config()),
+            StintCheck::Remind
+        );
+
+        let stint = next_stint(Some(stint), Some(HeightZone::Sitting), false, reminded_at);
+        assert_eq!(stint.map(|s| s.since), Some(start));
+    }
+
+    #[test]
+    fn starts_over_after_a_resume() {
+        let start = Instant::now();
+        let resumed_at = start + 60 * MINUTE;
+        let stint = next_stint(
+            Some(Stint::new(HeightZone::Sitting, start)),
+            Some(HeightZone::Sitting),
+            true,
+            resumed_at,
+        )
+        .unwrap();
+
+        assert_eq!(stint.since, resumed_at);
+        assert_eq!(stint.reminded_at, None);
+        assert_eq!(stint.check(resumed_at, &config()), StintCheck::Wait);
+        assert_eq!(
+            stint.check(resumed_at + 45 * MINUTE, &config()),
+            StintCheck::Remind
+        );
+
+        assert!(next_stint(Some(stint), None, false, resumed_at).is_none());
+    }
+}
@@ -0,0 +1,379 @@
+use std::{
+    sync::{mpsc::Sender, Arc, Mutex},
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use btleplug::api::BDAddr;
+use chrono::{DateTime, Local};
+
+use crate::app::{
+    actions::{ActionSender, AppAction, MovingDirection},
+    config::ScheduleConfig,
+    schedule::{Schedule, ScheduleStatus},
+    state::{MoveOutcome, State},
+};
+
+use super::notification_job::{Notification, NotificationType};
+
+const SCHEDULER_VALIDATION_DELAY: Duration = Duration::new(1, 0);
+// note: how long a run waits for the desk to be connected before being reported as missed
+const SCHEDULE_RETRY_WINDOW: Duration = Duration::new(10 * 60, 0);
+// note: how long a started move is followed before its outcome is given up on
+const SCHEDULE_MOVE_TIMEOUT: Duration = Duration::new(2 * 60, 0);
+
+struct PendingRun {
+    index: usize,
+    due: DateTime<Local>,
+    reconnect_requested: bool,
+    started_move: Option<StartedMove>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum RunCheck {
+    Start(u16),
+    Reconnect,
+    Wait,
+    UnknownPosition,
+    Missed,
+}
+
+impl PendingRun {
+    fn new(index: usize, due: DateTime<Local>) -> Self {
+        Self {
+            index,
+            due,
+            reconnect_requested: false,
+            started_move: None,
+        }
+    }
+
+    // note: a disconnected desk is waited for a retry window after the due
+    // time, reconnecting to the predefined one once
+    fn check(
+        &self,
+        height: Option<u16>,
+        is_connected: bool,
+        can_reconnect: bool,
+        now: DateTime<Local>,
+    ) -> RunCheck {
+        match height {
+            None => RunCheck::UnknownPosition,
+            Some(height) if is_connected => RunCheck::Start(height),
+            Some(_) if (now - self.due).to_std().unwrap_or_default() >= SCHEDULE_RETRY_WINDOW => {
+                RunCheck::Missed
+            }
+            Some(_) if can_reconnect && !self.reconnect_requested => RunCheck::Reconnect,
+            Some(_) => RunCheck::Wait,
+        }
+    }
+}
+
+// note: the move requested for a run, whose outcome is the one of the first
+// move to its target started after the request
+struct StartedMove {
+    target: u16,
+    // note: State::moves_started when the move was requested
+    moves_before: u64,
+    at: DateTime<Local>,
+}
+
+impl StartedMove {
+    // note: the outcome of the run once the move is over, None until then
+    fn outcome(&self, last_move: Option<&MoveOutcome>, now: DateTime<Local>) -> Option<String> {
+        let started = self.at.format("%a %H:%M");
+        match last_move {
+            Some(&MoveOutcome::Reached { id, target, .. })
+                if self.is_requested_move(id, target) =>
+            {
+                Some(format!("moved {}", started))
+            }
+            Some(&MoveOutcome::Aborted {
+                id,
+                target,
+                ref reason,
+            }) if self.is_requested_move(id, target) => {
+                Some(format!("failed {}: {}", started, reason))
+            }
+            _ if (now - self.at).to_std().unwrap_or_default() >= SCHEDULE_MOVE_TIMEOUT => {
+                Some(format!("started {}, no outcome", started))
+            }
+            _ => None,
+        }
+    }
+
+    fn is_requested_move(&self, id: u64, target: u16) -> bool {
+        id > self.moves_before && target == self.target
+    }
+}
+
+pub struct SchedulerJob {
+    schedules: Vec<ScheduleConfig>,
+    predefined_mac: Option<BDAddr>,
+    state: Arc<Mutex<State>>,
+    action_sx: ActionSender,
+    notification_sx: Sender<Notification>,
+}
+
+impl SchedulerJob {
+    pub fn new(
+        schedules: Vec<ScheduleConfig>,
+        predefined_mac: Option<BDAddr>,
+        state: Arc<Mutex<State>>,
+        action_sx: ActionSender,
+        notification_sx: Sender<Notification>,
+    ) -> Self {
+        Self {
+            schedules,
+            predefined_mac,
+            state,
+            action_sx,
+            notification_sx,
+        }
+    }
+
+    pub fn run(self) -> JoinHandle<()> {
+        thread::spawn(move || {
+            let statuses = self
+                .schedules
+                .iter()
+                .filter_map(|config| match Schedule::parse(config) {
+                    Ok(schedule) => Some(ScheduleStatus::new(schedule)),
+                    Err(e) => {
+                        self.show_notification(NotificationType::Error, e.to_string());
+                        None
+                    }
+                })
+                .collect();
+            self.state.lock().unwrap().schedules.set(statuses);
+
+            let mut pending: Vec<PendingRun> = Vec::new();
+            loop {
+                let now = Local::now();
+
+                for (index, status) in self
+                    .state
+                    .lock()
+                    .unwrap()
+                    .schedules
+                    .items_mut()
+                    .iter_mut()
+                    .enumerate()
+                {
+                    let due = match status.next_run {
+                        Some(run) if run <= now => run,
+                        _ => continue,
+                    };
+
+                    if status.skip_next {
+                        status.skip_next = false;
+                        status.last_outcome = Some(format!("skipped {}", due.format("%a %H:%M")));
+                    } else {
+                        pending.push(PendingRun::new(index, due));
+                    }
+                    status.next_run = status.schedule.next_run_after(now);
+                }
+
+                pending.retain_mut(|run| !self.try_run(run, now));
+
+                thread::sleep(SCHEDULER_VALIDATION_DELAY);
+            }
+        })
+    }
+
+    // note: returns true once the run does not need to be retried anymore
+    fn try_run(&self, run: &mut PendingRun, now: DateTime<Local>) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if let Some(ref started_move) = run.started_move {
+            return match started_move.outcome(state.last_move.as_ref(), now) {
+                Some(outcome) => {
+                    if let Some(status) = state.schedules.items_mut().get_mut(run.index) {
+                        status.last_outcome = Some(outcome);
+                    }
+                    true
+                }
+                None => false,
+            };
+        }
+
+        let position_name = match state.schedules.items().get(run.index) {
+            Some(status) => status.schedule.position.clone(),
+            None => return true,
+        };
+        let height = state
+            .positions_list
+            .items()
+            .iter()
+            .find(|p| p.name == position_name)
+            .map(|p| p.height);
+
+        let is_connected = state.connected_device.is_some();
+        let outcome = match run.check(height, is_connected, self.predefined_mac.is_some(), now) {
+            RunCheck::Start(height) => {
+                run.started_move = Some(StartedMove {
+                    target: height,
+                    moves_before: state.moves_started,
+                    at: now,
+                });
+                let _ = self
+                    .action_sx
+                    .send(AppAction::StartMoving(MovingDirection::ToHeight(height)));
+                self.show_notification(
+                    NotificationType::Info,
+                    format!("scheduled move to {}", position_name),
+                );
+                if let Some(status) = state.schedules.items_mut().get_mut(run.index) {
+                    status.last_outcome = Some(format!("started {}", now.format("%a %H:%M")));
+                }
+                return false;
+            }
+            RunCheck::Reconnect => {
+                run.reconnect_requested = true;
+                if let Some(addr) = self.predefined_mac {
+                    let _ = self.action_sx.send(AppAction::ConnectDevice(addr));
+                }
+                return false;
+            }
+            RunCheck::Wait => return false,
+            RunCheck::UnknownPosition => {
+                self.show_notification(
+                    NotificationType::Error,
+                    format!("scheduled move failed: unknown position {}", position_name),
+                );
+                format!("unknown position {}", position_name)
+            }
+            RunCheck::Missed => {
+                self.show_notification(
+                    NotificationType::Error,
+                    format!(
+                        "missed scheduled move to {} at {}: desk disconnected",
+                        position_name,
+                        run.due.format("%H:%M")
+                    ),
+                );
+                format!("missed {}", run.due.format("%a %H:%M"))
+            }
+        };
+
+        if let Some(status) = state.schedules.items_mut().get_mut(run.index) {
+            status.last_outcome = Some(outcome);
+        }
+        true
+    }
+
+    fn show_notification(&self, kind: NotificationType, msg: String) {
+        let _ = self.notification_sx.send(Notification::new(kind, msg));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    // note: a monday
+    fn due() -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(2024, 1, 15, 8, 0, 0)
+            .single()
+            .unwrap()
+    }
+
+    fn minutes_after_due(minutes: i64) -> DateTime<Local> {
+        due() + chrono::Duration::minutes(minutes)
+    }
+
+    #[test]
+    fn starts_the_move_once_the_desk_is_connected() {
+        let run = PendingRun::new(0, due());
+        assert_eq!(
+            run.check(Some(11000), true, true, due()),
+            RunCheck::Start(11000)
+        );
+        assert_eq!(
+            run.check(Some(11000), true, false, minutes_after_due(9)),
+            RunCheck::Start(11000)
+        );
+        assert_eq!(
+            run.check(None, true, true, due()),
+            RunCheck::UnknownPosition
+        );
+        assert_eq!(
+            run.check(None, false, true, due()),
+            RunCheck::UnknownPosition
+        );
+    }
+
+    #[test]
+    fn reconnects_once_and_misses_the_run_after_the_retry_window() {
+        let mut run = PendingRun::new(0, due());
+        assert_eq!(
+            run.check(Some(11000), false, true, due()),
+            RunCheck::Reconnect
+        );
+        assert_eq!(run.check(Some(11000), false, false, due()), RunCheck::Wait);
+
+        run.reconnect_requested = true;
+        assert_eq!(
+            run.check(Some(11000), false, true, minutes_after_due(1)),
+            RunCheck::Wait
+        );
+        assert_eq!(
+            run.check(Some(11000), false, true, minutes_after_due(9)),
+            RunCheck::Wait
+        );
+        assert_eq!(
+            run.check(Some(11000), false, true, minutes_after_due(10)),
+            RunCheck::Missed
+        );
+        assert_eq!(
+            run.check(Some(11000), false, false, minutes_after_due(10)),
+            RunCheck::Missed
+        );
+    }
+
+    #[test]
+    fn reports_the_outcome_of_the_started_move_only() {
+        let started_move = StartedMove {
+            target: 11000,
+            moves_before: 3,
+            at: due(),
+        };
+        let reached = |id, target| MoveOutcome::Reached {
+            id,
+            target,
+            error: 5,
+        };
+
+        assert_eq!(started_move.outcome(None, minutes_after_due(1)), None);
+        // note: the outcome of a previous move or of a move to another height
+        assert_eq!(
+            started_move.outcome(Some(&reached(3, 11000)), minutes_after_due(1)),
+            None
+        );
+        assert_eq!(
+            started_move.outcome(Some(&reached(4, 7000)), minutes_after_due(1)),
+            None
+        );
+
+        assert_eq!(
+            started_move.outcome(Some(&reached(4, 11000)), minutes_after_due(1)),
+            Some("moved Mon 08:00".to_string())
+        );
+        let aborted = MoveOutcome::Aborted {
+            id: 5,
+            target: 11000,
+            reason: "stopped".to_string(),
+        };
+        assert_eq!(
+            started_move.outcome(Some(&aborted), minutes_after_due(1)),
+            Some("failed Mon 08:00: stopped".to_string())
+        );
+
+        assert_eq!(
+            started_move.outcome(Some(&reached(3, 11000)), minutes_after_due(2)),
+            Some("started Mon 08:00, no outcome".to_string())
+        );
+    }
+}
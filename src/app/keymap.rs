@@ -0,0 +1,1003 @@
+use std::collections::HashMap;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeymapScope {
+    // note: global bindings are active in every view and overlay
+    Global,
+    Connection,
+    Movement,
+    Positions,
+    Devices,
+    Statistics,
+    Chart,
+    Schedules,
+    Notifications,
+}
+
+impl KeymapScope {
+    pub const ALL: [KeymapScope; 9] = [
+        KeymapScope::Global,
+        KeymapScope::Connection,
+        KeymapScope::Movement,
+        KeymapScope::Positions,
+        KeymapScope::Devices,
+        KeymapScope::Statistics,
+        KeymapScope::Chart,
+        KeymapScope::Schedules,
+        KeymapScope::Notifications,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            KeymapScope::Global => "global",
+            KeymapScope::Connection => "connection",
+            KeymapScope::Movement => "movement",
+            KeymapScope::Positions => "positions",
+            KeymapScope::Devices => "devices",
+            KeymapScope::Statistics => "statistics",
+            KeymapScope::Chart => "chart",
+            KeymapScope::Schedules => "schedules",
+            KeymapScope::Notifications => "notifications",
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            KeymapScope::Global => "Everywhere",
+            KeymapScope::Connection => "Connection",
+            KeymapScope::Movement => "Movement",
+            KeymapScope::Positions => "Saved positions",
+            KeymapScope::Devices => "Devices",
+            KeymapScope::Statistics => "Statistics",
+            KeymapScope::Chart => "Height chart",
+            KeymapScope::Schedules => "Schedules",
+            KeymapScope::Notifications => "Notifications",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Quit,
+    NextView,
+    PrevView,
+    ToggleSchedules,
+    CommandPalette,
+    Help,
+    Notifications,
+    DismissNotifications,
+    ToggleZoom,
+    // note: hotkey of a saved position, from 1 to 9
+    GoToPosition(u8),
+
+    ToggleReminders,
+    NextProfile,
+    PrevProfile,
+
+    MoveUp,
+    MoveDown,
+    GoToHeight,
+    NudgeUp,
+    NudgeDown,
+    FineNudgeUp,
+    FineNudgeDown,
+    ClearCount,
+    ToggleReference,
+
+    Select,
+    SelectPrev,
+    SelectNext,
+    MoveItemUp,
+    MoveItemDown,
+    Add,
+    SaveCurrent,
+    Rename,
+    Delete,
+    Wizard,
+
+    Refresh,
+    Connect,
+
+    PrevPeriod,
+    NextPeriod,
+    Export,
+    ToggleFormat,
+
+    ZoomIn,
+    ZoomOut,
+
+    Close,
+    SkipNext,
+    ClearHistory,
+}
+
+impl Action {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::NextView => "next_view",
+            Action::PrevView => "prev_view",
+            Action::ToggleSchedules => "schedules",
+            Action::CommandPalette => "command_palette",
+            Action::Help => "help",
+            Action::Notifications => "notifications",
+            Action::DismissNotifications => "dismiss_notifications",
+            Action::ToggleZoom => "zoom",
+            Action::GoToPosition(hotkey) => GO_TO_POSITION_NAMES[*hotkey as usize - 1],
+            Action::ToggleReminders => "toggle_reminders",
+            Action::NextProfile => "next_profile",
+            Action::PrevProfile => "prev_profile",
+            Action::MoveUp => "up",
+            Action::MoveDown => "down",
+            Action::GoToHeight => "go_to_height",
+            Action::NudgeUp => "nudge_up",
+            Action::NudgeDown => "nudge_down",
+            Action::FineNudgeUp => "fine_nudge_up",
+            Action::FineNudgeDown => "fine_nudge_down",
+            Action::ClearCount => "clear_count",
+            Action::ToggleReference => "toggle_reference",
+            Action::Select => "select",
+            Action::SelectPrev => "select_prev",
+            Action::SelectNext => "select_next",
+            Action::MoveItemUp => "move_up",
+            Action::MoveItemDown => "move_down",
+            Action::Add => "add",
+            Action::SaveCurrent => "save_current",
+            Action::Rename => "rename",
+            Action::Delete => "delete",
+            Action::Wizard => "wizard",
+            Action::Refresh => "refresh",
+            Action::Connect => "connect",
+            Action::PrevPeriod => "prev_period",
+            Action::NextPeriod => "next_period",
+            Action::Export => "export",
+            Action::ToggleFormat => "toggle_format",
+            Action::ZoomIn => "zoom_in",
+            Action::ZoomOut => "zoom_out",
+            Action::Close => "close",
+            Action::SkipNext => "skip_next",
+            Action::ClearHistory => "clear",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::NextView => "next section",
+            Action::PrevView => "previous section",
+            Action::ToggleSchedules => "schedules",
+            Action::CommandPalette => "commands",
+            Action::Help => "help",
+            Action::Notifications => "notifications",
+            Action::DismissNotifications => "dismiss notifications",
+            Action::ToggleZoom => "zoom section",
+            Action::GoToPosition(hotkey) => GO_TO_POSITION_DESCRIPTIONS[*hotkey as usize - 1],
+            Action::ToggleReminders => "toggle reminders",
+            Action::NextProfile => "next profile",
+            Action::PrevProfile => "previous profile",
+            Action::MoveUp => "up",
+            Action::MoveDown => "down",
+            Action::GoToHeight => "go to height",
+            Action::NudgeUp => "+1cm",
+            Action::NudgeDown => "-1cm",
+            Action::FineNudgeUp => "+5mm",
+            Action::FineNudgeDown => "-5mm",
+            Action::ClearCount => "clear count",
+            Action::ToggleReference => "reference",
+            Action::Select => "select",
+            Action::SelectPrev => "previous",
+            Action::SelectNext => "next",
+            Action::MoveItemUp => "move up",
+            Action::MoveItemDown => "move down",
+            Action::Add => "add",
+            Action::SaveCurrent => "save current height",
+            Action::Rename => "rename",
+            Action::Delete => "delete",
+            Action::Wizard => "ergonomic wizard",
+            Action::Refresh => "refresh",
+            Action::Connect => "connect",
+            Action::PrevPeriod => "previous period",
+            Action::NextPeriod => "next period",
+            Action::Export => "export period",
+            Action::ToggleFormat => "format",
+            Action::ZoomIn => "zoom in",
+            Action::ZoomOut => "zoom out",
+            Action::Close => "close",
+            Action::SkipNext => "skip next run",
+            Action::ClearHistory => "clear history",
+        }
+    }
+}
+
+const GO_TO_POSITION_NAMES: [&str; 9] = [
+    "position_1",
+    "position_2",
+    "position_3",
+    "position_4",
+    "position_5",
+    "position_6",
+    "position_7",
+    "position_8",
+    "position_9",
+];
+const GO_TO_POSITION_DESCRIPTIONS: [&str; 9] = [
+    "go to position 1",
+    "go to position 2",
+    "go to position 3",
+    "go to position 4",
+    "go to position 5",
+    "go to position 6",
+    "go to position 7",
+    "go to position 8",
+    "go to position 9",
+];
+
+const DEFAULT_BINDINGS: &[(KeymapScope, Action, &[&str])] = &[
+    (KeymapScope::Global, Action::Quit, &["q"]),
+    (KeymapScope::Global, Action::NextView, &["tab"]),
+    (KeymapScope::Global, Action::PrevView, &["backtab"]),
+    (KeymapScope::Global, Action::ToggleSchedules, &["s"]),
+    (
+        KeymapScope::Global,
+        Action::CommandPalette,
+        &[":", "ctrl+p"],
+    ),
+    (KeymapScope::Global, Action::Help, &["?"]),
+    (KeymapScope::Global, Action::Notifications, &["n"]),
+    (KeymapScope::Global, Action::DismissNotifications, &["X"]),
+    (KeymapScope::Global, Action::ToggleZoom, &["z"]),
+    (KeymapScope::Global, Action::GoToPosition(1), &["1"]),
+    (KeymapScope::Global, Action::GoToPosition(2), &["2"]),
+    (KeymapScope::Global, Action::GoToPosition(3), &["3"]),
+    (KeymapScope::Global, Action::GoToPosition(4), &["4"]),
+    (KeymapScope::Global, Action::GoToPosition(5), &["5"]),
+    (KeymapScope::Global, Action::GoToPosition(6), &["6"]),
+    (KeymapScope::Global, Action::GoToPosition(7), &["7"]),
+    (KeymapScope::Global, Action::GoToPosition(8), &["8"]),
+    (KeymapScope::Global, Action::GoToPosition(9), &["9"]),
+    (KeymapScope::Connection, Action::ToggleReminders, &["t"]),
+    (KeymapScope::Connection, Action::NextProfile, &["p"]),
+    (KeymapScope::Connection, Action::PrevProfile, &["P"]),
+    (KeymapScope::Movement, Action::MoveUp, &["u"]),
+    (KeymapScope::Movement, Action::MoveDown, &["d"]),
+    (KeymapScope::Movement, Action::GoToHeight, &["g"]),
+    (KeymapScope::Movement, Action::NudgeUp, &["+", "="]),
+    (KeymapScope::Movement, Action::NudgeDown, &["-"]),
+    // note: terminals report shift+`+`/`-` as other characters (`+` is often
+    // shift+`=` already), so the fine nudge sits on shift with the arrows
+    (KeymapScope::Movement, Action::FineNudgeUp, &["shift+up"]),
+    (
+        KeymapScope::Movement,
+        Action::FineNudgeDown,
+        &["shift+down"],
+    ),
+    (KeymapScope::Movement, Action::ClearCount, &["esc"]),
+    (KeymapScope::Movement, Action::ToggleReference, &["r"]),
+    (KeymapScope::Positions, Action::Select, &["enter"]),
+    (KeymapScope::Positions, Action::SelectPrev, &["up", "k"]),
+    (KeymapScope::Positions, Action::SelectNext, &["down", "j"]),
+    (KeymapScope::Positions, Action::MoveItemUp, &["K"]),
+    (KeymapScope::Positions, Action::MoveItemDown, &["J"]),
+    (KeymapScope::Positions, Action::Add, &["a"]),
+    (KeymapScope::Positions, Action::SaveCurrent, &["c"]),
+    (KeymapScope::Positions, Action::Rename, &["e"]),
+    (KeymapScope::Positions, Action::Delete, &["x"]),
+    (KeymapScope::Positions, Action::Wizard, &["w"]),
+    (KeymapScope::Devices, Action::Refresh, &["r"]),
+    (KeymapScope::Devices, Action::Connect, &["enter"]),
+    (KeymapScope::Devices, Action::SelectPrev, &["up", "k"]),
+    (KeymapScope::Devices, Action::SelectNext, &["down", "j"]),
+    (KeymapScope::Statistics, Action::PrevPeriod, &["left", "h"]),
+    (KeymapScope::Statistics, Action::NextPeriod, &["right", "l"]),
+    (KeymapScope::Statistics, Action::Export, &["e"]),
+    (KeymapScope::Statistics, Action::ToggleFormat, &["f"]),
+    (KeymapScope::Chart, Action::ZoomIn, &["+", "="]),
+    (KeymapScope::Chart, Action::ZoomOut, &["-"]),
+    (KeymapScope::Schedules, Action::Close, &["esc"]),
+    (KeymapScope::Schedules, Action::SelectPrev, &["up", "k"]),
+    (KeymapScope::Schedules, Action::SelectNext, &["down", "j"]),
+    (KeymapScope::Schedules, Action::SkipNext, &["x"]),
+    (KeymapScope::Notifications, Action::Close, &["esc"]),
+    (KeymapScope::Notifications, Action::SelectPrev, &["up", "k"]),
+    (
+        KeymapScope::Notifications,
+        Action::SelectNext,
+        &["down", "j"],
+    ),
+    (KeymapScope::Notifications, Action::ClearHistory, &["c"]),
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum KeyList {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeyList {
+    fn keys(&self) -> Vec<&str> {
+        match self {
+            KeyList::One(key) => vec![key.as_str()],
+            KeyList::Many(keys) => keys.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyBinding {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    const RELEVANT_MODIFIERS: KeyModifiers = KeyModifiers::CONTROL
+        .union(KeyModifiers::ALT)
+        .union(KeyModifiers::SHIFT);
+
+    /// Parses keys such as `q`, `K`, `enter`, `shift+up` or `ctrl+d`.
+    pub fn parse(key: &str) -> Result<Self, String> {
+        let (modifiers, name) = match key.strip_suffix("++").filter(|m| !m.is_empty()) {
+            Some(modifiers) => (Some(modifiers), "+"),
+            None => match key.rsplit_once('+') {
+                Some((modifiers, name)) if !modifiers.is_empty() && !name.is_empty() => {
+                    (Some(modifiers), name)
+                }
+                _ => (None, key),
+            },
+        };
+
+        let mut binding = Self {
+            code: Self::parse_code(name).ok_or_else(|| format!("unknown key '{}'", key))?,
+            modifiers: KeyModifiers::NONE,
+        };
+        for modifier in modifiers.into_iter().flat_map(|m| m.split('+')) {
+            binding.modifiers |= match modifier.to_lowercase().as_str() {
+                "ctrl" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(format!("unknown modifier in '{}'", key)),
+            };
+        }
+
+        Ok(binding.normalized())
+    }
+
+    fn parse_code(name: &str) -> Option<KeyCode> {
+        let mut chars = name.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return Some(KeyCode::Char(c));
+        }
+
+        Some(match name.to_lowercase().as_str() {
+            "enter" => KeyCode::Enter,
+            "esc" => KeyCode::Esc,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "backspace" => KeyCode::Backspace,
+            "delete" => KeyCode::Delete,
+            "space" => KeyCode::Char(' '),
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            name => KeyCode::F(name.strip_prefix('f')?.parse().ok()?),
+        })
+    }
+
+    // note: shift is already part of characters and of backtab
+    fn normalized(mut self) -> Self {
+        self.modifiers &= Self::RELEVANT_MODIFIERS;
+        match self.code {
+            KeyCode::Char(_) | KeyCode::BackTab => self.modifiers.remove(KeyModifiers::SHIFT),
+            KeyCode::Tab if self.modifiers.contains(KeyModifiers::SHIFT) => {
+                self.code = KeyCode::BackTab;
+                self.modifiers.remove(KeyModifiers::SHIFT)
+            }
+            _ => (),
+        }
+        self
+    }
+
+    // note: digits without modifiers, as typed for a count
+    fn count_digit(&self) -> Option<u16> {
+        match self.code {
+            KeyCode::Char(c) if self.modifiers.is_empty() => c.to_digit(10).map(|d| d as u16),
+            _ => None,
+        }
+    }
+
+    fn from_event(ev: &KeyEvent) -> Self {
+        Self {
+            code: ev.code,
+            modifiers: ev.modifiers,
+        }
+        .normalized()
+    }
+
+    pub fn label(&self) -> String {
+        let mut label = String::new();
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "ctrl+"),
+            (KeyModifiers::ALT, "alt+"),
+            (KeyModifiers::SHIFT, "shift+"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                label.push_str(name)
+            }
+        }
+
+        label.push_str(&match self.code {
+            KeyCode::Char(' ') => "<space>".to_string(),
+            KeyCode::Char(c) => c.to_string(),
+            KeyCode::Enter => "<enter>".to_string(),
+            KeyCode::Esc => "<esc>".to_string(),
+            KeyCode::Tab => "<tab>".to_string(),
+            KeyCode::BackTab => "<backtab>".to_string(),
+            KeyCode::Backspace => "<backspace>".to_string(),
+            KeyCode::F(n) => format!("<f{}>", n),
+            code => format!("{:?}", code).to_lowercase(),
+        });
+        label
+    }
+}
+
+// note: keys handled outside of the keymap, which cannot be rebound
+const FIXED_BINDINGS: [(KeymapScope, &str, &str); 1] =
+    [(KeymapScope::Movement, "0-9", "count (e.g. 3+)")];
+
+/// Dialogs and overlays, whose keys are not part of the keymap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DialogScope {
+    Input,
+    Confirm,
+    Picker,
+    Palette,
+    Help,
+}
+
+impl DialogScope {
+    pub const ALL: [DialogScope; 5] = [
+        DialogScope::Input,
+        DialogScope::Confirm,
+        DialogScope::Picker,
+        DialogScope::Palette,
+        DialogScope::Help,
+    ];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            DialogScope::Input => "Text inputs",
+            DialogScope::Confirm => "Confirmations",
+            DialogScope::Picker => "Lists to pick from",
+            DialogScope::Palette => "Command palette",
+            DialogScope::Help => "Key bindings help",
+        }
+    }
+}
+
+// note: shared by the help bars of the dialogs and the help overlay. These
+// keys are left out of the keymap on purpose: confirming and cancelling work
+// the same everywhere, and no keymap can lock a dialog or the help overlay
+// that shows the active bindings
+const DIALOG_BINDINGS: &[(DialogScope, &str, &str)] = &[
+    (DialogScope::Input, "<enter>", "confirm"),
+    (DialogScope::Input, "<esc>", "cancel"),
+    (DialogScope::Confirm, "y/<enter>", "confirm"),
+    (DialogScope::Confirm, "n/<esc>", "cancel"),
+    (DialogScope::Picker, "<up>/<down>/k/j", "select"),
+    (DialogScope::Picker, "<enter>", "pick"),
+    (DialogScope::Picker, "<esc>", "cancel"),
+    (DialogScope::Palette, "<enter>", "run"),
+    (DialogScope::Palette, "<up>/<down>", "select"),
+    (DialogScope::Palette, "ctrl+p/ctrl+n", "select"),
+    (DialogScope::Palette, "<esc>", "close"),
+    (DialogScope::Help, "<up>/<down>", "scroll"),
+    (DialogScope::Help, "<pgup>/<pgdn>", "page"),
+    (DialogScope::Help, "<home>/<end>", "top/bottom"),
+    (DialogScope::Help, "type", "search"),
+    (DialogScope::Help, "<esc>", "clear/close"),
+];
+
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: HashMap<(KeymapScope, Action), Vec<KeyBinding>>,
+}
+
+impl Keymap {
+    fn default_bindings() -> HashMap<(KeymapScope, Action), Vec<KeyBinding>> {
+        DEFAULT_BINDINGS
+            .iter()
+            .map(|(scope, action, keys)| {
+                (
+                    (*scope, *action),
+                    keys.iter()
+                        .map(|key| KeyBinding::parse(key).unwrap())
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+
+    /// Applies the user overrides to the default bindings. When anything is
+    /// wrong, the default keymap is returned together with the problems found.
+    pub fn new(overrides: &HashMap<String, HashMap<String, KeyList>>) -> (Self, Vec<String>) {
+        let defaults = Self {
+            bindings: Self::default_bindings(),
+        };
+
+        let mut bindings = defaults.bindings.clone();
+        let mut errors = Vec::new();
+
+        for (scope_name, actions) in overrides {
+            let scope = match KeymapScope::ALL.iter().find(|s| s.name() == scope_name) {
+                Some(scope) => *scope,
+                None => {
+                    errors.push(format!("unknown keymap section '{}'", scope_name));
+                    continue;
+                }
+            };
+
+            for (action_name, keys) in actions {
+                let action = DEFAULT_BINDINGS
+                    .iter()
+                    .find(|(s, a, _)| *s == scope && a.name() == action_name)
+                    .map(|(_, a, _)| *a);
+                let action = match action {
+                    Some(action) => action,
+                    None => {
+                        errors.push(format!("unknown action '{}.{}'", scope_name, action_name));
+                        continue;
+                    }
+                };
+
+                match keys.keys().into_iter().map(KeyBinding::parse).collect() {
+                    Ok(keys) => {
+                        bindings.insert((scope, action), keys);
+                    }
+                    Err(e) => errors.push(format!("{}.{}: {}", scope_name, action_name, e)),
+                }
+            }
+        }
+
+        let keymap = Self { bindings };
+        errors.extend(keymap.conflicts());
+
+        if errors.is_empty() {
+            (keymap, errors)
+        } else {
+            (defaults, errors)
+        }
+    }
+
+    // note: a view binding cannot reuse a global key, since both are active,
+    // nor a count digit of the Movement section
+    fn conflicts(&self) -> Vec<String> {
+        let mut conflicts = Vec::new();
+
+        for scope in KeymapScope::ALL {
+            let mut used: HashMap<KeyBinding, (KeymapScope, Action)> = HashMap::new();
+            let scopes = if scope == KeymapScope::Global {
+                vec![KeymapScope::Global]
+            } else {
+                vec![KeymapScope::Global, scope]
+            };
+
+            for ((s, action), keys) in self.sorted_bindings() {
+                if !scopes.contains(&s) {
+                    continue;
+                }
+                let is_shadowed = |key: &KeyBinding| {
+                    scope == KeymapScope::Movement
+                        && matches!(action, Action::GoToPosition(_))
+                        && key.count_digit().is_some()
+                };
+                for key in keys.iter().filter(|k| !is_shadowed(k)) {
+                    match used.get(key) {
+                        // note: conflicts among globals are only reported once
+                        Some((other_scope, _))
+                            if *other_scope == KeymapScope::Global
+                                && s == KeymapScope::Global
+                                && scope != KeymapScope::Global => {}
+                        Some((other_scope, other)) => conflicts.push(format!(
+                            "'{}' is bound to both {}.{} and {}.{}",
+                            key.label(),
+                            other_scope.name(),
+                            other.name(),
+                            s.name(),
+                            action.name()
+                        )),
+                        None => {
+                            used.insert(*key, (s, action));
+                        }
+                    }
+                }
+            }
+        }
+
+        // note: only the position hotkeys give way to the count digits
+        for ((s, action), keys) in self.sorted_bindings() {
+            let is_active_in_movement = match s {
+                KeymapScope::Global => !matches!(action, Action::GoToPosition(_)),
+                KeymapScope::Movement => true,
+                _ => false,
+            };
+            if !is_active_in_movement {
+                continue;
+            }
+            for key in keys.iter().filter(|k| k.count_digit().is_some()) {
+                conflicts.push(format!(
+                    "'{}' is bound to both {}.count and {}.{}",
+                    key.label(),
+                    KeymapScope::Movement.name(),
+                    s.name(),
+                    action.name()
+                ))
+            }
+        }
+
+        conflicts
+    }
+
+    // note: keeps the reported conflicts stable across runs
+    fn sorted_bindings(&self) -> Vec<((KeymapScope, Action), &Vec<KeyBinding>)> {
+        DEFAULT_BINDINGS
+            .iter()
+            .filter_map(|(scope, action, _)| {
+                let keys = self.bindings.get(&(*scope, *action))?;
+                Some(((*scope, *action), keys))
+            })
+            .collect()
+    }
+
+    pub fn action(&self, scope: KeymapScope, ev: &KeyEvent) -> Option<Action> {
+        let pressed = KeyBinding::from_event(ev);
+        DEFAULT_BINDINGS
+            .iter()
+            .filter(|(s, _, _)| *s == scope)
+            .find(|(s, a, _)| {
+                self.bindings
+                    .get(&(*s, *a))
+                    .is_some_and(|keys| keys.contains(&pressed))
+            })
+            .map(|(_, a, _)| *a)
+    }
+
+    /// Global action of a key pressed in the given section. In the Movement
+    /// section the digits type a count (e.g. `3+`) instead of going to the
+    /// saved positions.
+    pub fn global_action(&self, scope: KeymapScope, ev: &KeyEvent) -> Option<Action> {
+        self.action(KeymapScope::Global, ev).filter(|action| {
+            !matches!(action, Action::GoToPosition(_)) || Self::count_digit(scope, ev).is_none()
+        })
+    }
+
+    /// Digit of the count typed in the given section, if the key is one.
+    pub fn count_digit(scope: KeymapScope, ev: &KeyEvent) -> Option<u16> {
+        match scope {
+            KeymapScope::Movement => KeyBinding::from_event(ev).count_digit(),
+            _ => None,
+        }
+    }
+
+    pub fn keys_label(&self, scope: KeymapScope, action: Action) -> Option<String> {
+        let keys = self.bindings.get(&(scope, action))?;
+        if keys.is_empty() {
+            return None;
+        }
+        Some(
+            keys.iter()
+                .map(KeyBinding::label)
+                .collect::<Vec<String>>()
+                .join("/"),
+        )
+    }
+
+    /// Help bar labels of the given actions, skipping the unbound ones.
+    pub fn help(&self, scope: KeymapScope, actions: &[Action]) -> Vec<String> {
+        actions
+            .iter()
+            .filter_map(|action| self.help_with(scope, *action, action.description()))
+            .collect()
+    }
+
+    pub fn help_with(
+        &self,
+        scope: KeymapScope,
+        action: Action,
+        description: &str,
+    ) -> Option<String> {
+        self.keys_label(scope, action)
+            .map(|keys| format!("{}: {}", keys, description))
+    }
+
+    /// Help bar label of the saved position hotkeys, shortened when they
+    /// are the digits.
+    pub fn positions_help(&self) -> Option<String> {
+        let keys: Vec<String> = (1..=GO_TO_POSITION_NAMES.len() as u8)
+            .filter_map(|hotkey| self.keys_label(KeymapScope::Global, Action::GoToPosition(hotkey)))
+            .collect();
+        let digits: Vec<String> = (1..=keys.len()).map(|n| n.to_string()).collect();
+
+        match keys.len() {
+            0 => None,
+            len if keys == digits => Some(format!("1-{}: go to position", len)),
+            _ => Some(format!("{}: go to position", keys.join("/"))),
+        }
+    }
+
+    /// Help bar labels of the keys that are not part of the keymap.
+    pub fn fixed_help(scope: KeymapScope) -> Vec<String> {
+        FIXED_BINDINGS
+            .iter()
+            .filter(|(s, _, _)| *s == scope)
+            .map(|(_, keys, description)| format!("{}: {}", keys, description))
+            .collect()
+    }
+
+    /// Help bar labels of a dialog, the given descriptions replacing the
+    /// default ones in order (e.g. what confirming does).
+    pub fn dialog_help(scope: DialogScope, descriptions: &[&str]) -> Vec<String> {
+        Self::dialog_bindings(scope)
+            .into_iter()
+            .enumerate()
+            .map(|(idx, (keys, default))| {
+                let description = descriptions.get(idx).copied().unwrap_or(default);
+                format!("{}: {}", keys, description)
+            })
+            .collect()
+    }
+
+    pub fn dialog_bindings(scope: DialogScope) -> Vec<(String, &'static str)> {
+        DIALOG_BINDINGS
+            .iter()
+            .filter(|(s, _, _)| *s == scope)
+            .map(|(_, keys, description)| (keys.to_string(), *description))
+            .collect()
+    }
+
+    /// Every bound key of the given section, with its description.
+    pub fn bindings(&self, scope: KeymapScope) -> Vec<(String, &'static str)> {
+        DEFAULT_BINDINGS
+            .iter()
+            .filter(|(s, _, _)| *s == scope)
+            .filter_map(|(s, action, _)| {
+                Some((self.keys_label(*s, *action)?, action.description()))
+            })
+            .chain(
+                FIXED_BINDINGS
+                    .iter()
+                    .filter(|(s, _, _)| *s == scope)
+                    .map(|(_, keys, description)| (keys.to_string(), *description)),
+            )
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keymap_with(overrides: &[(&str, &str, &str)]) -> (Keymap, Vec<String>) {
+        let mut sections: HashMap<String, HashMap<String, KeyList>> = HashMap::new();
+        for (scope, action, key) in overrides {
+            sections
+                .entry(scope.to_string())
+                .or_default()
+                .insert(action.to_string(), KeyList::One(key.to_string()));
+        }
+        Keymap::new(&sections)
+    }
+
+    fn press(key: &str) -> KeyEvent {
+        let binding = KeyBinding::parse(key).unwrap();
+        KeyEvent::new(binding.code, binding.modifiers)
+    }
+
+    #[test]
+    fn digits_go_to_the_positions_outside_of_the_movement_section() {
+        let (keymap, errors) = keymap_with(&[]);
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        assert_eq!(
+            keymap.global_action(KeymapScope::Positions, &press("3")),
+            Some(Action::GoToPosition(3))
+        );
+        assert_eq!(
+            keymap.global_action(KeymapScope::Movement, &press("3")),
+            None
+        );
+        assert_eq!(
+            Keymap::count_digit(KeymapScope::Movement, &press("3")),
+            Some(3)
+        );
+        assert_eq!(
+            Keymap::count_digit(KeymapScope::Positions, &press("3")),
+            None
+        );
+        assert_eq!(
+            keymap.global_action(KeymapScope::Movement, &press("q")),
+            Some(Action::Quit)
+        );
+    }
+
+    #[test]
+    fn position_hotkeys_off_the_digits_work_in_the_movement_section() {
+        let (keymap, errors) = keymap_with(&[("global", "position_3", "f3")]);
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        assert_eq!(
+            keymap.global_action(KeymapScope::Movement, &press("f3")),
+            Some(Action::GoToPosition(3))
+        );
+        assert_eq!(
+            keymap.global_action(KeymapScope::Positions, &press("3")),
+            None
+        );
+    }
+
+    #[test]
+    fn reports_the_keys_taken_from_the_movement_count() {
+        let (keymap, errors) = keymap_with(&[("movement", "nudge_up", "3")]);
+        assert_eq!(
+            errors,
+            vec!["'3' is bound to both movement.count and movement.nudge_up"]
+        );
+        assert_eq!(
+            keymap.action(KeymapScope::Movement, &press("+")),
+            Some(Action::NudgeUp)
+        );
+
+        let (_, errors) = keymap_with(&[("global", "quit", "0")]);
+        assert_eq!(
+            errors,
+            vec!["'0' is bound to both movement.count and global.quit"]
+        );
+    }
+
+    #[test]
+    fn reports_a_movement_key_taken_by_a_global_one() {
+        let (keymap, errors) = keymap_with(&[("movement", "up", "q")]);
+        assert_eq!(
+            errors,
+            vec!["'q' is bound to both global.quit and movement.up"]
+        );
+        assert_eq!(
+            keymap.action(KeymapScope::Movement, &press("u")),
+            Some(Action::MoveUp)
+        );
+    }
+
+    #[test]
+    fn parses_keys_with_modifiers() {
+        let key = |code, modifiers| KeyBinding { code, modifiers };
+
+        assert_eq!(
+            KeyBinding::parse("q"),
+            Ok(key(KeyCode::Char('q'), KeyModifiers::NONE))
+        );
+        assert_eq!(
+            KeyBinding::parse("K"),
+            Ok(key(KeyCode::Char('K'), KeyModifiers::NONE))
+        );
+        assert_eq!(
+            KeyBinding::parse("Enter"),
+            Ok(key(KeyCode::Enter, KeyModifiers::NONE))
+        );
+        assert_eq!(
+            KeyBinding::parse("space"),
+            Ok(key(KeyCode::Char(' '), KeyModifiers::NONE))
+        );
+        assert_eq!(
+            KeyBinding::parse("f12"),
+            Ok(key(KeyCode::F(12), KeyModifiers::NONE))
+        );
+        assert_eq!(
+            KeyBinding::parse("shift+up"),
+            Ok(key(KeyCode::Up, KeyModifiers::SHIFT))
+        );
+        assert_eq!(
+            KeyBinding::parse("Ctrl+alt+d"),
+            Ok(key(
+                KeyCode::Char('d'),
+                KeyModifiers::CONTROL | KeyModifiers::ALT
+            ))
+        );
+        assert_eq!(
+            KeyBinding::parse("+"),
+            Ok(key(KeyCode::Char('+'), KeyModifiers::NONE))
+        );
+        assert_eq!(
+            KeyBinding::parse("ctrl++"),
+            Ok(key(KeyCode::Char('+'), KeyModifiers::CONTROL))
+        );
+
+        // note: shift is part of the character typed
+        assert_eq!(KeyBinding::parse("shift+K"), KeyBinding::parse("K"));
+        assert_eq!(KeyBinding::parse("shift+tab"), KeyBinding::parse("backtab"));
+        assert_eq!(KeyBinding::parse("ctrl+p").unwrap().label(), "ctrl+p");
+
+        for key in ["", "enterr", "f0x", "hyper+a", "ctrl+", "ctrl+foo"] {
+            assert!(KeyBinding::parse(key).is_err(), "{:?}", key);
+        }
+    }
+
+    #[test]
+    fn reports_the_keys_bound_twice() {
+        let (_, errors) = keymap_with(&[("movement", "down", "u")]);
+        assert_eq!(
+            errors,
+            vec!["'u' is bound to both movement.up and movement.down"]
+        );
+
+        // note: reported once, not for every section
+        let (_, errors) = keymap_with(&[("global", "help", "q")]);
+        assert_eq!(
+            errors,
+            vec!["'q' is bound to both global.quit and global.help"]
+        );
+
+        // note: sections are never active together
+        let (keymap, errors) = keymap_with(&[("devices", "refresh", "w")]);
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(
+            keymap.action(KeymapScope::Devices, &press("w")),
+            Some(Action::Refresh)
+        );
+        assert_eq!(
+            keymap.action(KeymapScope::Positions, &press("w")),
+            Some(Action::Wizard)
+        );
+    }
+
+    #[test]
+    fn falls_back_to_the_default_keymap() {
+        let (keymap, mut errors) = keymap_with(&[
+            ("movement", "up", "k"),
+            ("movement", "jump", "x"),
+            ("nowhere", "up", "x"),
+            ("positions", "add", "hyper+a"),
+        ]);
+        errors.sort();
+        assert_eq!(
+            errors,
+            vec![
+                "positions.add: unknown modifier in 'hyper+a'",
+                "unknown action 'movement.jump'",
+                "unknown keymap section 'nowhere'",
+            ]
+        );
+
+        // note: the valid overrides are dropped too
+        assert_eq!(
+            keymap.action(KeymapScope::Movement, &press("u")),
+            Some(Action::MoveUp)
+        );
+        assert_eq!(keymap.action(KeymapScope::Movement, &press("k")), None);
+        assert_eq!(
+            keymap.action(KeymapScope::Positions, &press("a")),
+            Some(Action::Add)
+        );
+    }
+
+    #[test]
+    fn dialog_help_replaces_the_descriptions_in_order() {
+        assert_eq!(
+            Keymap::dialog_help(DialogScope::Input, &["move"]),
+            vec!["<enter>: move", "<esc>: cancel"]
+        );
+        assert_eq!(
+            Keymap::dialog_help(DialogScope::Confirm, &["save", "close"]),
+            vec!["y/<enter>: save", "n/<esc>: close"]
+        );
+        assert!(DialogScope::ALL
+            .iter()
+            .all(|scope| !Keymap::dialog_bindings(*scope).is_empty()));
+    }
+}
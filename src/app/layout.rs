@@ -0,0 +1,382 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    widgets::Padding,
+};
+
+use super::{
+    app::Views,
+    views::{
+        connections::view::{CONNECTION_CONTENT_HEIGHT, CONNECTION_MIN_HEIGHT},
+        movement::view::{MOVEMENT_CONTENT_HEIGHT, MOVEMENT_MIN_HEIGHT, MOVEMENT_MIN_WIDTH},
+    },
+};
+
+const BORDERS_SIZE: u16 = 2;
+const PADDING_X: u16 = 4;
+const PADDING_Y: u16 = 2;
+// note: a section this tall shows all of its content, padding included
+const SECTION_HEIGHT: u16 =
+    max(CONNECTION_CONTENT_HEIGHT, MOVEMENT_CONTENT_HEIGHT) + BORDERS_SIZE + 2 * PADDING_Y;
+const TOP_ROW_MIN_HEIGHT: u16 = max(CONNECTION_MIN_HEIGHT, MOVEMENT_MIN_HEIGHT);
+// note: the borders and a few rows
+const LIST_MIN_HEIGHT: u16 = 5;
+const CHART_HEIGHT: u16 = 8;
+const CHART_MIN_WIDTH: u16 = 20;
+// note: the height chart shares the row and needs a few lines to be readable
+const STATISTICS_HEIGHT: u16 = 9;
+const STATISTICS_WIDTH: u16 = 58;
+const HELP_BAR_HEIGHT: u16 = 3;
+const COMPACT_HELP_BAR_HEIGHT: u16 = 1;
+const TABS_HEIGHT: u16 = 1;
+
+const GRID_MIN_WIDTH: u16 = max(2 * MOVEMENT_MIN_WIDTH, STATISTICS_WIDTH + CHART_MIN_WIDTH);
+const GRID_MIN_HEIGHT: u16 =
+    TOP_ROW_MIN_HEIGHT + LIST_MIN_HEIGHT + STATISTICS_HEIGHT + HELP_BAR_HEIGHT;
+const STACKED_MIN_WIDTH: u16 = max(MOVEMENT_MIN_WIDTH, STATISTICS_WIDTH);
+const STACKED_MIN_HEIGHT: u16 = CONNECTION_MIN_HEIGHT
+    + MOVEMENT_MIN_HEIGHT
+    + 2 * LIST_MIN_HEIGHT
+    + STATISTICS_HEIGHT
+    + CHART_HEIGHT
+    + HELP_BAR_HEIGHT;
+
+const fn max(a: u16, b: u16) -> u16 {
+    if a > b {
+        a
+    } else {
+        b
+    }
+}
+
+/// Padding of a section, on the sides where it leaves room for the content.
+pub fn section_padding(area: Rect, content_width: u16, content_height: u16) -> Padding {
+    let x = if area.width >= content_width + BORDERS_SIZE + 2 * PADDING_X {
+        PADDING_X
+    } else {
+        0
+    };
+    let y = if area.height >= content_height + BORDERS_SIZE + 2 * PADDING_Y {
+        PADDING_Y
+    } else {
+        0
+    };
+    Padding::new(x, x, y, y)
+}
+
+/// How the sections are arranged, picked from the size of the terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayoutMode {
+    // note: four sections in a grid, the statistics and the chart below
+    Grid,
+    // note: every section in a single column, for narrow terminals
+    Stacked,
+    // note: the focused section only, asked for by the user
+    Zoomed,
+    // note: the focused section only, with one line bars, for small terminals
+    Compact,
+}
+
+impl LayoutMode {
+    pub fn for_size(size: Rect, is_zoomed: bool) -> Self {
+        let fits_grid = size.width >= GRID_MIN_WIDTH && size.height >= GRID_MIN_HEIGHT;
+        let fits_stacked = size.width >= STACKED_MIN_WIDTH && size.height >= STACKED_MIN_HEIGHT;
+
+        if !fits_grid && !fits_stacked {
+            LayoutMode::Compact
+        } else if is_zoomed {
+            LayoutMode::Zoomed
+        } else if fits_grid {
+            LayoutMode::Grid
+        } else {
+            LayoutMode::Stacked
+        }
+    }
+
+    pub fn shows_tabs(&self) -> bool {
+        matches!(self, LayoutMode::Zoomed | LayoutMode::Compact)
+    }
+}
+
+/// Areas of the screen for a given layout mode.
+pub struct ScreenLayout {
+    pub mode: LayoutMode,
+    // note: overlays cover the whole area above the help bar
+    pub view_area: Rect,
+    pub bar_area: Rect,
+    pub tabs_area: Option<Rect>,
+    // note: sections left out are not drawn
+    pub sections: Vec<(Views, Rect)>,
+}
+
+impl ScreenLayout {
+    pub fn new(size: Rect, focused: Views, is_zoomed: bool) -> Self {
+        let mode = LayoutMode::for_size(size, is_zoomed);
+        let bar_height = match mode {
+            LayoutMode::Compact => COMPACT_HELP_BAR_HEIGHT,
+            _ => HELP_BAR_HEIGHT,
+        };
+
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(bar_height)])
+            .split(size);
+        let (view_area, bar_area) = (layout[0], layout[1]);
+
+        let (tabs_area, sections_area) = if mode.shows_tabs() {
+            let layout = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(TABS_HEIGHT), Constraint::Min(0)])
+                .split(view_area);
+            (Some(layout[0]), layout[1])
+        } else {
+            (None, view_area)
+        };
+
+        let sections = match mode {
+            LayoutMode::Grid => Self::grid(sections_area),
+            LayoutMode::Stacked => Self::stacked(sections_area),
+            LayoutMode::Zoomed | LayoutMode::Compact => vec![(focused, sections_area)],
+        };
+
+        Self {
+            mode,
+            view_area,
+            bar_area,
+            tabs_area,
+            sections,
+        }
+    }
+
+    fn grid(area: Rect) -> Vec<(Views, Rect)> {
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(STATISTICS_HEIGHT)])
+            .split(area);
+        let (grid_area, bottom_area) = (layout[0], layout[1]);
+
+        let bottom = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(STATISTICS_WIDTH), Constraint::Min(0)])
+            .split(bottom_area);
+
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(grid_area);
+
+        // note: half of the height, shrunk down to the minimum to leave a row to the lists
+        let top_height = (grid_area.height / 2)
+            .max(SECTION_HEIGHT.min(grid_area.height.saturating_sub(LIST_MIN_HEIGHT)))
+            .max(TOP_ROW_MIN_HEIGHT)
+            .min(grid_area.height);
+        let rows = |area: Rect| {
+            Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(top_height), Constraint::Min(0)])
+                .split(area)
+        };
+        let (left, right) = (rows(columns[0]), rows(columns[1]));
+
+        vec![
+            (Views::Connection, left[0]),
+            (Views::Movement, right[0]),
+            (Views::SavedPositions, left[1]),
+            (Views::DeviceList, right[1]),
+            (Views::Statistics, bottom[0]),
+            (Views::HeightChart, bottom[1]),
+        ]
+    }
+
+    // note: the first sections grow up to their full content, the lists share what is left
+    fn stacked(area: Rect) -> Vec<(Views, Rect)> {
+        let mut spare = area
+            .height
+            .saturating_sub(STACKED_MIN_HEIGHT - HELP_BAR_HEIGHT);
+        let mut grow = |min_height: u16| {
+            let extra = spare.min(SECTION_HEIGHT.saturating_sub(min_height));
+            spare -= extra;
+            min_height + extra
+        };
+        let (connection_height, movement_height) =
+            (grow(CONNECTION_MIN_HEIGHT), grow(MOVEMENT_MIN_HEIGHT));
+
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(connection_height),
+                Constraint::Length(movement_height),
+                Constraint::Min(LIST_MIN_HEIGHT),
+                Constraint::Min(LIST_MIN_HEIGHT),
+                Constraint::Length(STATISTICS_HEIGHT),
+                Constraint::Length(CHART_HEIGHT),
+            ])
+            .split(area);
+
+        vec![
+            (Views::Connection, layout[0]),
+            (Views::Movement, layout[1]),
+            (Views::SavedPositions, layout[2]),
+            (Views::DeviceList, layout[3]),
+            (Views::Statistics, layout[4]),
+            (Views::HeightChart, layout[5]),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::{
+        backend::TestBackend,
+        widgets::{Block, Borders},
+        Terminal,
+    };
+
+    use crate::tui::help_bar;
+
+    use super::*;
+
+    const VIEWS: [Views; 6] = [
+        Views::Connection,
+        Views::Movement,
+        Views::SavedPositions,
+        Views::DeviceList,
+        Views::Statistics,
+        Views::HeightChart,
+    ];
+
+    fn contains(outer: Rect, inner: Rect) -> bool {
+        inner.x >= outer.x
+            && inner.y >= outer.y
+            && inner.right() <= outer.right()
+            && inner.bottom() <= outer.bottom()
+    }
+
+    fn assert_fits(layout: &ScreenLayout, size: Rect) {
+        let section = |view: Views| {
+            layout
+                .sections
+                .iter()
+                .find(|(v, _)| *v == view)
+                .map(|(_, area)| *area)
+                .unwrap()
+        };
+        let (connection, movement) = (section(Views::Connection), section(Views::Movement));
+        let (positions, devices) = (section(Views::SavedPositions), section(Views::DeviceList));
+        let (statistics, chart) = (section(Views::Statistics), section(Views::HeightChart));
+
+        assert!(connection.height >= CONNECTION_MIN_HEIGHT, "{:?}", size);
+        assert!(movement.width >= MOVEMENT_MIN_WIDTH, "{:?}", size);
+        assert!(movement.height >= MOVEMENT_MIN_HEIGHT, "{:?}", size);
+        assert!(positions.height >= LIST_MIN_HEIGHT, "{:?}", size);
+        assert!(devices.height >= LIST_MIN_HEIGHT, "{:?}", size);
+        assert!(statistics.width >= STATISTICS_WIDTH, "{:?}", size);
+        assert_eq!(statistics.height, STATISTICS_HEIGHT, "{:?}", size);
+        match layout.mode {
+            LayoutMode::Grid => assert!(chart.width >= CHART_MIN_WIDTH, "{:?}", size),
+            _ => assert_eq!(chart.height, CHART_HEIGHT, "{:?}", size),
+        }
+    }
+
+    #[test]
+    fn picks_a_mode_that_fits_every_size() {
+        let mut modes = Vec::new();
+
+        for width in 1..=GRID_MIN_WIDTH + 10 {
+            for height in 1..=STACKED_MIN_HEIGHT + 10 {
+                let size = Rect::new(0, 0, width, height);
+                let fits_grid = width >= GRID_MIN_WIDTH && height >= GRID_MIN_HEIGHT;
+                let fits_stacked = width >= STACKED_MIN_WIDTH && height >= STACKED_MIN_HEIGHT;
+
+                for is_zoomed in [false, true] {
+                    for focused in VIEWS {
+                        let layout = ScreenLayout::new(size, focused, is_zoomed);
+                        if !modes.contains(&layout.mode) {
+                            modes.push(layout.mode);
+                        }
+
+                        match layout.mode {
+                            LayoutMode::Grid => assert!(fits_grid && !is_zoomed),
+                            LayoutMode::Stacked => assert!(fits_stacked && !fits_grid),
+                            LayoutMode::Zoomed => assert!(fits_grid || fits_stacked),
+                            LayoutMode::Compact => assert!(!fits_grid && !fits_stacked),
+                        }
+                        assert_eq!(layout.tabs_area.is_some(), layout.mode.shows_tabs());
+                        assert!(
+                            contains(size, layout.view_area) && contains(size, layout.bar_area)
+                        );
+                        for (_, area) in &layout.sections {
+                            assert!(contains(layout.view_area, *area), "{:?}", size);
+                        }
+
+                        match layout.mode {
+                            LayoutMode::Grid | LayoutMode::Stacked => {
+                                assert_eq!(layout.sections.len(), VIEWS.len());
+                                assert_fits(&layout, size)
+                            }
+                            LayoutMode::Zoomed | LayoutMode::Compact => {
+                                assert_eq!(layout.sections, vec![(focused, layout.sections[0].1)])
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        assert_eq!(modes.len(), 4, "{:?}", modes);
+    }
+
+    // note: every width and height, each against the sizes around the breakpoints
+    fn swept_sizes() -> Vec<(u16, u16)> {
+        let widths = [
+            1,
+            2,
+            STACKED_MIN_WIDTH - 1,
+            STACKED_MIN_WIDTH,
+            GRID_MIN_WIDTH - 1,
+            GRID_MIN_WIDTH,
+        ];
+        let heights = [
+            1,
+            2,
+            GRID_MIN_HEIGHT - 1,
+            GRID_MIN_HEIGHT,
+            STACKED_MIN_HEIGHT - 1,
+            STACKED_MIN_HEIGHT,
+        ];
+
+        (1..=GRID_MIN_WIDTH + 10)
+            .flat_map(|width| heights.map(|height| (width, height)))
+            .chain(
+                (1..=STACKED_MIN_HEIGHT + 10)
+                    .flat_map(|height| widths.map(|width| (width, height))),
+            )
+            .collect()
+    }
+
+    #[test]
+    fn draws_every_mode_at_any_size() {
+        let mut terminal = Terminal::new(TestBackend::new(1, 1)).unwrap();
+
+        for (width, height) in swept_sizes() {
+            terminal.backend_mut().resize(width, height);
+            for is_zoomed in [false, true] {
+                terminal
+                    .draw(|frame| {
+                        let layout = ScreenLayout::new(frame.size(), Views::Movement, is_zoomed);
+                        for (view, area) in layout.sections {
+                            frame.render_widget(
+                                Block::default().borders(Borders::ALL).title(view.label()),
+                                area,
+                            );
+                        }
+                        help_bar::draw(
+                            frame,
+                            layout.bar_area,
+                            vec!["q: quit".to_string(), "?: help".to_string()],
+                        );
+                    })
+                    .unwrap();
+            }
+        }
+    }
+}
@@ -2,9 +2,19 @@ pub mod app;
 pub mod config;
 
 mod actions;
+mod layout;
+mod palette;
 mod state;
 mod views;
 
-mod ui_event_emitter;
+pub mod ui_event_emitter;
+
+pub mod ergonomics;
+pub mod export;
+pub mod height;
+pub mod history;
+pub mod keymap;
+pub mod schedule;
+pub mod zone;
 
 pub mod jobs;
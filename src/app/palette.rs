@@ -0,0 +1,145 @@
+use std::cmp::Reverse;
+
+use crate::tui::modal::TextInput;
+
+use super::{actions::AppAction, app::Views, height::HeightUnit};
+
+const CONSECUTIVE_BONUS: u32 = 5;
+const WORD_START_BONUS: u32 = 3;
+
+/// What an entry of the command palette does once picked.
+#[derive(Clone)]
+pub enum Command {
+    // note: sent to the background job, like the keys doing the same
+    Background(AppAction),
+    Focus(Views),
+    ToggleSchedules,
+    ToggleZoom,
+    GoToHeight,
+    SetUnit(HeightUnit),
+    ToggleReference,
+    ToggleReminders,
+    PickProfile,
+    AddPosition,
+    SaveCurrent(u16),
+    Wizard,
+    Export,
+}
+
+#[derive(Clone)]
+pub struct PaletteEntry {
+    pub label: String,
+    pub command: Command,
+}
+
+impl PaletteEntry {
+    pub fn new(label: impl Into<String>, command: Command) -> Self {
+        Self {
+            label: label.into(),
+            command,
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct CommandPalette {
+    pub input: TextInput,
+    // note: index in the filtered entries
+    pub selected: usize,
+}
+
+/// Scores how well `query` matches `text`: None unless the characters of the
+/// query appear in the text in the same order, higher when they are
+/// consecutive or start a word. Case and whitespace in the query are ignored.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<u32> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut next = 0;
+    let mut last_match: Option<usize> = None;
+
+    for c in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let idx = next + text[next..].iter().position(|t| *t == c)?;
+        score += 1;
+        if last_match.is_some_and(|last| last + 1 == idx) {
+            score += CONSECUTIVE_BONUS;
+        }
+        if idx == 0 || !text[idx - 1].is_alphanumeric() {
+            score += WORD_START_BONUS;
+        }
+        last_match = Some(idx);
+        next = idx + 1;
+    }
+
+    Some(score)
+}
+
+/// Entries matching `query`, best first; ties keep their original order.
+pub fn filter(entries: Vec<PaletteEntry>, query: &str) -> Vec<PaletteEntry> {
+    let mut scored: Vec<(u32, PaletteEntry)> = entries
+        .into_iter()
+        .filter_map(|entry| Some((fuzzy_score(query, &entry.label)?, entry)))
+        .collect();
+    scored.sort_by_key(|(score, _)| Reverse(*score));
+    scored.into_iter().map(|(_, entry)| entry).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(entries: Vec<PaletteEntry>) -> Vec<String> {
+        entries.into_iter().map(|entry| entry.label).collect()
+    }
+
+    fn entries(labels: &[&str]) -> Vec<PaletteEntry> {
+        labels
+            .iter()
+            .map(|label| PaletteEntry::new(*label, Command::ToggleZoom))
+            .collect()
+    }
+
+    #[test]
+    fn rejects_characters_out_of_order() {
+        assert!(fuzzy_score("zm", "zoom").is_some());
+        assert_eq!(fuzzy_score("oz", "zoom"), None);
+        assert_eq!(fuzzy_score("zooom", "zoom"), None);
+    }
+
+    #[test]
+    fn ignores_case_and_whitespace() {
+        assert_eq!(
+            fuzzy_score("Go To", "go to height"),
+            fuzzy_score("goto", "go to height")
+        );
+        assert!(fuzzy_score("", "anything").is_some());
+    }
+
+    #[test]
+    fn prefers_consecutive_characters() {
+        let consecutive = fuzzy_score("ab", "xabx").unwrap();
+        let scattered = fuzzy_score("ab", "xaxb").unwrap();
+        assert!(consecutive > scattered);
+    }
+
+    #[test]
+    fn prefers_word_starts() {
+        let word_start = fuzzy_score("h", "go to height").unwrap();
+        let inside = fuzzy_score("h", "go to weight").unwrap();
+        assert!(word_start > inside);
+    }
+
+    #[test]
+    fn orders_best_first_and_keeps_ties_in_order() {
+        let filtered = filter(
+            entries(&["show schedules", "toggle zoom", "zoom", "set unit"]),
+            "zoom",
+        );
+        assert_eq!(labels(filtered), vec!["toggle zoom", "zoom"]);
+
+        let filtered = filter(entries(&["set unit", "show schedules", "stats"]), "s");
+        assert_eq!(
+            labels(filtered),
+            vec!["set unit", "show schedules", "stats"]
+        );
+    }
+}
@@ -0,0 +1,269 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveTime, TimeZone, Weekday};
+
+use crate::tui::list::ListableItem;
+
+use super::config::ScheduleConfig;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("invalid schedule days '{}'", _0)]
+    InvalidDays(String),
+
+    #[error("invalid schedule time '{}', expected HH:MM", _0)]
+    InvalidTime(String),
+}
+
+#[derive(Clone, Debug)]
+pub struct Schedule {
+    pub days: Vec<Weekday>,
+    pub time: NaiveTime,
+    pub position: String,
+}
+
+impl Schedule {
+    pub fn parse(config: &ScheduleConfig) -> Result<Self, Error> {
+        Ok(Self {
+            days: Self::parse_days(&config.days)?,
+            time: NaiveTime::parse_from_str(&config.time, "%H:%M")
+                .map_err(|_| Error::InvalidTime(config.time.clone()))?,
+            position: config.position.clone(),
+        })
+    }
+
+    // note: accepts "daily", "weekdays", "weekends" or a comma separated list
+    // of days and ranges, e.g. "mon,wed,fri" or "mon-thu,sat"
+    fn parse_days(days: &str) -> Result<Vec<Weekday>, Error> {
+        use Weekday::*;
+
+        let invalid = || Error::InvalidDays(days.to_string());
+        let parse_day = |day: &str| day.trim().parse::<Weekday>().map_err(|_| invalid());
+
+        match days.trim().to_lowercase().as_str() {
+            "daily" => Ok(vec![Mon, Tue, Wed, Thu, Fri, Sat, Sun]),
+            "weekdays" => Ok(vec![Mon, Tue, Wed, Thu, Fri]),
+            "weekends" => Ok(vec![Sat, Sun]),
+            list => {
+                let mut parsed = Vec::new();
+                for item in list.split(',') {
+                    // note: a range may wrap around the end of the week, e.g. "sat-mon"
+                    let (first, last) = match item.split_once('-') {
+                        Some((first, last)) => (parse_day(first)?, parse_day(last)?),
+                        None => (parse_day(item)?, parse_day(item)?),
+                    };
+                    let mut day = first;
+                    loop {
+                        if !parsed.contains(&day) {
+                            parsed.push(day);
+                        }
+                        if day == last {
+                            break;
+                        }
+                        day = day.succ();
+                    }
+                }
+                Ok(parsed)
+            }
+        }
+    }
+
+    /// First run strictly after the given instant, looking at most one week
+    /// ahead. A run falling in a DST gap happens an hour later, once the
+    /// clocks went forward, and the first of two ambiguous times is used.
+    pub fn next_run_after<Tz: TimeZone>(&self, after: DateTime<Tz>) -> Option<DateTime<Tz>> {
+        let tz = after.timezone();
+        (0..=7)
+            .map(|offset| after.date_naive() + Duration::days(offset))
+            .filter(|date| self.days.contains(&date.weekday()))
+            .filter_map(|date| {
+                let run = date.and_time(self.time);
+                tz.from_local_datetime(&run).earliest().or_else(|| {
+                    tz.from_local_datetime(&(run + Duration::hours(1)))
+                        .earliest()
+                })
+            })
+            .find(|run| *run > after)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ScheduleStatus {
+    pub schedule: Schedule,
+    pub next_run: Option<DateTime<Local>>,
+    pub skip_next: bool,
+    pub last_outcome: Option<String>,
+}
+
+impl ScheduleStatus {
+    pub fn new(schedule: Schedule) -> Self {
+        Self {
+            next_run: schedule.next_run_after(Local::now()),
+            schedule,
+            skip_next: false,
+            last_outcome: None,
+        }
+    }
+}
+
+impl ListableItem for ScheduleStatus {
+    fn render_row(&self) -> String {
+        let next_run = match self.next_run {
+            Some(run) => {
+                let remaining = run - Local::now();
+                format!(
+                    "{} (in {}h {}m)",
+                    run.format("%a %H:%M"),
+                    remaining.num_hours(),
+                    remaining.num_minutes() % 60
+                )
+            }
+            None => "never".to_string(),
+        };
+
+        let mut row = format!("{} → {}", next_run, self.schedule.position);
+        if self.skip_next {
+            row.push_str(" [skipped]");
+        }
+        if let Some(ref outcome) = self.last_outcome {
+            row.push_str(&format!(" - last: {}", outcome));
+        }
+        row
+    }
+
+    fn is_highlighted(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, Utc};
+    use chrono_tz::Europe::Paris;
+
+    use super::*;
+
+    fn schedule(days: &str, time: &str) -> Schedule {
+        Schedule::parse(&ScheduleConfig {
+            days: days.to_string(),
+            time: time.to_string(),
+            position: "standing".to_string(),
+        })
+        .unwrap()
+    }
+
+    fn at<Tz: TimeZone>(tz: &Tz, date: (i32, u32, u32), time: (u32, u32)) -> DateTime<Tz> {
+        let date = NaiveDate::from_ymd_opt(date.0, date.1, date.2).unwrap();
+        tz.from_local_datetime(&date.and_hms_opt(time.0, time.1, 0).unwrap())
+            .earliest()
+            .unwrap()
+    }
+
+    #[test]
+    fn parses_day_lists_and_ranges() {
+        use Weekday::*;
+
+        let days = |days: &str| Schedule::parse_days(days).unwrap();
+        assert_eq!(days("weekdays"), [Mon, Tue, Wed, Thu, Fri]);
+        assert_eq!(days(" Weekends "), [Sat, Sun]);
+        assert_eq!(days("daily").len(), 7);
+        assert_eq!(days("mon, wed,FRI"), [Mon, Wed, Fri]);
+        assert_eq!(days("mon-thu,sat"), [Mon, Tue, Wed, Thu, Sat]);
+        assert_eq!(days("fri-mon"), [Fri, Sat, Sun, Mon]);
+        assert_eq!(days("tuesday-tuesday,tue"), [Tue]);
+    }
+
+    #[test]
+    fn rejects_invalid_schedules() {
+        for days in [
+            "",
+            "mon,",
+            "someday",
+            "mon-",
+            "-fri",
+            "mon-tue-wed",
+            "mon;fri",
+        ] {
+            assert!(
+                matches!(Schedule::parse_days(days), Err(Error::InvalidDays(_))),
+                "{:?}",
+                days
+            );
+        }
+
+        for time in ["", "7", "25:00", "12:60", "noon"] {
+            let config = ScheduleConfig {
+                days: "daily".to_string(),
+                time: time.to_string(),
+                position: "standing".to_string(),
+            };
+            assert!(
+                matches!(Schedule::parse(&config), Err(Error::InvalidTime(_))),
+                "{:?}",
+                time
+            );
+        }
+    }
+
+    #[test]
+    fn rolls_over_midnight_and_the_end_of_the_week() {
+        // note: 2024-01-05 is a Friday
+        let schedule = schedule("weekdays", "08:30");
+        let run = |date, time| schedule.next_run_after(at(&Utc, date, time));
+
+        assert_eq!(
+            run((2024, 1, 4), (8, 0)),
+            Some(at(&Utc, (2024, 1, 4), (8, 30)))
+        );
+        assert_eq!(
+            run((2024, 1, 4), (8, 30)),
+            Some(at(&Utc, (2024, 1, 5), (8, 30)))
+        );
+        assert_eq!(
+            run((2024, 1, 4), (23, 59)),
+            Some(at(&Utc, (2024, 1, 5), (8, 30)))
+        );
+        assert_eq!(
+            run((2024, 1, 5), (9, 0)),
+            Some(at(&Utc, (2024, 1, 8), (8, 30)))
+        );
+        assert_eq!(
+            run((2024, 1, 6), (0, 0)),
+            Some(at(&Utc, (2024, 1, 8), (8, 30)))
+        );
+
+        // note: a single day, already passed, is a week later
+        let schedule = self::schedule("fri", "08:30");
+        assert_eq!(
+            schedule.next_run_after(at(&Utc, (2024, 1, 5), (9, 0))),
+            Some(at(&Utc, (2024, 1, 12), (8, 30)))
+        );
+    }
+
+    #[test]
+    fn runs_across_dst_transitions() {
+        // note: in Paris, 02:00-03:00 is skipped on 2024-03-31 and 02:00-03:00
+        // happens twice on 2024-10-27, both are Sundays
+        let schedule = schedule("sun", "02:30");
+
+        let run = schedule
+            .next_run_after(at(&Paris, (2024, 3, 30), (12, 0)))
+            .unwrap();
+        assert_eq!(run, at(&Paris, (2024, 3, 31), (3, 30)));
+
+        let run = schedule
+            .next_run_after(at(&Paris, (2024, 10, 26), (12, 0)))
+            .unwrap();
+        assert_eq!(run.with_timezone(&Utc), at(&Utc, (2024, 10, 27), (0, 30)));
+
+        // note: the same local time the day before and after the change
+        let schedule = self::schedule("daily", "08:00");
+        let run = schedule
+            .next_run_after(at(&Paris, (2024, 3, 31), (8, 0)))
+            .unwrap();
+        assert_eq!(run, at(&Paris, (2024, 4, 1), (8, 0)));
+        assert_eq!(run - at(&Paris, (2024, 3, 31), (8, 0)), Duration::hours(24));
+        let run = schedule
+            .next_run_after(at(&Paris, (2024, 3, 30), (8, 0)))
+            .unwrap();
+        assert_eq!(run - at(&Paris, (2024, 3, 30), (8, 0)), Duration::hours(23));
+    }
+}
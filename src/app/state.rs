@@ -1,13 +1,32 @@
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
 use btleplug::api::BDAddr;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
 use crate::{
     bluetooth::ble_device::BleDevice,
-    idasen::idasen::PositionSpeed,
-    tui::list::{ListableItem, StatefulList},
+    idasen::idasen::{PositionSpeed, MAX_HEIGHT, MIN_HEIGHT},
+    tui::{
+        list::StatefulList,
+        modal::{Picker, TextInput},
+    },
 };
 
-use super::{app::Views, config::Config};
+use super::{
+    app::Views,
+    config::Config,
+    ergonomics::{Measurements, Recommendation},
+    export::ExportFormat,
+    height::{deserialize_height, HeightFormat, HeightReference, HeightUnit},
+    history::{ChartWindow, StatisticsPeriod, StatisticsSummary},
+    palette::CommandPalette,
+    schedule::ScheduleStatus,
+    zone::HeightZone,
+};
 
 #[derive(Clone)]
 pub enum Status {
@@ -16,45 +35,560 @@ pub enum Status {
     Running,
 }
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
 pub struct SavedPosition {
     pub name: String,
+    #[serde(deserialize_with = "deserialize_height")]
     pub height: u16,
+    // note: defaults to the place of the position among the first nine
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_hotkey"
+    )]
+    pub hotkey: Option<u8>,
 }
 
-impl ListableItem for SavedPosition {
-    fn render_row(&self) -> String {
+pub const MAX_POSITION_HOTKEY: u8 = 9;
+const MAX_NUDGE_COUNT: u16 = 999;
+
+fn deserialize_hotkey<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u8>, D::Error> {
+    match Option::<u8>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(hotkey @ 1..=MAX_POSITION_HOTKEY) => Ok(Some(hotkey)),
+        Some(hotkey) => Err(serde::de::Error::custom(format!(
+            "invalid hotkey {}, expected 1 to {}",
+            hotkey, MAX_POSITION_HOTKEY
+        ))),
+    }
+}
+
+/// Hotkey of each position: the one set in the config, otherwise its place in
+/// the list, unless another position claimed it. When two positions claim the
+/// same hotkey, the first one gets it.
+pub fn position_hotkeys(positions: &[SavedPosition]) -> Vec<Option<u8>> {
+    let mut claimed = [false; MAX_POSITION_HOTKEY as usize + 1];
+    let mut hotkeys: Vec<Option<u8>> = positions
+        .iter()
+        .map(|p| {
+            let hotkey = p.hotkey.filter(|k| !claimed[*k as usize])?;
+            claimed[hotkey as usize] = true;
+            Some(hotkey)
+        })
+        .collect();
+
+    for (idx, position) in positions
+        .iter()
+        .enumerate()
+        .take(MAX_POSITION_HOTKEY as usize)
+    {
+        let hotkey = idx as u8 + 1;
+        if position.hotkey.is_none() && !claimed[hotkey as usize] {
+            hotkeys[idx] = Some(hotkey);
+            claimed[hotkey as usize] = true;
+        }
+    }
+
+    hotkeys
+}
+
+impl SavedPosition {
+    pub fn new(name: String, height: u16) -> Self {
+        Self {
+            name,
+            height,
+            hotkey: None,
+        }
+    }
+
+    // note: the hotkey depends on the other positions, see position_hotkeys
+    pub fn render_row(&self, format: HeightFormat, hotkey: Option<u8>) -> String {
         format!(
-            "{} ({:.2}cm)",
-            self.name.to_string(),
-            self.height as f32 * 0.01
+            "{} {} ({})",
+            hotkey.map_or(" ".to_string(), |k| k.to_string()),
+            self.name,
+            format.format(self.height)
         )
     }
+}
+
+#[derive(Clone)]
+pub enum PositionEditKind {
+    AddName,
+    AddHeight(String),
+    SaveCurrent(u16),
+    Rename(usize),
+    Delete(usize),
+}
+
+#[derive(Clone)]
+pub struct PositionEdit {
+    pub kind: PositionEditKind,
+    pub input: TextInput,
+}
+
+impl PositionEdit {
+    pub fn prompt(&self, format: HeightFormat) -> String {
+        match self.kind {
+            PositionEditKind::AddName => "Name of the new position".to_string(),
+            PositionEditKind::AddHeight(ref name) => {
+                format!(
+                    "Height of '{}' ({}, {} reference)",
+                    name,
+                    format.unit.suffix(),
+                    format.reference.label()
+                )
+            }
+            PositionEditKind::SaveCurrent(height) => format!("Save {} as", format.format(height)),
+            PositionEditKind::Rename(_) => "New name".to_string(),
+            PositionEditKind::Delete(_) => "Delete the selected position? (y/n)".to_string(),
+        }
+    }
+
+    pub fn accepts_text(&self) -> bool {
+        !matches!(self.kind, PositionEditKind::Delete(_))
+    }
+}
+
+#[derive(Clone)]
+pub enum WizardStep {
+    BodyHeight,
+    ElbowHeight,
+    ChairHeight,
+    Result(Recommendation),
+}
+
+#[derive(Clone)]
+pub struct ErgonomicsWizard {
+    pub step: WizardStep,
+    pub input: TextInput,
+    pub measurements: Measurements,
+}
+
+impl ErgonomicsWizard {
+    pub fn new() -> Self {
+        Self {
+            step: WizardStep::BodyHeight,
+            input: TextInput::default(),
+            measurements: Measurements::default(),
+        }
+    }
+
+    pub fn prompt(&self, unit: HeightUnit) -> String {
+        match self.step {
+            WizardStep::BodyHeight => format!("Your body height ({})", unit.suffix()),
+            WizardStep::ElbowHeight => format!(
+                "Standing elbow height from the floor ({}, empty to estimate)",
+                unit.suffix()
+            ),
+            WizardStep::ChairHeight => {
+                format!("Chair seat height ({}, empty to estimate)", unit.suffix())
+            }
+            WizardStep::Result(_) => "Save as saved positions? (y/n)".to_string(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ReminderStatus {
+    pub enabled: bool,
+    pub zone: Option<HeightZone>,
+    pub in_zone_for: Duration,
+    pub switch_in: Duration,
+}
+
+impl ReminderStatus {
+    pub fn new() -> Self {
+        Self {
+            enabled: true,
+            zone: None,
+            in_zone_for: Duration::ZERO,
+            switch_in: Duration::ZERO,
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct HelpOverlay {
+    pub search: TextInput,
+    // note: first visible line
+    pub scroll: usize,
+}
+
+#[derive(Clone, Default)]
+pub struct NotificationCentre {
+    // note: first visible line
+    pub scroll: usize,
+}
+
+#[derive(Clone, Copy)]
+pub struct MoveProgress {
+    pub from: u16,
+    pub target: u16,
+    pub started_at: Instant,
+}
+
+impl MoveProgress {
+    pub fn new(from: u16, target: u16) -> Self {
+        Self {
+            from,
+            target,
+            started_at: Instant::now(),
+        }
+    }
+
+    pub fn remaining(&self, current: u16) -> u16 {
+        current.abs_diff(self.target)
+    }
+
+    // note: 0.0 at the start height, 1.0 at the target
+    pub fn ratio(&self, current: u16) -> f64 {
+        let total = self.from.abs_diff(self.target);
+        if total == 0 {
+            return 1.0;
+        }
+
+        // note: along the direction of the move, so that overshooting the target stays at 1.0
+        let moved = if self.target >= self.from {
+            current as f64 - self.from as f64
+        } else {
+            self.from as f64 - current as f64
+        };
+        (moved / total as f64).clamp(0.0, 1.0)
+    }
 
-    fn is_highlighted(&self) -> bool {
-        false
+    // note: based on the average speed so far, None until the desk moves
+    pub fn eta(&self, current: u16) -> Option<Duration> {
+        self.eta_at(current, Instant::now())
+    }
+
+    fn eta_at(&self, current: u16, now: Instant) -> Option<Duration> {
+        let moved = self
+            .from
+            .abs_diff(current)
+            .min(self.from.abs_diff(self.target));
+        if moved == 0 {
+            return None;
+        }
+
+        let speed = moved as f64 / now.duration_since(self.started_at).as_secs_f64();
+        Some(Duration::from_secs_f64(
+            self.remaining(current) as f64 / speed,
+        ))
+    }
+}
+
+// note: id is the number of the move to a height, see State::moves_started
+#[derive(Clone)]
+pub enum MoveOutcome {
+    // note: error is the distance from the target, in tenth of mm
+    Reached {
+        id: u64,
+        target: u16,
+        error: u16,
+    },
+    Aborted {
+        id: u64,
+        target: u16,
+        reason: String,
+    },
+}
+
+/// Overlay or dialog drawn above the sections.
+#[derive(Clone)]
+pub enum Modal {
+    Schedules,
+    NotificationCentre(NotificationCentre),
+    ErgonomicsWizard(ErgonomicsWizard),
+    PositionEdit(PositionEdit),
+    HeightInput(TextInput),
+    ExportRange(TextInput),
+    ProfilePicker(Picker),
+    Help(HelpOverlay),
+    CommandPalette(CommandPalette),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModalKind {
+    Schedules,
+    NotificationCentre,
+    ErgonomicsWizard,
+    PositionEdit,
+    HeightInput,
+    ExportRange,
+    ProfilePicker,
+    Help,
+    CommandPalette,
+}
+
+impl Modal {
+    pub fn kind(&self) -> ModalKind {
+        match self {
+            Modal::Schedules => ModalKind::Schedules,
+            Modal::NotificationCentre(_) => ModalKind::NotificationCentre,
+            Modal::ErgonomicsWizard(_) => ModalKind::ErgonomicsWizard,
+            Modal::PositionEdit(_) => ModalKind::PositionEdit,
+            Modal::HeightInput(_) => ModalKind::HeightInput,
+            Modal::ExportRange(_) => ModalKind::ExportRange,
+            Modal::ProfilePicker(_) => ModalKind::ProfilePicker,
+            Modal::Help(_) => ModalKind::Help,
+            Modal::CommandPalette(_) => ModalKind::CommandPalette,
+        }
     }
 }
 
 #[derive(Clone)]
 pub struct State {
+    // note: None is the default profile
+    pub profile: Option<String>,
     pub positions_list: StatefulList<SavedPosition>,
     pub devices_list: StatefulList<BleDevice>,
     pub current_view: Views,
+    pub unit: HeightUnit,
+    pub height_reference: HeightReference,
+    pub height_offsets: HashMap<BDAddr, i16>,
     pub status: Status,
     pub connected_device: Option<BDAddr>,
+    // note: the desk given in the configuration, its offset applies until a desk is connected
+    pub predefined_device: Option<BDAddr>,
     pub position: Option<PositionSpeed>,
+    // note: the move to a given height in progress, if any
+    pub move_progress: Option<MoveProgress>,
+    pub last_move: Option<MoveOutcome>,
+    // note: the number of moves to a height started so far
+    pub moves_started: u64,
+    // note: vim-like count applied to the next nudge, typed with the digits
+    pub nudge_count: Option<u16>,
+    pub reminder: Option<ReminderStatus>,
+    pub pending_bell: bool,
+    pub schedules: StatefulList<ScheduleStatus>,
+    // note: only the focused section is shown
+    pub is_zoomed: bool,
+    // note: the last one is on top, drawn last and given the input
+    pub modals: Vec<Modal>,
+    pub statistics: Option<StatisticsSummary>,
+    pub statistics_period: StatisticsPeriod,
+    pub chart_window: ChartWindow,
+    pub export_format: ExportFormat,
 }
 
 impl State {
-    pub fn new(config: Config) -> Self {
+    pub fn new(config: Config, profile: Option<String>) -> Self {
+        let positions = config.positions_for(profile.as_deref());
+
+        // note: new users are guided to their first positions
+        let modals = if positions.is_empty() && !config.wizard_dismissed {
+            vec![Modal::ErgonomicsWizard(ErgonomicsWizard::new())]
+        } else {
+            Vec::new()
+        };
+
         Self {
-            positions_list: StatefulList::with_items(config.saved_positions),
+            positions_list: StatefulList::with_items(positions),
             devices_list: StatefulList::new(),
             current_view: Views::Connection,
+            unit: config.unit,
+            height_reference: config.height_reference,
+            height_offsets: config
+                .height_offsets
+                .iter()
+                .filter_map(|(addr, offset)| Some((BDAddr::from_str(addr).ok()?, *offset)))
+                .collect(),
             status: Status::Running,
             connected_device: None,
+            predefined_device: config
+                .predefined_mac
+                .as_deref()
+                .and_then(|addr| BDAddr::from_str(addr).ok()),
             position: None,
+            move_progress: None,
+            last_move: None,
+            moves_started: 0,
+            nudge_count: None,
+            reminder: config
+                .reminders_for(profile.as_deref())
+                .map(|_| ReminderStatus::new()),
+            profile,
+            pending_bell: false,
+            schedules: StatefulList::new(),
+            is_zoomed: false,
+            modals,
+            statistics: None,
+            statistics_period: StatisticsPeriod::Today,
+            chart_window: ChartWindow::Hour,
+            export_format: ExportFormat::Csv,
+        }
+    }
+
+    // note: a modal opened again is moved on top
+    pub fn open_modal(&mut self, modal: Modal) {
+        self.close_modal(modal.kind());
+        self.modals.push(modal)
+    }
+
+    pub fn close_modal(&mut self, kind: ModalKind) {
+        self.modals.retain(|m| m.kind() != kind)
+    }
+
+    pub fn toggle_modal(&mut self, modal: Modal) {
+        if self.has_modal(modal.kind()) {
+            self.close_modal(modal.kind())
+        } else {
+            self.modals.push(modal)
+        }
+    }
+
+    pub fn has_modal(&self, kind: ModalKind) -> bool {
+        self.modals.iter().any(|m| m.kind() == kind)
+    }
+
+    pub fn top_modal(&self) -> Option<&Modal> {
+        self.modals.last()
+    }
+
+    pub fn modal(&self, kind: ModalKind) -> Option<&Modal> {
+        self.modals.iter().find(|m| m.kind() == kind)
+    }
+
+    pub fn modal_mut(&mut self, kind: ModalKind) -> Option<&mut Modal> {
+        self.modals.iter_mut().find(|m| m.kind() == kind)
+    }
+
+    pub fn height_format(&self) -> HeightFormat {
+        HeightFormat {
+            unit: self.unit,
+            reference: self.height_reference,
+            offset: self
+                .connected_device
+                .or(self.predefined_device)
+                .and_then(|addr| self.height_offsets.get(&addr).copied())
+                .unwrap_or(0),
         }
     }
+
+    // note: a leading 0 does not start a count, as in vim
+    pub fn push_nudge_count_digit(&mut self, digit: u16) {
+        match self.nudge_count {
+            None if digit == 0 => (),
+            count => {
+                self.nudge_count = Some((count.unwrap_or(0) * 10 + digit).min(MAX_NUDGE_COUNT))
+            }
+        }
+    }
+
+    /// Target of a nudge by `step` (tenth of mm) from the current position,
+    /// repeated as many times as the pending count, which is used up.
+    pub fn take_nudge_target(&mut self, step: i32) -> Option<u16> {
+        let count = self.nudge_count.take().unwrap_or(1);
+        let position = self.position.as_ref()?.position;
+        let target = (position as i32 + step * count as i32)
+            .clamp(MIN_HEIGHT as i32, MAX_HEIGHT as i32) as u16;
+
+        (target != position).then_some(target)
+    }
+
+    // note: without offsets, every desk has the same
+    pub fn is_offset_known(&self) -> bool {
+        self.height_offsets.is_empty() || self.connected_device.or(self.predefined_device).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(json: &str) -> Result<SavedPosition, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    #[test]
+    fn reads_a_missing_or_null_hotkey_as_none() {
+        let missing = position(r#"{ "name": "a", "height": 7000 }"#).unwrap();
+        let null = position(r#"{ "name": "a", "height": 7000, "hotkey": null }"#).unwrap();
+        assert_eq!(missing.hotkey, None);
+        assert_eq!(null.hotkey, None);
+        assert!(position(r#"{ "name": "a", "height": 7000, "hotkey": 0 }"#).is_err());
+        assert!(position(r#"{ "name": "a", "height": 7000, "hotkey": 10 }"#).is_err());
+    }
+
+    #[test]
+    fn gives_the_claimed_hotkeys_first() {
+        let mut positions: Vec<SavedPosition> = ["a", "b", "c"]
+            .iter()
+            .map(|name| SavedPosition::new(name.to_string(), 7000))
+            .collect();
+        positions[2].hotkey = Some(1);
+
+        assert_eq!(position_hotkeys(&positions), vec![None, Some(2), Some(1)]);
+    }
+
+    #[test]
+    fn renders_the_rows_in_the_height_format() {
+        let position = SavedPosition::new("standing".to_string(), 11000);
+        let format = |unit, reference| HeightFormat {
+            unit,
+            reference,
+            offset: 250,
+        };
+
+        assert_eq!(
+            position.render_row(
+                format(HeightUnit::Centimeters, HeightReference::Desk),
+                Some(2)
+            ),
+            "2 standing (110.00cm)"
+        );
+        assert_eq!(
+            position.render_row(format(HeightUnit::Inches, HeightReference::Surface), None),
+            "  standing (44.29in)"
+        );
+    }
+
+    #[test]
+    fn computes_the_progress_of_a_move() {
+        let up = MoveProgress::new(7000, 11000);
+        assert_eq!(up.remaining(7000), 4000);
+        assert_eq!(up.remaining(10000), 1000);
+        assert_eq!(up.remaining(11500), 500);
+        assert_eq!(up.ratio(7000), 0.0);
+        assert_eq!(up.ratio(8000), 0.25);
+        assert_eq!(up.ratio(11000), 1.0);
+        // note: overshooting or moving away is clamped
+        assert_eq!(up.ratio(11500), 1.0);
+        assert_eq!(up.ratio(6500), 0.0);
+
+        let down = MoveProgress::new(11000, 7000);
+        assert_eq!(down.remaining(8000), 1000);
+        assert_eq!(down.ratio(8000), 0.75);
+        assert_eq!(down.ratio(6500), 1.0);
+        assert_eq!(down.ratio(11500), 0.0);
+
+        let still = MoveProgress::new(7000, 7000);
+        assert_eq!(still.remaining(7000), 0);
+        assert_eq!(still.ratio(7000), 1.0);
+    }
+
+    #[test]
+    fn estimates_the_end_of_a_move_from_the_average_speed() {
+        let progress = MoveProgress::new(7000, 11000);
+        let start = progress.started_at;
+
+        assert_eq!(progress.eta_at(7000, start + Duration::from_secs(1)), None);
+        // note: 1000 in 2s, 3000 left
+        assert_eq!(
+            progress.eta_at(8000, start + Duration::from_secs(2)),
+            Some(Duration::from_secs(6))
+        );
+        assert_eq!(
+            progress.eta_at(11000, start + Duration::from_secs(8)),
+            Some(Duration::ZERO)
+        );
+
+        let down = MoveProgress::new(11000, 7000);
+        let start = down.started_at;
+        assert_eq!(
+            down.eta_at(9000, start + Duration::from_secs(4)),
+            Some(Duration::from_secs(4))
+        );
+    }
 }
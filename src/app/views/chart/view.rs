@@ -0,0 +1,257 @@
+use chrono::{DateTime, Local};
+use ratatui::{
+    layout::Rect,
+    style::Stylize,
+    symbols::Marker,
+    text::Span,
+    widgets::{Axis, Block, BorderType, Borders, Chart, Dataset, GraphType},
+    Frame,
+};
+
+use crate::{
+    app::{
+        app::{App, MouseTarget, Views},
+        history::{ChartWindow, HeightSample},
+        keymap::{Action, Keymap, KeymapScope},
+        state::State,
+        ui_event_emitter::UIEvent,
+    },
+    idasen::idasen::{MAX_HEIGHT, MIN_HEIGHT},
+    tui::{help_bar, theme::Theme},
+};
+
+struct ChartView;
+
+impl ChartView {
+    fn draw_help_bar(frame: &mut Frame, area: Rect, keymap: &Keymap) {
+        let mut buttons = keymap.help(KeymapScope::Global, &[Action::Quit, Action::Help]);
+        buttons.extend(keymap.help(KeymapScope::Chart, &[Action::ZoomIn, Action::ZoomOut]));
+
+        help_bar::draw(frame, area, buttons)
+    }
+
+    fn x_of(at: DateTime<Local>, from: DateTime<Local>, now: DateTime<Local>) -> f64 {
+        (at.max(from) - now).num_seconds() as f64
+    }
+
+    /// Turns the samples into step lines, split wherever the height is unknown.
+    fn segments(
+        samples: &[HeightSample],
+        current: Option<u16>,
+        window: ChartWindow,
+        now: DateTime<Local>,
+    ) -> Vec<Vec<(f64, f64)>> {
+        let from = now - window.duration();
+        let live = HeightSample {
+            at: now,
+            height: current,
+        };
+
+        let mut segments = Vec::new();
+        let mut segment: Vec<(f64, f64)> = Vec::new();
+        let mut last_height: Option<u16> = None;
+
+        for sample in samples.iter().chain([&live]) {
+            let x = Self::x_of(sample.at, from, now);
+            if let Some(height) = last_height {
+                segment.push((x, height as f64));
+            }
+
+            match sample.height {
+                Some(height) => segment.push((x, height as f64)),
+                None if !segment.is_empty() => segments.push(std::mem::take(&mut segment)),
+                None => (),
+            }
+            last_height = sample.height;
+        }
+
+        if !segment.is_empty() {
+            segments.push(segment);
+        }
+        segments
+    }
+
+    fn draw_chart_box(
+        frame: &mut Frame,
+        area: Rect,
+        state: &State,
+        samples: &[HeightSample],
+        theme: &Theme,
+        is_focused: bool,
+    ) {
+        let now = Local::now();
+        let window = state.chart_window;
+        let window_secs = window.duration().num_seconds() as f64;
+        let format = state.height_format();
+
+        let segments = Self::segments(
+            samples,
+            state.position.as_ref().map(|p| p.position),
+            window,
+            now,
+        );
+        let markers: Vec<[(f64, f64); 2]> = state
+            .positions_list
+            .items()
+            .iter()
+            .map(|p| [(-window_secs, p.height as f64), (0.0, p.height as f64)])
+            .collect();
+
+        let mut datasets: Vec<Dataset> = markers
+            .iter()
+            .map(|marker| {
+                Dataset::default()
+                    .marker(Marker::Braille)
+                    .graph_type(GraphType::Line)
+                    .style(theme.info().dim())
+                    .data(marker)
+            })
+            .collect();
+        datasets.extend(segments.iter().map(|segment| {
+            Dataset::default()
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(theme.accent())
+                .data(segment)
+        }));
+
+        let mut container = Block::default()
+            .title(format!("Height ({})", window.label()))
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded);
+        if is_focused {
+            container = container.border_style(theme.focus_border());
+        }
+
+        let chart = Chart::new(datasets)
+            .block(container)
+            .legend_position(None)
+            .x_axis(
+                Axis::default()
+                    .bounds([-window_secs, 0.0])
+                    .labels(vec![
+                        Span::from(format!("-{}", window.label())),
+                        Span::from("now"),
+                    ])
+                    .style(theme.accent().dim()),
+            )
+            .y_axis(
+                Axis::default()
+                    .bounds([MIN_HEIGHT as f64, MAX_HEIGHT as f64])
+                    .labels(vec![
+                        Span::from(format.format(MIN_HEIGHT)),
+                        Span::from(format.format(MAX_HEIGHT)),
+                    ])
+                    .style(theme.accent().dim()),
+            );
+
+        frame.render_widget(chart, area);
+    }
+}
+
+impl App {
+    pub fn handle_chart_event(&mut self, ev: UIEvent) {
+        if let UIEvent::KeyPress(ev) = ev {
+            match self.keymap().action(KeymapScope::Chart, &ev) {
+                Some(Action::ZoomIn) => self.zoom_chart_in(),
+                Some(Action::ZoomOut) => self.zoom_chart_out(),
+                _ => (),
+            }
+        }
+    }
+
+    pub fn draw_chart(&mut self, frame: &mut Frame, view_area: Rect, bar_area: Rect) {
+        let state = self.get_current_state();
+        let height_samples = self.height_samples();
+        self.register_mouse_target(MouseTarget::View(Views::HeightChart), view_area);
+        ChartView::draw_chart_box(
+            frame,
+            view_area,
+            &state,
+            height_samples.lock().unwrap().samples(),
+            self.theme(),
+            state.current_view == Views::HeightChart,
+        );
+
+        if state.current_view == Views::HeightChart {
+            ChartView::draw_help_bar(frame, bar_area, self.keymap())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone};
+
+    use super::*;
+
+    fn now() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap()
+    }
+
+    fn sample(minutes_ago: i64, height: Option<u16>) -> HeightSample {
+        HeightSample {
+            at: now() - Duration::minutes(minutes_ago),
+            height,
+        }
+    }
+
+    #[test]
+    fn splits_the_steps_where_the_height_is_unknown() {
+        let samples = [
+            sample(20, Some(7000)),
+            sample(10, Some(11000)),
+            sample(5, None),
+            sample(2, Some(9000)),
+        ];
+
+        // note: the sample older than the window starts at its left edge
+        assert_eq!(
+            ChartView::segments(&samples, Some(9500), ChartWindow::QuarterHour, now()),
+            [
+                vec![
+                    (-900.0, 7000.0),
+                    (-600.0, 7000.0),
+                    (-600.0, 11000.0),
+                    (-300.0, 11000.0),
+                ],
+                vec![(-120.0, 9000.0), (0.0, 9000.0), (0.0, 9500.0)],
+            ]
+        );
+
+        // note: the last height is carried up to now, even once disconnected
+        assert_eq!(
+            ChartView::segments(&samples, None, ChartWindow::QuarterHour, now()),
+            [
+                vec![
+                    (-900.0, 7000.0),
+                    (-600.0, 7000.0),
+                    (-600.0, 11000.0),
+                    (-300.0, 11000.0),
+                ],
+                vec![(-120.0, 9000.0), (0.0, 9000.0)],
+            ]
+        );
+    }
+
+    #[test]
+    fn clamps_the_samples_to_the_start_of_the_window() {
+        let samples = [sample(90, Some(7000)), sample(70, Some(11000))];
+
+        assert_eq!(
+            ChartView::segments(&samples, Some(11000), ChartWindow::Hour, now()),
+            [vec![
+                (-3600.0, 7000.0),
+                (-3600.0, 7000.0),
+                (-3600.0, 11000.0),
+                (0.0, 11000.0),
+                (0.0, 11000.0),
+            ]]
+        );
+        assert_eq!(
+            ChartView::segments(&samples, Some(11000), ChartWindow::FourHours, now())[0][0],
+            (-5400.0, 7000.0)
+        );
+        assert!(ChartView::segments(&[], None, ChartWindow::Hour, now()).is_empty());
+    }
+}
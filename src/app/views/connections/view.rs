@@ -1,76 +1,196 @@
-use btleplug::api::BDAddr;
+use std::time::Duration;
+
 use ratatui::{
     layout::Rect,
-    style::{Style, Stylize},
+    style::Stylize,
     text::{Line, Span},
     widgets::{Block, BorderType, Borders, Padding, Paragraph},
     Frame,
 };
 
 use crate::{
-    app::app::{App, Views},
-    tui::help_bar,
+    app::{
+        actions::SelectionMove,
+        app::{App, MouseTarget, Views},
+        keymap::{Action, Keymap, KeymapScope},
+        layout,
+        state::{ReminderStatus, State},
+        ui_event_emitter::UIEvent,
+    },
+    tui::{
+        help_bar,
+        modal::{ModalOutcome, Picker, PickerDialog},
+        theme::Theme,
+    },
 };
 
+const PROFILE_PICKER_TITLE: &str = "Profiles";
+// note: the longest line, asking to select a device
+const CONNECTION_CONTENT_WIDTH: u16 = 51;
+// note: the welcome, status, device, profile and reminder lines with blank lines between them
+pub const CONNECTION_CONTENT_HEIGHT: u16 = 9;
+// note: the welcome and status lines between the borders
+pub const CONNECTION_MIN_HEIGHT: u16 = 3 + 2;
+
 struct ConnectionsView;
 
 impl ConnectionsView {
-    fn draw_help_bar(frame: &mut Frame, area: Rect) {
-        help_bar::draw(frame, area, vec!["q: quit".to_string()])
+    fn draw_help_bar(
+        frame: &mut Frame,
+        area: Rect,
+        keymap: &Keymap,
+        has_reminders: bool,
+        has_profiles: bool,
+    ) {
+        let mut buttons = keymap.help(KeymapScope::Global, &[Action::Quit, Action::Help]);
+
+        if has_reminders {
+            buttons.extend(keymap.help(KeymapScope::Connection, &[Action::ToggleReminders]));
+        }
+
+        if has_profiles {
+            buttons.extend(keymap.help(
+                KeymapScope::Connection,
+                &[Action::NextProfile, Action::PrevProfile],
+            ));
+        }
+
+        help_bar::draw(frame, area, buttons)
+    }
+
+    fn format_minutes(duration: Duration) -> String {
+        format!("{}m", duration.as_secs() / 60)
+    }
+
+    fn reminder_line<'a>(reminder: &ReminderStatus, theme: &Theme) -> Line<'a> {
+        match (reminder.enabled, reminder.zone) {
+            (false, _) => Line::from(vec![
+                "Reminders: ".into(),
+                Span::styled("paused", theme.warning()),
+            ]),
+            (true, None) => Line::from(vec!["Reminders: ".into(), "waiting for the desk".into()]),
+            (true, Some(zone)) => Line::from(vec![
+                "Reminders: ".into(),
+                format!(
+                    "{} for {}, switch in {}",
+                    zone.label(),
+                    Self::format_minutes(reminder.in_zone_for),
+                    Self::format_minutes(reminder.switch_in)
+                )
+                .into(),
+            ]),
+        }
     }
 
     fn draw_connection_box(
         frame: &mut Frame,
         area: Rect,
-        is_focused: bool,
-        connected_device: Option<BDAddr>,
+        state: &State,
+        profile: Option<String>,
+        is_compact: bool,
+        theme: &Theme,
     ) {
         let mut container = Block::default()
             .border_type(BorderType::Rounded)
             .borders(Borders::ALL)
-            .padding(Padding::new(4, 4, 2, 2))
+            .padding(if is_compact {
+                Padding::zero()
+            } else {
+                layout::section_padding(area, CONNECTION_CONTENT_WIDTH, CONNECTION_CONTENT_HEIGHT)
+            })
             .title("Connection");
 
-        if is_focused {
-            container = container.border_style(Style::new().bold().light_red());
+        if state.current_view == Views::Connection {
+            container = container.border_style(theme.focus_border());
         }
 
         let mut content = vec![
             Line::from("Welcome to idasen-tui!"),
             Line::from(""),
-            if let Some(addr) = connected_device {
+            if let Some(addr) = state.connected_device {
                 Line::from(vec![
                     "Connected Device: ".into(),
-                    addr.to_string().green(),
+                    Span::styled(addr.to_string(), theme.success()),
                 ])
             } else {
-                Line::from(Span::from("Disconnected").red())
+                Line::from(Span::styled("Disconnected", theme.error()))
             },
         ];
 
-        if let None = connected_device {
+        if state.connected_device.is_none() {
             content.append(&mut vec![
                 Line::from(""),
                 Line::from("Select your Idåsen device using the Devices section"),
             ])
         }
 
+        if let Some(name) = profile {
+            content.append(&mut vec![
+                Line::from(""),
+                Line::from(vec!["Profile: ".into(), name.bold()]),
+            ])
+        }
+
+        if let Some(ref reminder) = state.reminder {
+            content.append(&mut vec![
+                Line::from(""),
+                Self::reminder_line(reminder, theme),
+            ])
+        }
+
         frame.render_widget(Paragraph::new(content).block(container), area);
     }
 }
 
 impl App {
+    pub fn handle_connections_event(&mut self, ev: UIEvent) {
+        if let UIEvent::KeyPress(ev) = ev {
+            match self.keymap().action(KeymapScope::Connection, &ev) {
+                Some(Action::ToggleReminders) => self.toggle_reminders(),
+                Some(Action::NextProfile) => self.switch_profile(SelectionMove::Next),
+                Some(Action::PrevProfile) => self.switch_profile(SelectionMove::Prev),
+                _ => (),
+            }
+        }
+    }
+
+    pub fn handle_profile_picker_event(&mut self, ev: UIEvent) {
+        match self.update_profile_picker(&ev) {
+            ModalOutcome::Confirmed => self.confirm_profile_picker(),
+            ModalOutcome::Cancelled => self.close_profile_picker(),
+            ModalOutcome::Pending => (),
+        }
+    }
+
     pub fn draw_connections(&mut self, frame: &mut Frame, view_area: Rect, bar_area: Rect) {
         let state = self.get_current_state();
+        self.register_mouse_target(MouseTarget::View(Views::Connection), view_area);
         ConnectionsView::draw_connection_box(
             frame,
             view_area,
-            state.current_view == Views::Connection,
-            state.connected_device,
+            &state,
+            self.profile_label(),
+            self.is_compact(),
+            self.theme(),
         );
 
         if state.current_view == Views::Connection {
-            ConnectionsView::draw_help_bar(frame, bar_area)
+            ConnectionsView::draw_help_bar(
+                frame,
+                bar_area,
+                self.keymap(),
+                state.reminder.is_some(),
+                self.profile_label().is_some(),
+            )
+        }
+    }
+
+    pub fn draw_profile_picker(&mut self, frame: &mut Frame, bar_area: Rect, picker: &Picker) {
+        PickerDialog {
+            title: PROFILE_PICKER_TITLE,
+            picker,
         }
+        .draw(frame, self.theme());
+        help_bar::draw(frame, bar_area, PickerDialog::help())
     }
 }
@@ -1,7 +1,5 @@
-use crossterm::event::KeyCode;
 use ratatui::{
     layout::Rect,
-    style::{Style, Stylize},
     widgets::{Block, BorderType, Borders},
     Frame,
 };
@@ -9,7 +7,8 @@ use ratatui::{
 use crate::{
     app::{
         actions::{AppAction, SelectionMove},
-        app::{App, Views},
+        app::{App, MouseTarget, Views},
+        keymap::{Action, Keymap, KeymapScope},
         state::Status,
         ui_event_emitter::UIEvent,
     },
@@ -19,16 +18,15 @@ use crate::{
 struct DevicesListView;
 
 impl DevicesListView {
-    fn draw_help_bar(frame: &mut Frame, area: Rect, is_connected: bool) {
-        let mut buttons = vec![
-            "q: quit".to_string(),
-            "r: refresh".to_string(),
-            "h/j/k/l: move selection".to_string(),
-            "arrows: move selection".to_string(),
-        ];
+    fn draw_help_bar(frame: &mut Frame, area: Rect, keymap: &Keymap, is_connected: bool) {
+        let mut buttons = keymap.help(KeymapScope::Global, &[Action::Quit, Action::Help]);
+        buttons.extend(keymap.help(
+            KeymapScope::Devices,
+            &[Action::Refresh, Action::SelectPrev, Action::SelectNext],
+        ));
 
         if !is_connected {
-            buttons.push("<enter>: connect".to_string());
+            buttons.extend(keymap.help(KeymapScope::Devices, &[Action::Connect]));
         }
 
         help_bar::draw(frame, area, buttons)
@@ -38,26 +36,24 @@ impl DevicesListView {
 impl App {
     pub fn handle_device_list_event(&mut self, ev: UIEvent) {
         let state = self.get_current_state();
-        match ev {
-            UIEvent::KeyPress(ev) => match ev.code {
-                KeyCode::Char('r') => {
+        if let UIEvent::KeyPress(ev) = ev {
+            match self.keymap().action(KeymapScope::Devices, &ev) {
+                Some(Action::Refresh) => {
                     if let Status::Running = state.status {
                         self.start_action(AppAction::UpdateDevicesList)
                     }
                 }
-                KeyCode::Enter => self.connect_selected(),
-                KeyCode::Up | KeyCode::Char('k') => self.move_device_selection(SelectionMove::Prev),
-                KeyCode::Down | KeyCode::Char('j') => {
-                    self.move_device_selection(SelectionMove::Next)
-                }
+                Some(Action::Connect) => self.connect_selected(),
+                Some(Action::SelectPrev) => self.move_device_selection(SelectionMove::Prev),
+                Some(Action::SelectNext) => self.move_device_selection(SelectionMove::Next),
                 _ => (),
-            },
-            _ => (),
+            }
         }
     }
 
     pub fn draw_device_list(&mut self, frame: &mut Frame, view_area: Rect, bar_area: Rect) {
         let mut state = self.get_current_state();
+        self.register_mouse_target(MouseTarget::View(Views::DeviceList), view_area);
 
         let mut container = Block::default()
             .title("Devices")
@@ -65,23 +61,28 @@ impl App {
             .border_type(BorderType::Rounded);
 
         if state.current_view == Views::DeviceList {
-            container = container.border_style(Style::new().bold().light_red());
+            container = container.border_style(self.theme().focus_border());
         }
 
         frame.render_widget(container.clone(), view_area);
 
         let is_highlighted = state.devices_list.is_current_selected_highlighted();
-        state
-            .devices_list
-            .draw(frame, container.inner(view_area), |f| {
-                match state.connected_device {
-                    None => false,
-                    Some(d) => f.properties.address == d,
-                }
-            });
+        state.devices_list.draw(
+            frame,
+            container.inner(view_area),
+            self.theme(),
+            |f| match state.connected_device {
+                None => false,
+                Some(d) => f.properties.address == d,
+            },
+        );
+        self.register_mouse_target(
+            MouseTarget::ListRows(Views::DeviceList, state.devices_list.offset()),
+            container.inner(view_area),
+        );
 
         if state.current_view == Views::DeviceList {
-            DevicesListView::draw_help_bar(frame, bar_area, is_highlighted);
+            DevicesListView::draw_help_bar(frame, bar_area, self.keymap(), is_highlighted);
         }
     }
 }
@@ -0,0 +1,165 @@
+use ratatui::{
+    layout::Rect,
+    style::Stylize,
+    text::{Line, Span},
+    Frame,
+};
+
+use crate::{
+    app::{
+        app::App,
+        ergonomics::{self, RangeFit, Recommendation},
+        height::{HeightFormat, HeightUnit},
+        state::{ErgonomicsWizard, Modal, ModalKind, WizardStep},
+        ui_event_emitter::UIEvent,
+    },
+    idasen::idasen::{MAX_HEIGHT, MIN_HEIGHT},
+    tui::{
+        help_bar,
+        modal::{ConfirmDialog, InputDialog, ModalOutcome},
+        theme::Theme,
+    },
+};
+
+const WIZARD_TITLE: &str = "Ergonomic heights";
+
+struct ErgonomicsView;
+
+impl ErgonomicsView {
+    fn draw_help_bar(frame: &mut Frame, area: Rect, step: &WizardStep) {
+        help_bar::draw(
+            frame,
+            area,
+            match step {
+                WizardStep::Result(_) => ConfirmDialog::help("save", "close"),
+                _ => InputDialog::help("next", "close"),
+            },
+        )
+    }
+
+    fn recommendation_line(
+        label: &str,
+        surface_height: u16,
+        offset: i16,
+        unit: HeightUnit,
+    ) -> Line<'static> {
+        let (desk_height, fit) = ergonomics::fit_desk_range(surface_height, offset);
+        let note = match fit {
+            RangeFit::Fits => "",
+            RangeFit::BelowMin => ", below the desk range",
+            RangeFit::AboveMax => ", above the desk range",
+        };
+
+        Line::from(format!(
+            "{:<10}{} (desk at {}{})",
+            label,
+            unit.format(surface_height),
+            unit.format(desk_height),
+            note
+        ))
+    }
+
+    // note: the range of the desk follows the selected reference, as elsewhere
+    fn result_lines(recommendation: &Recommendation, format: HeightFormat) -> Vec<Line<'static>> {
+        let (offset, unit) = (format.offset, format.unit);
+        vec![
+            Line::from(Span::from("Recommended working surface heights:").bold()),
+            Self::recommendation_line("sitting", recommendation.sitting, offset, unit),
+            Self::recommendation_line("standing", recommendation.standing, offset, unit),
+            Line::from(format!(
+                "The desk reaches {} to {} ({} height).",
+                format.format(MIN_HEIGHT),
+                format.format(MAX_HEIGHT),
+                format.reference.label()
+            )),
+            Line::from(""),
+        ]
+    }
+
+    fn draw_popup(
+        frame: &mut Frame,
+        wizard: &ErgonomicsWizard,
+        format: HeightFormat,
+        theme: &Theme,
+    ) {
+        match wizard.step {
+            WizardStep::Result(ref recommendation) => {
+                let mut lines = Self::result_lines(recommendation, format);
+                lines.push(Line::from(wizard.prompt(format.unit)));
+                ConfirmDialog {
+                    title: WIZARD_TITLE,
+                    lines,
+                }
+                .draw(frame, theme)
+            }
+            _ => InputDialog {
+                title: WIZARD_TITLE,
+                lines: vec![Line::from(wizard.prompt(format.unit))],
+                input: &wizard.input,
+            }
+            .draw(frame, theme),
+        }
+    }
+}
+
+impl App {
+    pub fn handle_ergonomics_event(&mut self, ev: UIEvent) {
+        let is_result = match self.get_current_state().modal(ModalKind::ErgonomicsWizard) {
+            Some(Modal::ErgonomicsWizard(wizard)) => matches!(wizard.step, WizardStep::Result(_)),
+            _ => return,
+        };
+
+        match self.update_ergonomics_wizard(&ev) {
+            ModalOutcome::Confirmed if is_result => self.save_recommended_positions(),
+            ModalOutcome::Confirmed => self.confirm_ergonomics_step(),
+            ModalOutcome::Cancelled => self.close_ergonomics_wizard(),
+            ModalOutcome::Pending => (),
+        }
+    }
+
+    pub fn draw_ergonomics(
+        &mut self,
+        frame: &mut Frame,
+        bar_area: Rect,
+        wizard: &ErgonomicsWizard,
+    ) {
+        let format = self.get_current_state().height_format();
+        ErgonomicsView::draw_popup(frame, wizard, format, self.theme());
+        ErgonomicsView::draw_help_bar(frame, bar_area, &wizard.step)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::height::HeightReference;
+
+    fn range_line(reference: HeightReference) -> String {
+        let recommendation = Recommendation {
+            sitting: 7000,
+            standing: 11000,
+        };
+        let format = HeightFormat {
+            unit: HeightUnit::Centimeters,
+            reference,
+            offset: 300,
+        };
+        ErgonomicsView::result_lines(&recommendation, format)[3]
+            .spans
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect()
+    }
+
+    #[test]
+    fn shows_the_desk_range_in_the_selected_reference() {
+        assert_eq!(
+            range_line(HeightReference::Desk),
+            "The desk reaches 62.00cm to 127.00cm (desk height)."
+        );
+        assert_eq!(
+            range_line(HeightReference::Surface),
+            "The desk reaches 65.00cm to 130.00cm (surface height)."
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use crossterm::event::{KeyCode, MouseEventKind};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::Stylize,
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use crate::{
+    app::{
+        app::App,
+        keymap::{DialogScope, Keymap, KeymapScope},
+        state::{HelpOverlay, Modal, ModalKind},
+        ui_event_emitter::UIEvent,
+    },
+    tui::{centered_rect::centered_rect, help_bar, theme::Theme},
+};
+
+const HELP_WIDTH_PERCENT: u16 = 70;
+const HELP_HEIGHT_PERCENT: u16 = 80;
+const PAGE_LINES: isize = 10;
+
+struct HelpView;
+
+impl HelpView {
+    fn draw_help_bar(frame: &mut Frame, area: Rect) {
+        help_bar::draw(frame, area, Keymap::dialog_help(DialogScope::Help, &[]))
+    }
+
+    // note: a section matching the search is listed whole
+    fn lines(keymap: &Keymap, search: &str, theme: &Theme) -> Vec<Line<'static>> {
+        let search = search.to_lowercase();
+        let mut lines = Vec::new();
+
+        let sections = KeymapScope::ALL
+            .iter()
+            .map(|scope| (scope.title(), keymap.bindings(*scope)))
+            .chain(
+                DialogScope::ALL
+                    .iter()
+                    .map(|scope| (scope.title(), Keymap::dialog_bindings(*scope))),
+            );
+        for (title, bindings) in sections {
+            let is_scope_match = title.to_lowercase().contains(&search);
+            let bindings: Vec<(String, &str)> = bindings
+                .into_iter()
+                .filter(|(keys, description)| {
+                    is_scope_match
+                        || keys.to_lowercase().contains(&search)
+                        || description.to_lowercase().contains(&search)
+                })
+                .collect();
+            if bindings.is_empty() {
+                continue;
+            }
+
+            if !lines.is_empty() {
+                lines.push(Line::from(""));
+            }
+            lines.push(Line::from(Span::styled(title, theme.accent()).bold()));
+
+            let width = bindings
+                .iter()
+                .map(|(keys, _)| keys.chars().count())
+                .max()
+                .unwrap_or(0);
+            lines.extend(bindings.into_iter().map(|(keys, description)| {
+                Line::from(vec![
+                    Span::styled(format!("  {:<width$}", keys, width = width), theme.info()),
+                    Span::from(format!("  {}", description)),
+                ])
+            }));
+        }
+
+        lines
+    }
+
+    // note: returns the scroll, clamped to the lines that fit in the area
+    fn draw_overlay(
+        frame: &mut Frame,
+        area: Rect,
+        help: &HelpOverlay,
+        keymap: &Keymap,
+        theme: &Theme,
+    ) -> usize {
+        let area = centered_rect(area, HELP_WIDTH_PERCENT, HELP_HEIGHT_PERCENT);
+        let container = Block::default()
+            .title("Key bindings")
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(theme.focus_border());
+
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(2), Constraint::Min(0)])
+            .split(container.inner(area));
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(container, area);
+        frame.render_widget(Paragraph::new(help.search.line(0)), layout[0]);
+
+        let lines = Self::lines(keymap, help.search.value(), theme);
+        if lines.is_empty() {
+            frame.render_widget(Paragraph::new("no matching key"), layout[1]);
+            return 0;
+        }
+
+        let scroll = help
+            .scroll
+            .min(lines.len().saturating_sub(layout[1].height as usize));
+        frame.render_widget(Paragraph::new(lines).scroll((scroll as u16, 0)), layout[1]);
+        scroll
+    }
+}
+
+impl App {
+    pub fn handle_help_event(&mut self, ev: UIEvent) {
+        let has_search = match self.get_current_state().modal(ModalKind::Help) {
+            Some(Modal::Help(help)) => !help.search.value().is_empty(),
+            _ => return,
+        };
+
+        match ev {
+            UIEvent::KeyPress(ev) => match ev.code {
+                KeyCode::Esc if has_search => self.clear_help_search(),
+                KeyCode::Esc => self.close_help(),
+                KeyCode::Up => self.scroll_help(-1),
+                KeyCode::Down => self.scroll_help(1),
+                KeyCode::PageUp => self.scroll_help(-PAGE_LINES),
+                KeyCode::PageDown => self.scroll_help(PAGE_LINES),
+                KeyCode::Home => self.scroll_help(isize::MIN),
+                KeyCode::End => self.scroll_help(isize::MAX),
+                _ => self.edit_help_search(&ev),
+            },
+            UIEvent::Click(ev) => match ev.kind {
+                MouseEventKind::ScrollUp => self.scroll_help(-1),
+                MouseEventKind::ScrollDown => self.scroll_help(1),
+                _ => (),
+            },
+            _ => (),
+        }
+    }
+
+    pub fn draw_help(
+        &mut self,
+        frame: &mut Frame,
+        view_area: Rect,
+        bar_area: Rect,
+        help: &HelpOverlay,
+    ) {
+        let scroll = HelpView::draw_overlay(frame, view_area, help, self.keymap(), self.theme());
+        self.set_help_scroll(scroll);
+        HelpView::draw_help_bar(frame, bar_area)
+    }
+}
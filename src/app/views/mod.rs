@@ -1,4 +1,11 @@
+pub mod chart;
 pub mod connections;
 pub mod devices;
+pub mod ergonomics;
+pub mod help;
 pub mod movement;
+pub mod notifications;
+pub mod palette;
 pub mod positions;
+pub mod schedules;
+pub mod statistics;
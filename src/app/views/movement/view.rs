@@ -1,9 +1,11 @@
-use crossterm::event::KeyCode;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crossterm::event::KeyEvent;
 use ratatui::{
-    layout::Rect,
-    style::{Style, Stylize},
+    layout::{Constraint, Direction as LayoutDirection, Layout, Rect},
+    style::Stylize,
     text::Line,
-    widgets::{Block, BorderType, Borders, Padding},
+    widgets::{Block, BorderType, Borders, LineGauge, Padding, Paragraph},
     Frame,
 };
 use tui_big_text::{BigText, PixelSize};
@@ -11,53 +13,149 @@ use tui_big_text::{BigText, PixelSize};
 use crate::{
     app::{
         actions::MovingDirection,
-        app::{App, Views},
+        app::{App, MouseTarget, MoveButton, Views},
+        height::{HeightFormat, HeightUnit, MAX_FORMATTED_HEIGHT_LEN},
+        keymap::{Action, Keymap, KeymapScope},
+        layout,
+        state::{ModalKind, MoveOutcome, State},
         ui_event_emitter::UIEvent,
     },
-    idasen::idasen::{Direction, PositionSpeed},
-    tui::help_bar,
+    idasen::idasen::{Direction, MAX_HEIGHT, MIN_HEIGHT},
+    tui::{
+        height_gauge::{HeightGauge, HEIGHT_GAUGE_WIDTH},
+        help_bar,
+        modal::{InputDialog, ModalOutcome, TextInput},
+        theme::Theme,
+    },
 };
 
+// note: a line of quadrant big text takes 4 rows, and 4 columns a char
+const BIG_TEXT_HEIGHT: u16 = 4;
+const BIG_TEXT_WIDTH: u16 = MAX_FORMATTED_HEIGHT_LEN * 4;
+// note: tenth of mm
+const NUDGE_STEP: i32 = 100;
+const FINE_NUDGE_STEP: i32 = 50;
+const MOVE_BUTTONS: [(MoveButton, &str); 3] = [
+    (MoveButton::Up, " ▲ up "),
+    (MoveButton::Stop, " ■ stop "),
+    (MoveButton::Down, " ▼ down "),
+];
+const MOVE_BUTTONS_GAP: u16 = 2;
+const GAUGE_MARGIN: u16 = 2;
+const ARROW_FRAME_MILLIS: u128 = 250;
+// note: the progress bar and the line below it
+const MOVE_PROGRESS_HEIGHT: u16 = 2;
+const MOVEMENT_CONTENT_WIDTH: u16 = HEIGHT_GAUGE_WIDTH + GAUGE_MARGIN + BIG_TEXT_WIDTH;
+// note: the height, the other reference, the progress and the buttons
+pub const MOVEMENT_CONTENT_HEIGHT: u16 = BIG_TEXT_HEIGHT + 1 + MOVE_PROGRESS_HEIGHT + 1;
+// note: the height in big text between the borders, the rest is left out
+pub const MOVEMENT_MIN_HEIGHT: u16 = BIG_TEXT_HEIGHT + 2;
+pub const MOVEMENT_MIN_WIDTH: u16 = MOVEMENT_CONTENT_WIDTH + 2;
+
+// note: what a key of the section asks the app to do
+#[derive(Debug, PartialEq)]
+enum MovementKey {
+    Action(Action),
+    // note: a nudge, with the count applied
+    MoveTo(u16),
+}
+
 struct MovementView;
 
 impl MovementView {
-    fn draw_help_bar(frame: &mut Frame, area: Rect, is_connected: bool) {
-        help_bar::draw(
-            frame,
-            area,
-            if is_connected {
-                vec![
-                    "q: quit".to_string(),
-                    "u: up".to_string(),
-                    "d: down".to_string(),
-                ]
-            } else {
-                vec!["q: quit".to_string()]
-            },
-        )
+    // note: the count is typed and used up in the state
+    fn handle_key(keymap: &Keymap, state: &mut State, ev: &KeyEvent) -> Option<MovementKey> {
+        let step = match keymap.action(KeymapScope::Movement, ev) {
+            Some(Action::NudgeUp) => NUDGE_STEP,
+            Some(Action::NudgeDown) => -NUDGE_STEP,
+            Some(Action::FineNudgeUp) => FINE_NUDGE_STEP,
+            Some(Action::FineNudgeDown) => -FINE_NUDGE_STEP,
+            Some(Action::ClearCount) => {
+                state.nudge_count = None;
+                return None;
+            }
+            Some(action) => return Some(MovementKey::Action(action)),
+            None => {
+                if let Some(digit) = Keymap::count_digit(KeymapScope::Movement, ev) {
+                    state.push_nudge_count_digit(digit)
+                }
+                return None;
+            }
+        };
+
+        state.take_nudge_target(step).map(MovementKey::MoveTo)
+    }
+
+    fn draw_help_bar(
+        frame: &mut Frame,
+        area: Rect,
+        keymap: &Keymap,
+        is_connected: bool,
+        format: HeightFormat,
+    ) {
+        let mut buttons = keymap.help(KeymapScope::Global, &[Action::Quit, Action::Help]);
+
+        if is_connected {
+            buttons.extend(keymap.help(
+                KeymapScope::Movement,
+                &[
+                    Action::MoveUp,
+                    Action::MoveDown,
+                    Action::GoToHeight,
+                    Action::NudgeUp,
+                    Action::NudgeDown,
+                    Action::FineNudgeUp,
+                    Action::FineNudgeDown,
+                ],
+            ));
+            buttons.extend(Keymap::fixed_help(KeymapScope::Movement));
+            buttons.extend(keymap.help_with(
+                KeymapScope::Movement,
+                Action::ToggleReference,
+                &format!("reference ({})", format.reference.label()),
+            ));
+        }
+
+        help_bar::draw(frame, area, buttons)
+    }
+
+    fn draw_height_input_help_bar(frame: &mut Frame, area: Rect) {
+        help_bar::draw(frame, area, InputDialog::help("move", "cancel"))
+    }
+
+    fn draw_height_input_popup(
+        frame: &mut Frame,
+        input: &TextInput,
+        format: HeightFormat,
+        theme: &Theme,
+    ) {
+        InputDialog {
+            title: "Movement",
+            lines: vec![Line::from(format!(
+                "Target {} height in {} (or e.g. 104.5cm, 41in, 1045mm)",
+                format.reference.label(),
+                format.unit.suffix()
+            ))],
+            input,
+        }
+        .draw(frame, theme)
     }
 
     fn draw_movement_box(
         frame: &mut Frame,
         area: Rect,
-        position: Option<PositionSpeed>,
+        state: &State,
+        theme: &Theme,
         is_focused: bool,
-    ) {
-        let direction_text = match position {
-            // fixme: it would be nice to substitute this with an arrow
-            Some(ref p) => match p.get_direction() {
-                Direction::Up => "(up)",
-                Direction::Down => "(down)",
-                Direction::Idle => "",
-            },
-            None => "",
-        };
+        is_compact: bool,
+    ) -> Vec<(MoveButton, Rect)> {
+        let (position, format) = (state.position.as_ref(), state.height_format());
 
         let movement_text = BigText::builder()
             .pixel_size(PixelSize::Quadrant)
-            .style(Style::new().blue())
-            .lines(vec![Line::from(if let Some(ref v) = position {
-                format!("{:.2}cm {}", v.position_to_cm(), direction_text)
+            .style(theme.accent())
+            .lines(vec![Line::from(if let Some(v) = position {
+                format.format(v.position)
             } else {
                 "???".to_string()
             })])
@@ -67,48 +165,404 @@ impl MovementView {
         let mut container = Block::default()
             .border_type(BorderType::Rounded)
             .borders(Borders::ALL)
-            .padding(Padding::new(4, 4, 2, 2))
-            .title("Movement");
+            .padding(if is_compact {
+                Padding::zero()
+            } else {
+                layout::section_padding(area, MOVEMENT_CONTENT_WIDTH, MOVEMENT_CONTENT_HEIGHT)
+            })
+            .title(match state.nudge_count {
+                Some(count) => format!("Movement ({}x)", count),
+                None => "Movement".to_string(),
+            });
         if is_focused {
-            container = container.border_style(Style::new().bold().light_red());
+            container = container.border_style(theme.focus_border());
         }
 
-        let inner_area = container.inner(area);
+        let columns = Layout::default()
+            .direction(LayoutDirection::Horizontal)
+            .constraints([
+                Constraint::Length(HEIGHT_GAUGE_WIDTH),
+                Constraint::Length(GAUGE_MARGIN),
+                Constraint::Min(0),
+            ])
+            .split(container.inner(area));
+
+        let layout = Layout::default()
+            .direction(LayoutDirection::Vertical)
+            .constraints([
+                Constraint::Length(BIG_TEXT_HEIGHT),
+                Constraint::Length(1),
+                Constraint::Length(MOVE_PROGRESS_HEIGHT),
+                Constraint::Min(0),
+                Constraint::Length(1),
+            ])
+            .split(columns[2]);
 
         frame.render_widget(container.clone(), area);
-        frame.render_widget(movement_text, inner_area);
+        frame.render_widget(Self::height_gauge(state, theme), columns[0]);
+        frame.render_widget(movement_text, layout[0]);
+
+        // note: the other reference is shown as well, as long as it differs
+        if let Some(v) = position {
+            let other = HeightFormat {
+                reference: format.reference.toggle(),
+                ..format
+            };
+            if other.format(v.position) != format.format(v.position) {
+                frame.render_widget(
+                    Paragraph::new(format!(
+                        "{}: {}",
+                        other.reference.label(),
+                        other.format(v.position)
+                    ))
+                    .dim(),
+                    layout[1],
+                );
+            }
+        }
+
+        Self::draw_move_progress(frame, layout[2], state, theme);
+
+        match position {
+            Some(_) => Self::draw_move_buttons(frame, layout[4], theme),
+            None => Vec::new(),
+        }
+    }
+
+    fn format_eta(eta: Option<Duration>) -> String {
+        match eta {
+            Some(eta) => format!("~{}s", eta.as_secs_f32().ceil() as u64),
+            None => "~?s".to_string(),
+        }
+    }
+
+    // note: the move in progress if any, otherwise how the last one ended
+    fn draw_move_progress(frame: &mut Frame, area: Rect, state: &State, theme: &Theme) {
+        let rows = Layout::default()
+            .direction(LayoutDirection::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Length(1)])
+            .split(area);
+        let format = state.height_format();
+
+        match (state.move_progress, state.position.as_ref()) {
+            (Some(progress), Some(position)) => {
+                let current = position.position;
+                frame.render_widget(
+                    LineGauge::default()
+                        .ratio(progress.ratio(current))
+                        .gauge_style(theme.accent()),
+                    rows[0],
+                );
+                frame.render_widget(
+                    Paragraph::new(format!(
+                        "{} → {}, {} left, {}",
+                        format.format(progress.from),
+                        format.format(progress.target),
+                        format.unit.format(progress.remaining(current)),
+                        Self::format_eta(progress.eta(current))
+                    )),
+                    rows[1],
+                );
+            }
+            (None, _) => match state.last_move {
+                Some(MoveOutcome::Reached { target, error, .. }) => frame.render_widget(
+                    Paragraph::new(format!(
+                        "reached {} (±{})",
+                        format.format(target),
+                        HeightUnit::Millimeters.format(error)
+                    ))
+                    .style(theme.success()),
+                    rows[1],
+                ),
+                Some(MoveOutcome::Aborted {
+                    target, ref reason, ..
+                }) => frame.render_widget(
+                    Paragraph::new(format!(
+                        "move to {} aborted: {}",
+                        format.format(target),
+                        reason
+                    ))
+                    .style(theme.error()),
+                    rows[1],
+                ),
+                None => (),
+            },
+            _ => (),
+        }
+    }
+
+    fn height_gauge<'a>(state: &State, theme: &'a Theme) -> HeightGauge<'a> {
+        let frame = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |t| t.as_millis() / ARROW_FRAME_MILLIS) as usize;
+
+        HeightGauge {
+            min: MIN_HEIGHT,
+            max: MAX_HEIGHT,
+            current: state.position.as_ref().map(|p| p.position),
+            direction: state
+                .position
+                .as_ref()
+                .map_or(Direction::Idle, |p| p.get_direction()),
+            target: state.move_progress.map(|p| p.target),
+            ticks: state
+                .positions_list
+                .items()
+                .iter()
+                .map(|p| p.height)
+                .collect(),
+            frame,
+            theme,
+        }
+    }
+
+    // note: buttons that do not fit in the panel are left out
+    fn draw_move_buttons(frame: &mut Frame, area: Rect, theme: &Theme) -> Vec<(MoveButton, Rect)> {
+        let mut buttons = Vec::new();
+        let mut x = area.x;
+
+        for (button, label) in MOVE_BUTTONS {
+            let width = label.chars().count() as u16;
+            if area.height == 0 || x + width > area.right() {
+                break;
+            }
+
+            let button_area = Rect::new(x, area.y, width, 1);
+            frame.render_widget(
+                Paragraph::new(label).style(theme.accent().reversed()),
+                button_area,
+            );
+            buttons.push((button, button_area));
+            x += width + MOVE_BUTTONS_GAP;
+        }
+
+        buttons
     }
 }
 
 impl App {
     pub fn handle_movement_event(&mut self, ev: UIEvent) {
+        let state = self.get_current_state();
         match ev {
-            UIEvent::KeyPress(ev) => match ev.code {
-                KeyCode::Char('u') => self.trigger_movement(MovingDirection::Up),
-                KeyCode::Char('d') => self.trigger_movement(MovingDirection::Down),
-                _ => (),
-            },
-            UIEvent::KeyRelease(ev) => match ev.code {
-                KeyCode::Char('u') | KeyCode::Char('d') => {
+            UIEvent::KeyPress(ev) => {
+                match self.update_state(|state| MovementView::handle_key(self.keymap(), state, &ev))
+                {
+                    Some(MovementKey::MoveTo(target)) => {
+                        self.trigger_movement(MovingDirection::ToHeight(target))
+                    }
+                    Some(MovementKey::Action(Action::MoveUp)) => {
+                        self.trigger_movement(MovingDirection::Up)
+                    }
+                    Some(MovementKey::Action(Action::MoveDown)) => {
+                        self.trigger_movement(MovingDirection::Down)
+                    }
+                    Some(MovementKey::Action(Action::GoToHeight))
+                        if state.connected_device.is_some() =>
+                    {
+                        self.open_height_input()
+                    }
+                    Some(MovementKey::Action(Action::ToggleReference)) => {
+                        self.toggle_height_reference()
+                    }
+                    _ => (),
+                }
+            }
+            UIEvent::KeyRelease(ev) => {
+                if let Some(Action::MoveUp | Action::MoveDown) =
+                    self.keymap().action(KeymapScope::Movement, &ev)
+                {
                     self.trigger_movement(MovingDirection::Stop)
                 }
-                _ => (),
-            },
+            }
             _ => (),
         }
     }
 
+    pub fn handle_height_input_event(&mut self, ev: UIEvent) {
+        match self.update_height_input(&ev) {
+            ModalOutcome::Confirmed => self.confirm_height_input(),
+            ModalOutcome::Cancelled => self.close_height_input(),
+            ModalOutcome::Pending => (),
+        }
+    }
+
     pub fn draw_movement(&mut self, frame: &mut Frame, content_area: Rect, bar_area: Rect) {
         let state = self.get_current_state();
-        MovementView::draw_movement_box(
+        self.register_mouse_target(MouseTarget::View(Views::Movement), content_area);
+        let buttons = MovementView::draw_movement_box(
             frame,
             content_area,
-            state.position,
+            &state,
+            self.theme(),
             state.current_view == Views::Movement,
+            self.is_compact(),
         );
+        for (button, area) in buttons {
+            self.register_mouse_target(MouseTarget::MoveButton(button), area);
+        }
+
+        if state.current_view == Views::Movement && !state.has_modal(ModalKind::HeightInput) {
+            MovementView::draw_help_bar(
+                frame,
+                bar_area,
+                self.keymap(),
+                state.connected_device.is_some(),
+                state.height_format(),
+            )
+        }
+    }
+
+    pub fn draw_height_input(&mut self, frame: &mut Frame, bar_area: Rect, input: &TextInput) {
+        let state = self.get_current_state();
+        MovementView::draw_height_input_popup(frame, input, state.height_format(), self.theme());
+        MovementView::draw_height_input_help_bar(frame, bar_area)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crossterm::event::{KeyCode, KeyModifiers};
+    use ratatui::{backend::TestBackend, buffer::Buffer, widgets::Widget, Terminal};
 
-        if state.current_view == Views::Movement {
-            MovementView::draw_help_bar(frame, bar_area, state.connected_device.is_some())
+    use crate::{
+        app::{config::Config, height::HeightReference},
+        idasen::idasen::PositionSpeed,
+    };
+
+    use super::*;
+
+    fn state() -> State {
+        let config: Config = serde_json::from_value(serde_json::json!({
+            "predefined_mac": "AA:BB:CC:DD:EE:FF",
+            "height_offsets": { "AA:BB:CC:DD:EE:FF": i16::MAX },
+            "saved_positions": [
+                { "name": "sitting", "height": 7000 },
+                { "name": "focus", "height": 9000 },
+                { "name": "standing", "height": 11000 },
+            ],
+        }))
+        .unwrap();
+        State::new(config, None)
+    }
+
+    fn big_text(height: &str) -> Buffer {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 2 * BIG_TEXT_WIDTH, BIG_TEXT_HEIGHT));
+        BigText::builder()
+            .pixel_size(PixelSize::Quadrant)
+            .lines(vec![Line::from(height)])
+            .build()
+            .unwrap()
+            .render(buffer.area, &mut buffer);
+        buffer
+    }
+
+    // note: the symbols of the big text, cut to the given width
+    fn big_text_rows(buffer: &Buffer, x: u16, y: u16, width: u16) -> Vec<String> {
+        (y..y + BIG_TEXT_HEIGHT)
+            .map(|y| (x..x + width).map(|x| buffer.get(x, y).symbol()).collect())
+            .collect()
+    }
+
+    fn used_width(buffer: &Buffer) -> u16 {
+        (0..buffer.area.height)
+            .flat_map(|y| (0..buffer.area.width).map(move |x| (x, y)))
+            .filter(|(x, y)| buffer.get(*x, *y).symbol() != " ")
+            .map(|(x, _)| x + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    #[test]
+    fn fits_the_widest_heights_in_the_big_text() {
+        for height in ["6553.5mm", "655.35cm", "258.01in"] {
+            assert!(
+                used_width(&big_text(height)) <= BIG_TEXT_WIDTH,
+                "{}",
+                height
+            );
         }
     }
+
+    #[test]
+    fn draws_the_section_at_any_size() {
+        let theme = Theme::new(&Default::default(), false).0;
+        let mut state = state();
+        state.height_reference = HeightReference::Surface;
+        state.position = Some(PositionSpeed {
+            position: MAX_HEIGHT,
+            speed: 0,
+        });
+        state.move_progress = Some(crate::app::state::MoveProgress::new(MIN_HEIGHT, MAX_HEIGHT));
+        let height_text = state.height_format().format(MAX_HEIGHT);
+        assert_eq!(height_text.len(), MAX_FORMATTED_HEIGHT_LEN as usize);
+        let text = big_text(&height_text);
+        let text_width = used_width(&text);
+        let expected = big_text_rows(&text, 0, 0, text_width);
+
+        let sizes = (1..=MOVEMENT_MIN_WIDTH + 12)
+            .flat_map(|width| (1..=MOVEMENT_CONTENT_HEIGHT + 8).map(move |height| (width, height)));
+        for (width, height) in sizes {
+            for is_compact in [false, true] {
+                let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+                terminal
+                    .draw(|frame| {
+                        MovementView::draw_movement_box(
+                            frame,
+                            frame.size(),
+                            &state,
+                            &theme,
+                            true,
+                            is_compact,
+                        );
+                    })
+                    .unwrap();
+
+                // note: the big text starts after the border, the padding and the gauge
+                if width >= MOVEMENT_MIN_WIDTH && height >= MOVEMENT_MIN_HEIGHT {
+                    let buffer = terminal.backend().buffer();
+                    let found = (0..=width - text_width).any(|x| {
+                        (0..=height - BIG_TEXT_HEIGHT)
+                            .any(|y| big_text_rows(buffer, x, y, text_width) == expected)
+                    });
+                    assert!(found, "clipped at {}x{}", width, height);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn digits_count_the_next_nudge_instead_of_going_to_a_position() {
+        let (keymap, errors) = Keymap::new(&HashMap::new());
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        let mut state = state();
+        state.current_view = Views::Movement;
+        state.position = Some(PositionSpeed {
+            position: 8000,
+            speed: 0,
+        });
+
+        let keys: Vec<Option<MovementKey>> = ['3', '+']
+            .into_iter()
+            .map(|c| {
+                let ev = KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+                assert_eq!(
+                    keymap.global_action(state.current_view.keymap_scope(), &ev),
+                    None
+                );
+                MovementView::handle_key(&keymap, &mut state, &ev)
+            })
+            .collect();
+
+        assert_eq!(
+            keys,
+            vec![
+                None,
+                Some(MovementKey::MoveTo(8000 + 3 * NUDGE_STEP as u16))
+            ]
+        );
+        assert_eq!(state.nudge_count, None);
+    }
 }
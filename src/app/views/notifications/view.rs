@@ -0,0 +1,162 @@
+use crossterm::event::MouseEventKind;
+use ratatui::{
+    layout::Rect,
+    style::Stylize,
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use crate::{
+    app::{
+        app::App,
+        jobs::notification_job::{NotificationHistory, NotificationType},
+        keymap::{Action, Keymap, KeymapScope},
+        state::NotificationCentre,
+        ui_event_emitter::UIEvent,
+    },
+    tui::{centered_rect::centered_rect, help_bar, theme::Theme},
+};
+
+const NOTIFICATIONS_WIDTH_PERCENT: u16 = 80;
+const NOTIFICATIONS_HEIGHT_PERCENT: u16 = 60;
+const TIME_FORMAT: &str = "%H:%M:%S";
+
+struct NotificationCentreView;
+
+impl NotificationCentreView {
+    fn draw_help_bar(frame: &mut Frame, area: Rect, keymap: &Keymap) {
+        let mut buttons = keymap.help(KeymapScope::Global, &[Action::Quit]);
+
+        // note: the global toggle closes the overlay as well
+        let close_keys: Vec<String> = [
+            keymap.keys_label(KeymapScope::Global, Action::Notifications),
+            keymap.keys_label(KeymapScope::Notifications, Action::Close),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if !close_keys.is_empty() {
+            buttons.push(format!(
+                "{}: {}",
+                close_keys.join("/"),
+                Action::Close.description()
+            ));
+        }
+
+        buttons.extend(keymap.help(
+            KeymapScope::Notifications,
+            &[Action::SelectPrev, Action::SelectNext, Action::ClearHistory],
+        ));
+        buttons.extend(keymap.help(KeymapScope::Global, &[Action::DismissNotifications]));
+        help_bar::draw(frame, area, buttons)
+    }
+
+    // note: the most recent first
+    fn lines(history: &NotificationHistory, theme: &Theme) -> Vec<Line<'static>> {
+        history
+            .entries()
+            .iter()
+            .rev()
+            .map(|entry| {
+                let style = match entry.notification.kind {
+                    NotificationType::Info => theme.info(),
+                    NotificationType::Warning => theme.warning(),
+                    NotificationType::Error => theme.error(),
+                };
+                let time = if entry.count > 1 {
+                    format!(
+                        "{}-{}",
+                        entry.first_at.format(TIME_FORMAT),
+                        entry.last_at.format(TIME_FORMAT)
+                    )
+                } else {
+                    entry.last_at.format(TIME_FORMAT).to_string()
+                };
+                let count = if entry.count > 1 {
+                    format!(" (x{})", entry.count)
+                } else {
+                    String::new()
+                };
+
+                Line::from(vec![
+                    Span::from(format!("{:<17} ", time)).dim(),
+                    Span::styled(format!("{:<8}", entry.notification.kind.label()), style),
+                    Span::from(format!("{}{}", entry.notification.message, count)),
+                ])
+            })
+            .collect()
+    }
+
+    // note: returns the scroll, clamped to the lines that fit in the area
+    fn draw_overlay(
+        frame: &mut Frame,
+        area: Rect,
+        history: &NotificationHistory,
+        scroll: usize,
+        theme: &Theme,
+    ) -> usize {
+        let area = centered_rect(
+            area,
+            NOTIFICATIONS_WIDTH_PERCENT,
+            NOTIFICATIONS_HEIGHT_PERCENT,
+        );
+        let container = Block::default()
+            .title(format!("Notifications ({})", history.entries().len()))
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(theme.focus_border());
+        let inner_area = container.inner(area);
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(container, area);
+
+        let lines = Self::lines(history, theme);
+        if lines.is_empty() {
+            frame.render_widget(Paragraph::new("no notifications"), inner_area);
+            return 0;
+        }
+
+        let scroll = scroll.min(lines.len().saturating_sub(inner_area.height as usize));
+        frame.render_widget(Paragraph::new(lines).scroll((scroll as u16, 0)), inner_area);
+        scroll
+    }
+}
+
+impl App {
+    pub fn handle_notification_centre_event(&mut self, ev: UIEvent) {
+        match ev {
+            UIEvent::KeyPress(ev) => match self.keymap().action(KeymapScope::Notifications, &ev) {
+                Some(Action::Close) => self.toggle_notification_centre(),
+                Some(Action::SelectPrev) => self.scroll_notification_centre(-1),
+                Some(Action::SelectNext) => self.scroll_notification_centre(1),
+                Some(Action::ClearHistory) => self.clear_notifications(),
+                _ => (),
+            },
+            UIEvent::Click(ev) => match ev.kind {
+                MouseEventKind::ScrollUp => self.scroll_notification_centre(-1),
+                MouseEventKind::ScrollDown => self.scroll_notification_centre(1),
+                _ => (),
+            },
+            _ => (),
+        }
+    }
+
+    pub fn draw_notification_centre(
+        &mut self,
+        frame: &mut Frame,
+        view_area: Rect,
+        bar_area: Rect,
+        centre: &NotificationCentre,
+    ) {
+        let scroll = NotificationCentreView::draw_overlay(
+            frame,
+            view_area,
+            &self.notification_history(),
+            centre.scroll,
+            self.theme(),
+        );
+        self.set_notification_centre_scroll(scroll);
+        NotificationCentreView::draw_help_bar(frame, bar_area, self.keymap())
+    }
+}
@@ -0,0 +1,112 @@
+use crossterm::event::{KeyCode, KeyModifiers, MouseEventKind};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use crate::{
+    app::{
+        actions::SelectionMove,
+        app::App,
+        keymap::{DialogScope, Keymap},
+        palette::{self, CommandPalette, PaletteEntry},
+        ui_event_emitter::UIEvent,
+    },
+    tui::{centered_rect::centered_rect, help_bar, theme::Theme},
+};
+
+const PALETTE_WIDTH_PERCENT: u16 = 60;
+const PALETTE_HEIGHT_PERCENT: u16 = 60;
+
+struct PaletteView;
+
+impl PaletteView {
+    fn draw_help_bar(frame: &mut Frame, area: Rect) {
+        help_bar::draw(frame, area, Keymap::dialog_help(DialogScope::Palette, &[]))
+    }
+
+    fn draw_popup(
+        frame: &mut Frame,
+        area: Rect,
+        palette: &CommandPalette,
+        entries: &[PaletteEntry],
+        theme: &Theme,
+    ) {
+        let area = centered_rect(area, PALETTE_WIDTH_PERCENT, PALETTE_HEIGHT_PERCENT);
+        let container = Block::default()
+            .title("Commands")
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(theme.focus_border());
+
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)])
+            .split(container.inner(area));
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(container, area);
+        frame.render_widget(Paragraph::new(palette.input.line(0)), layout[0]);
+
+        if entries.is_empty() {
+            frame.render_widget(Paragraph::new("no matching command"), layout[1]);
+            return;
+        }
+
+        let items: Vec<ListItem> = entries
+            .iter()
+            .map(|entry| ListItem::new(entry.label.clone()))
+            .collect();
+        let list = List::new(items)
+            .highlight_style(Style::default().add_modifier(Modifier::BOLD | Modifier::REVERSED))
+            .highlight_symbol("> ");
+        // note: the list scrolls to keep the selection visible
+        let mut list_state = ListState::default().with_selected(Some(palette.selected));
+        frame.render_stateful_widget(list, layout[1], &mut list_state)
+    }
+}
+
+impl App {
+    pub fn handle_command_palette_event(&mut self, ev: UIEvent) {
+        match ev {
+            UIEvent::KeyPress(ev) => match ev.code {
+                KeyCode::Esc => self.close_command_palette(),
+                KeyCode::Enter => self.confirm_command_palette(),
+                KeyCode::Up => self.move_command_palette_selection(SelectionMove::Prev),
+                KeyCode::Down => self.move_command_palette_selection(SelectionMove::Next),
+                KeyCode::Char('p') if ev.modifiers.contains(KeyModifiers::CONTROL) => {
+                    self.move_command_palette_selection(SelectionMove::Prev)
+                }
+                KeyCode::Char('n') if ev.modifiers.contains(KeyModifiers::CONTROL) => {
+                    self.move_command_palette_selection(SelectionMove::Next)
+                }
+                _ => self.edit_command_palette_input(&ev),
+            },
+            UIEvent::Click(ev) => match ev.kind {
+                MouseEventKind::ScrollUp => {
+                    self.move_command_palette_selection(SelectionMove::Prev)
+                }
+                MouseEventKind::ScrollDown => {
+                    self.move_command_palette_selection(SelectionMove::Next)
+                }
+                _ => (),
+            },
+            _ => (),
+        }
+    }
+
+    pub fn draw_command_palette(
+        &mut self,
+        frame: &mut Frame,
+        view_area: Rect,
+        bar_area: Rect,
+        palette: &CommandPalette,
+    ) {
+        let state = self.get_current_state();
+        let entries = palette::filter(self.palette_entries(&state), palette.input.value());
+        PaletteView::draw_popup(frame, view_area, palette, &entries, self.theme());
+        PaletteView::draw_help_bar(frame, bar_area)
+    }
+}
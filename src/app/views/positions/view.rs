@@ -1,68 +1,137 @@
-use crossterm::event::KeyCode;
 use ratatui::{
     layout::Rect,
-    style::{Style, Stylize},
+    text::Line,
     widgets::{Block, BorderType, Borders},
     Frame,
 };
 
 use crate::{
     app::{
-        actions::{AppAction, MovingDirection, SelectionMove},
-        app::{App, Views},
+        actions::SelectionMove,
+        app::{App, MouseTarget, Views},
+        height::HeightFormat,
+        keymap::{Action, Keymap, KeymapScope},
+        state::{position_hotkeys, ModalKind, PositionEdit, PositionEditKind},
         ui_event_emitter::UIEvent,
     },
-    tui::help_bar,
+    tui::{
+        help_bar,
+        modal::{ConfirmDialog, InputDialog, ModalOutcome},
+        theme::Theme,
+    },
 };
 
+const EDIT_TITLE: &str = "Saved positions";
+
 struct PositionsView;
 
 impl PositionsView {
-    fn draw_help_bar(frame: &mut Frame, area: Rect, is_connected: bool) {
-        let mut buttons = vec![
-            "q: quit".to_string(),
-            "e: edit".to_string(),
-            "h/j/k/l: move selection".to_string(),
-            "arrows: move selection".to_string(),
-        ];
+    fn draw_help_bar(frame: &mut Frame, area: Rect, keymap: &Keymap, is_connected: bool) {
+        let mut buttons = keymap.help(KeymapScope::Global, &[Action::Quit, Action::Help]);
+        buttons.extend(keymap.help(
+            KeymapScope::Positions,
+            &[
+                Action::Add,
+                Action::Rename,
+                Action::Delete,
+                Action::MoveItemUp,
+                Action::MoveItemDown,
+                Action::Wizard,
+                Action::SelectPrev,
+                Action::SelectNext,
+            ],
+        ));
 
         if is_connected {
-            buttons.push("<enter>: select".to_string());
+            buttons.extend(keymap.help(
+                KeymapScope::Positions,
+                &[Action::SaveCurrent, Action::Select],
+            ));
+            buttons.extend(keymap.positions_help());
         }
 
         help_bar::draw(frame, area, buttons)
     }
+
+    fn draw_edit_help_bar(frame: &mut Frame, area: Rect, edit: &PositionEdit) {
+        help_bar::draw(
+            frame,
+            area,
+            if edit.accepts_text() {
+                InputDialog::help("confirm", "cancel")
+            } else {
+                ConfirmDialog::help("confirm", "cancel")
+            },
+        )
+    }
+
+    fn draw_edit_popup(
+        frame: &mut Frame,
+        edit: &PositionEdit,
+        format: HeightFormat,
+        theme: &Theme,
+    ) {
+        let lines = vec![Line::from(edit.prompt(format))];
+        if edit.accepts_text() {
+            InputDialog {
+                title: EDIT_TITLE,
+                lines,
+                input: &edit.input,
+            }
+            .draw(frame, theme)
+        } else {
+            ConfirmDialog {
+                title: EDIT_TITLE,
+                lines,
+            }
+            .draw(frame, theme)
+        }
+    }
 }
 
 impl App {
     pub fn handle_positions_event(&mut self, ev: UIEvent) {
         let state = self.get_current_state();
-        match ev {
-            UIEvent::KeyPress(ev) => match ev.code {
-                KeyCode::Enter => {
-                    if let Some(_) = state.connected_device {
-                        let current_position = state.positions_list.get_selected();
-                        if let Some(height) = current_position {
-                            self.start_action(AppAction::StartMoving(MovingDirection::ToHeight(
-                                height.height,
-                            )))
-                        }
+        if let UIEvent::KeyPress(ev) = ev {
+            match self.keymap().action(KeymapScope::Positions, &ev) {
+                Some(Action::Select) => self.move_to_selected_position(),
+                Some(Action::SelectPrev) => self.move_position_selection(SelectionMove::Prev),
+                Some(Action::SelectNext) => self.move_position_selection(SelectionMove::Next),
+                Some(Action::MoveItemUp) => self.move_selected_position(SelectionMove::Prev),
+                Some(Action::MoveItemDown) => self.move_selected_position(SelectionMove::Next),
+                Some(Action::Add) => self.start_position_edit(PositionEditKind::AddName),
+                Some(Action::Wizard) => self.open_ergonomics_wizard(),
+                Some(Action::SaveCurrent) => {
+                    if let (Some(_), Some(position)) = (state.connected_device, state.position) {
+                        self.start_position_edit(PositionEditKind::SaveCurrent(position.position))
                     }
                 }
-                KeyCode::Up | KeyCode::Char('k') => {
-                    self.move_position_selection(SelectionMove::Prev)
+                Some(Action::Rename) => {
+                    if let Some(idx) = state.positions_list.selected_index() {
+                        self.start_position_edit(PositionEditKind::Rename(idx))
+                    }
                 }
-                KeyCode::Down | KeyCode::Char('j') => {
-                    self.move_position_selection(SelectionMove::Next)
+                Some(Action::Delete) => {
+                    if let Some(idx) = state.positions_list.selected_index() {
+                        self.start_position_edit(PositionEditKind::Delete(idx))
+                    }
                 }
                 _ => (),
-            },
-            _ => (),
+            }
+        }
+    }
+
+    pub fn handle_position_edit_event(&mut self, ev: UIEvent) {
+        match self.update_position_edit(&ev) {
+            ModalOutcome::Confirmed => self.confirm_position_edit(),
+            ModalOutcome::Cancelled => self.cancel_position_edit(),
+            ModalOutcome::Pending => (),
         }
     }
 
     pub fn draw_positions(&mut self, frame: &mut Frame, view_area: Rect, bar_area: Rect) {
         let mut state = self.get_current_state();
+        self.register_mouse_target(MouseTarget::View(Views::SavedPositions), view_area);
 
         let mut container = Block::default()
             .borders(Borders::ALL)
@@ -70,24 +139,38 @@ impl App {
             .border_type(BorderType::Rounded);
 
         if state.current_view == Views::SavedPositions {
-            container = container.border_style(Style::new().bold().light_red());
+            container = container.border_style(self.theme().focus_border());
         }
 
         frame.render_widget(container.clone(), view_area);
-        state
-            .positions_list
-            .draw(frame, container.inner(view_area), |_| false);
+        let format = state.height_format();
+        let hotkeys = position_hotkeys(state.positions_list.items());
+        state.positions_list.draw_rows(
+            frame,
+            container.inner(view_area),
+            self.theme(),
+            |idx, p| p.render_row(format, hotkeys[idx]),
+            |_| false,
+        );
+        self.register_mouse_target(
+            MouseTarget::ListRows(Views::SavedPositions, state.positions_list.offset()),
+            container.inner(view_area),
+        );
 
-        if state.current_view == Views::SavedPositions {
+        if state.current_view == Views::SavedPositions && !state.has_modal(ModalKind::PositionEdit)
+        {
             PositionsView::draw_help_bar(
                 frame,
                 bar_area,
-                if let Some(_) = state.connected_device {
-                    true
-                } else {
-                    false
-                },
+                self.keymap(),
+                state.connected_device.is_some(),
             )
         }
     }
+
+    pub fn draw_position_edit(&mut self, frame: &mut Frame, bar_area: Rect, edit: &PositionEdit) {
+        let state = self.get_current_state();
+        PositionsView::draw_edit_popup(frame, edit, state.height_format(), self.theme());
+        PositionsView::draw_edit_help_bar(frame, bar_area, edit)
+    }
 }
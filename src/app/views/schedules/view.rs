@@ -0,0 +1,88 @@
+use crossterm::event::MouseEventKind;
+use ratatui::{
+    layout::Rect,
+    widgets::{Block, BorderType, Borders, Clear},
+    Frame,
+};
+
+use crate::{
+    app::{
+        actions::SelectionMove,
+        app::App,
+        keymap::{Action, Keymap, KeymapScope},
+        ui_event_emitter::UIEvent,
+    },
+    tui::{centered_rect::centered_rect, help_bar},
+};
+
+const SCHEDULES_WIDTH_PERCENT: u16 = 80;
+const SCHEDULES_HEIGHT_PERCENT: u16 = 60;
+
+struct SchedulesView;
+
+impl SchedulesView {
+    fn draw_help_bar(frame: &mut Frame, area: Rect, keymap: &Keymap) {
+        let mut buttons = keymap.help(KeymapScope::Global, &[Action::Quit, Action::Help]);
+
+        // note: the global schedules toggle closes the overlay as well
+        let close_keys: Vec<String> = [
+            keymap.keys_label(KeymapScope::Global, Action::ToggleSchedules),
+            keymap.keys_label(KeymapScope::Schedules, Action::Close),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if !close_keys.is_empty() {
+            buttons.push(format!(
+                "{}: {}",
+                close_keys.join("/"),
+                Action::Close.description()
+            ));
+        }
+
+        buttons.extend(keymap.help(
+            KeymapScope::Schedules,
+            &[Action::SelectPrev, Action::SelectNext, Action::SkipNext],
+        ));
+        help_bar::draw(frame, area, buttons)
+    }
+}
+
+impl App {
+    pub fn handle_schedules_event(&mut self, ev: UIEvent) {
+        match ev {
+            UIEvent::KeyPress(ev) => match self.keymap().action(KeymapScope::Schedules, &ev) {
+                Some(Action::Close) => self.toggle_schedules(),
+                Some(Action::SkipNext) => self.toggle_selected_schedule_skip(),
+                Some(Action::SelectPrev) => self.move_schedule_selection(SelectionMove::Prev),
+                Some(Action::SelectNext) => self.move_schedule_selection(SelectionMove::Next),
+                _ => (),
+            },
+            UIEvent::Click(ev) => match ev.kind {
+                MouseEventKind::ScrollUp => self.move_schedule_selection(SelectionMove::Prev),
+                MouseEventKind::ScrollDown => self.move_schedule_selection(SelectionMove::Next),
+                _ => (),
+            },
+            _ => (),
+        }
+    }
+
+    pub fn draw_schedules(&mut self, frame: &mut Frame, view_area: Rect, bar_area: Rect) {
+        let mut state = self.get_current_state();
+
+        let area = centered_rect(view_area, SCHEDULES_WIDTH_PERCENT, SCHEDULES_HEIGHT_PERCENT);
+        let container = Block::default()
+            .title("Schedules")
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(self.theme().focus_border());
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(container.clone(), area);
+        state
+            .schedules
+            .draw(frame, container.inner(area), self.theme(), |_| false);
+
+        SchedulesView::draw_help_bar(frame, bar_area, self.keymap())
+    }
+}
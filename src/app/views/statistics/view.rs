@@ -0,0 +1,196 @@
+use std::time::Duration;
+
+use ratatui::{
+    layout::{Alignment, Constraint, Rect},
+    style::Stylize,
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Cell, Paragraph, Row, Table},
+    Frame,
+};
+
+use crate::{
+    app::{
+        actions::SelectionMove,
+        app::{App, MouseTarget, Views},
+        export::ExportFormat,
+        history::{StatisticsPeriod, StatisticsSummary, UsageStats},
+        keymap::{Action, Keymap, KeymapScope},
+        state::ModalKind,
+        ui_event_emitter::UIEvent,
+    },
+    tui::{
+        help_bar,
+        modal::{InputDialog, ModalOutcome, TextInput},
+        theme::Theme,
+    },
+};
+
+struct StatisticsView;
+
+impl StatisticsView {
+    fn draw_help_bar(frame: &mut Frame, area: Rect, keymap: &Keymap, export_format: ExportFormat) {
+        let mut buttons = keymap.help(KeymapScope::Global, &[Action::Quit, Action::Help]);
+        buttons.extend(keymap.help(
+            KeymapScope::Statistics,
+            &[Action::PrevPeriod, Action::NextPeriod, Action::Export],
+        ));
+        buttons.extend(keymap.help_with(
+            KeymapScope::Statistics,
+            Action::ToggleFormat,
+            &format!("format ({})", export_format.extension()),
+        ));
+
+        help_bar::draw(frame, area, buttons)
+    }
+
+    fn draw_export_range_help_bar(frame: &mut Frame, area: Rect) {
+        help_bar::draw(frame, area, InputDialog::help("export", "cancel"))
+    }
+
+    fn draw_export_range_popup(
+        frame: &mut Frame,
+        input: &TextInput,
+        export_format: ExportFormat,
+        theme: &Theme,
+    ) {
+        InputDialog {
+            title: "Export",
+            lines: vec![
+                Line::from(format!(
+                    "First and last day to export as {}, e.g. 2024-05-01 2024-05-31",
+                    export_format.extension()
+                )),
+                Line::from("The files are written in the home directory"),
+            ],
+            input,
+        }
+        .draw(frame, theme)
+    }
+
+    fn format_duration(duration: Duration) -> String {
+        let minutes = duration.as_secs() / 60;
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    }
+
+    fn draw_statistics_box(
+        frame: &mut Frame,
+        area: Rect,
+        statistics: Option<StatisticsSummary>,
+        selected_period: StatisticsPeriod,
+        theme: &Theme,
+        is_focused: bool,
+    ) {
+        let mut container = Block::default()
+            .title("Statistics")
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded);
+
+        if is_focused {
+            container = container.border_style(theme.focus_border());
+        }
+
+        let statistics = match statistics {
+            Some(s) => s,
+            None => {
+                frame.render_widget(
+                    Paragraph::new("loading history...")
+                        .alignment(Alignment::Center)
+                        .block(container),
+                    area,
+                );
+                return;
+            }
+        };
+
+        let periods = [&statistics.today, &statistics.week, &statistics.month];
+        let row = |label: &'static str, value: fn(&UsageStats) -> String| {
+            Row::new(
+                std::iter::once(label.to_string())
+                    .chain(periods.iter().map(|stats| value(stats)))
+                    .collect::<Vec<String>>(),
+            )
+        };
+
+        let table = Table::new(
+            vec![
+                row("Sitting", |s| Self::format_duration(s.sitting)),
+                row("Standing", |s| Self::format_duration(s.standing)),
+                row("Transitions", |s| s.transitions.to_string()),
+                row("Longest sitting", |s| Self::format_duration(s.longest_sitting)),
+            ],
+            [
+                Constraint::Length(16),
+                Constraint::Length(12),
+                Constraint::Length(12),
+                Constraint::Length(12),
+            ],
+        )
+        .header(
+            Row::new(
+                [
+                    ("", None),
+                    ("Today", Some(StatisticsPeriod::Today)),
+                    ("This week", Some(StatisticsPeriod::Week)),
+                    ("This month", Some(StatisticsPeriod::Month)),
+                ]
+                .map(|(label, period)| {
+                    Cell::from(if period == Some(selected_period) && is_focused {
+                        Span::from(label).reversed()
+                    } else {
+                        Span::from(label)
+                    })
+                }),
+            )
+            .bold(),
+        )
+        .block(container);
+
+        frame.render_widget(table, area);
+    }
+}
+
+impl App {
+    pub fn handle_statistics_event(&mut self, ev: UIEvent) {
+        if let UIEvent::KeyPress(ev) = ev {
+            match self.keymap().action(KeymapScope::Statistics, &ev) {
+                Some(Action::PrevPeriod) => self.move_statistics_period(SelectionMove::Prev),
+                Some(Action::NextPeriod) => self.move_statistics_period(SelectionMove::Next),
+                Some(Action::Export) => self.open_export_range(),
+                Some(Action::ToggleFormat) => self.toggle_export_format(),
+                _ => (),
+            }
+        }
+    }
+
+    pub fn handle_export_range_event(&mut self, ev: UIEvent) {
+        match self.update_export_range(&ev) {
+            ModalOutcome::Confirmed => self.confirm_export_range(),
+            ModalOutcome::Cancelled => self.close_export_range(),
+            ModalOutcome::Pending => (),
+        }
+    }
+
+    pub fn draw_statistics(&mut self, frame: &mut Frame, view_area: Rect, bar_area: Rect) {
+        let state = self.get_current_state();
+        let is_exporting = state.has_modal(ModalKind::ExportRange);
+        self.register_mouse_target(MouseTarget::View(Views::Statistics), view_area);
+        StatisticsView::draw_statistics_box(
+            frame,
+            view_area,
+            state.statistics,
+            state.statistics_period,
+            self.theme(),
+            state.current_view == Views::Statistics,
+        );
+
+        if state.current_view == Views::Statistics && !is_exporting {
+            StatisticsView::draw_help_bar(frame, bar_area, self.keymap(), state.export_format)
+        }
+    }
+
+    pub fn draw_export_range(&mut self, frame: &mut Frame, bar_area: Rect, input: &TextInput) {
+        let state = self.get_current_state();
+        StatisticsView::draw_export_range_popup(frame, input, state.export_format, self.theme());
+        StatisticsView::draw_export_range_help_bar(frame, bar_area)
+    }
+}
@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+
+// note: 90cm, roughly halfway between a sitting and a standing position
+pub const DEFAULT_STANDING_THRESHOLD: u16 = 9000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HeightZone {
+    Sitting,
+    Standing,
+}
+
+impl HeightZone {
+    pub fn from_height(height: u16, standing_threshold: u16) -> Self {
+        if height >= standing_threshold {
+            HeightZone::Standing
+        } else {
+            HeightZone::Sitting
+        }
+    }
+
+    pub fn opposite(&self) -> Self {
+        match self {
+            HeightZone::Sitting => HeightZone::Standing,
+            HeightZone::Standing => HeightZone::Sitting,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            HeightZone::Sitting => "sitting",
+            HeightZone::Standing => "standing",
+        }
+    }
+}
@@ -1,14 +1,20 @@
-use std::time::Duration;
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 
 use btleplug::api::{BDAddr, Central, Manager as _, ParseBDAddrError, Peripheral, ScanFilter};
 use btleplug::platform::{Adapter, Manager};
 use tokio;
 
+use crate::metrics::Metrics;
+
 use super::ble_device::BleDevice;
 
 #[derive(Clone)]
 pub struct BleManager {
     low_level_manager: Manager,
+    metrics: Arc<Mutex<Metrics>>,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -24,9 +30,12 @@ pub enum Error {
 }
 
 impl BleManager {
-    pub async fn new() -> Result<Self, Error> {
+    pub async fn new(metrics: Arc<Mutex<Metrics>>) -> Result<Self, Error> {
         let low_level_manager = Manager::new().await?;
-        Ok(Self { low_level_manager })
+        Ok(Self {
+            low_level_manager,
+            metrics,
+        })
     }
 
     pub async fn find(&self, addr: BDAddr) -> Result<BleDevice, Error> {
@@ -40,6 +49,7 @@ impl BleManager {
     }
 
     pub async fn scan(&self) -> Result<Vec<BleDevice>, Error> {
+        let started_at = Instant::now();
         let adapters = self.low_level_manager.adapters().await?;
         let mut jobs = Vec::new();
 
@@ -56,6 +66,11 @@ impl BleManager {
             }
         }
 
+        self.metrics
+            .lock()
+            .unwrap()
+            .observe_scan(started_at.elapsed());
+
         Ok(peripherals)
     }
 
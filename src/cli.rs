@@ -0,0 +1,161 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::PathBuf,
+};
+
+use chrono::{Local, NaiveDate};
+
+use crate::app::{
+    config::Config,
+    export::{self, ExportFormat, ExportRange},
+    history,
+};
+
+pub const USAGE: &str = "usage:
+    idasen-tui [--profile NAME]
+    idasen-tui [--profile NAME] export [--daily] [--format csv|jsonl] [--from YYYY-MM-DD] [--to YYYY-MM-DD] [--output FILE]
+
+commands:
+    export    writes the recorded height history (or the per-day sit/stand totals
+              with --daily) to FILE, or to the standard output
+
+options:
+    --profile    uses the positions, reminders and history of the given profile";
+
+pub struct ExportOptions {
+    pub daily: bool,
+    pub format: ExportFormat,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub output: Option<PathBuf>,
+}
+
+pub enum Command {
+    Tui,
+    Export(ExportOptions),
+    Help,
+}
+
+pub struct Cli {
+    pub profile: Option<String>,
+    pub command: Command,
+}
+
+fn parse_date(value: Option<String>) -> Result<NaiveDate, String> {
+    let value = value.ok_or("missing date")?;
+    NaiveDate::parse_from_str(&value, "%Y-%m-%d").map_err(|_| format!("invalid date '{}'", value))
+}
+
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Cli, String> {
+    // note: --profile is accepted anywhere, before or after the command
+    let mut profile = None;
+    let mut rest = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--profile" {
+            profile = Some(args.next().ok_or("missing profile name")?);
+        } else {
+            rest.push(arg);
+        }
+    }
+
+    Ok(Cli {
+        profile,
+        command: parse_command(rest)?,
+    })
+}
+
+fn parse_command(args: impl IntoIterator<Item = String>) -> Result<Command, String> {
+    let mut args = args.into_iter();
+
+    match args.next().as_deref() {
+        None => Ok(Command::Tui),
+        Some("-h") | Some("--help") => Ok(Command::Help),
+        Some("export") => {
+            let mut options = ExportOptions {
+                daily: false,
+                format: ExportFormat::Csv,
+                from: None,
+                to: None,
+                output: None,
+            };
+
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--daily" => options.daily = true,
+                    "--format" => options.format = args.next().ok_or("missing format")?.parse()?,
+                    "--from" => options.from = Some(parse_date(args.next())?),
+                    "--to" => options.to = Some(parse_date(args.next())?),
+                    "--output" => {
+                        options.output = Some(args.next().ok_or("missing output file")?.into())
+                    }
+                    _ => return Err(format!("unknown argument '{}'", arg)),
+                }
+            }
+
+            if let (Some(from), Some(to)) = (options.from, options.to) {
+                ExportRange::new(from, to)?;
+            }
+
+            Ok(Command::Export(options))
+        }
+        Some(arg) => Err(format!("unknown command '{}'", arg)),
+    }
+}
+
+pub fn run_export(
+    options: ExportOptions,
+    config: &Config,
+    profile: Option<&str>,
+) -> io::Result<()> {
+    let records = history::history_path(profile)
+        .map(|path| history::load(&path))
+        .unwrap_or_default();
+
+    let today = Local::now().date_naive();
+    let to = options.to.unwrap_or(today);
+    // note: by default the whole history is exported
+    let from = options.from.unwrap_or_else(|| {
+        records
+            .first()
+            .map_or(today, |r| r.at().date_naive())
+            .min(to)
+    });
+    let range =
+        ExportRange::new(from, to).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+    let mut out: Box<dyn Write> = match options.output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(io::stdout().lock()),
+    };
+
+    if options.daily {
+        export::write_daily_totals(
+            &records,
+            config.standing_threshold,
+            range,
+            options.format,
+            &mut out,
+        )?;
+    } else {
+        export::write_history(&records, range, options.format, config.unit, &mut out)?;
+    }
+
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_args(args: &[&str]) -> Result<Cli, String> {
+        parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn export_range_must_not_end_before_it_starts() {
+        assert!(parse_args(&["export", "--from", "2024-05-31", "--to", "2024-05-01"]).is_err());
+        assert!(parse_args(&["export", "--from", "2024-05-01", "--to", "2024-05-01"]).is_ok());
+    }
+}
@@ -9,7 +9,8 @@ use btleplug::api::{BDAddr, Characteristic, ParseBDAddrError, WriteType};
 use btleplug::platform::Peripheral;
 use std::{
     cmp::{max, Ordering},
-    time::Instant,
+    sync::atomic::{self, AtomicBool},
+    time::{Duration, Instant},
 };
 
 use tokio_stream::{Stream, StreamExt};
@@ -29,6 +30,9 @@ const STOP: [u8; 2] = [0xFF, 0x00];
 pub const MIN_HEIGHT: u16 = 6200;
 pub const MAX_HEIGHT: u16 = 12700;
 
+// note: a move is given up when the desk does not move for this long
+const STALL_TIMEOUT: Duration = Duration::from_secs(3);
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct PositionSpeed {
     // tenth mm
@@ -51,10 +55,6 @@ impl PositionSpeed {
         PositionSpeed { position, speed }
     }
 
-    pub fn position_to_cm(&self) -> f32 {
-        self.position as f32 * 0.01
-    }
-
     pub fn get_direction(&self) -> Direction {
         match self.speed {
             0 => Direction::Idle,
@@ -73,6 +73,12 @@ pub enum Error {
     #[error("Desired position has to be between MIN_HEIGHT and MAX_HEIGHT.")]
     PositionNotInRange,
 
+    #[error("the desk stopped before reaching the target")]
+    Stalled,
+
+    #[error("stopped")]
+    Stopped,
+
     #[error("Cannot subscribe to read position.")]
     CannotSubscribePosition,
 
@@ -167,57 +173,13 @@ impl Idasen {
     }
 
     /// Move desk to a desired position. The precision is decent, usually less than 1mm off.
-    pub async fn move_to(&self, target_position: u16) -> Result<(), Error> {
-        self.move_to_target(target_position).await
-    }
-
-    async fn move_to_target(&self, target_position: u16) -> Result<(), Error> {
-        if !(MIN_HEIGHT..=MAX_HEIGHT).contains(&target_position) {
-            return Err(Error::PositionNotInRange);
-        }
-
-        let mut position_reached = false;
-        let mut last_position = self.position().await? as i16;
-        let mut last_position_read_at = Instant::now();
-        let target_position = target_position as i16;
-        while !position_reached {
-            let current_position = self.position().await? as i16;
-            let going_up = match target_position.cmp(&current_position) {
-                Ordering::Greater => true,
-                Ordering::Less => false,
-                Ordering::Equal => return Ok(()),
-            };
-            let remaining_distance = (target_position - current_position).abs();
-            let elapsed_millis = last_position_read_at.elapsed().as_millis();
-            let moved_height = (last_position - current_position).abs();
-
-            // Tenth of millimetres per second
-            let speed = ((moved_height as f64 / elapsed_millis as f64) * 1000f64) as i16;
-
-            if remaining_distance <= 10 {
-                // Millimetre or less is good enough.
-                position_reached = true;
-                self.stop().await?;
-            } else if going_up {
-                self.up().await?;
-            } else if !going_up {
-                self.down().await?;
-            }
-
-            // If we're either:
-            // * less than 5 millimetres, or:
-            // * less than half a second from target
-            // then we need to stop every iteration so that we don't overshoot
-            if remaining_distance < max(speed / 2, 50) {
-                self.stop().await?;
-            }
-
-            // Read last_position again to avoid weird speed readings when switching direction
-            last_position = self.position().await? as i16;
-            last_position_read_at = Instant::now();
-        }
-
-        Ok(())
+    /// The move ends with `Error::Stopped` once `stop_requested` is set.
+    pub async fn move_to(
+        &self,
+        target_position: u16,
+        stop_requested: &AtomicBool,
+    ) -> Result<(), Error> {
+        move_to_target(self, target_position, stop_requested).await
     }
 
     /// Return the desk height in tenth millimeters (1m = 10000)
@@ -248,3 +210,177 @@ impl Idasen {
             }))
     }
 }
+
+// note: the commands the move loop needs, so that it can be tested without a desk
+trait Controls {
+    async fn position(&self) -> Result<u16, Error>;
+    async fn up(&self) -> btleplug::Result<()>;
+    async fn down(&self) -> btleplug::Result<()>;
+    async fn stop(&self) -> btleplug::Result<()>;
+}
+
+impl Controls for Idasen {
+    async fn position(&self) -> Result<u16, Error> {
+        Idasen::position(self).await
+    }
+
+    async fn up(&self) -> btleplug::Result<()> {
+        Idasen::up(self).await
+    }
+
+    async fn down(&self) -> btleplug::Result<()> {
+        Idasen::down(self).await
+    }
+
+    async fn stop(&self) -> btleplug::Result<()> {
+        Idasen::stop(self).await
+    }
+}
+
+async fn move_to_target(
+    desk: &impl Controls,
+    target_position: u16,
+    stop_requested: &AtomicBool,
+) -> Result<(), Error> {
+    if !(MIN_HEIGHT..=MAX_HEIGHT).contains(&target_position) {
+        return Err(Error::PositionNotInRange);
+    }
+
+    let mut position_reached = false;
+    let mut last_position = desk.position().await? as i16;
+    let mut last_position_read_at = Instant::now();
+    let mut last_moved_at = Instant::now();
+    let mut last_moved_position = last_position;
+    let target_position = target_position as i16;
+    while !position_reached {
+        if stop_requested.load(atomic::Ordering::SeqCst) {
+            desk.stop().await?;
+            return Err(Error::Stopped);
+        }
+
+        let current_position = desk.position().await? as i16;
+        if current_position != last_moved_position {
+            last_moved_position = current_position;
+            last_moved_at = Instant::now();
+        } else if last_moved_at.elapsed() > STALL_TIMEOUT {
+            // e.g. an obstacle or the desk being moved from its own controls
+            desk.stop().await?;
+            return Err(Error::Stalled);
+        }
+        let going_up = match target_position.cmp(&current_position) {
+            Ordering::Greater => true,
+            Ordering::Less => false,
+            Ordering::Equal => return Ok(()),
+        };
+        let remaining_distance = (target_position - current_position).abs();
+        let elapsed_millis = last_position_read_at.elapsed().as_millis();
+        let moved_height = (last_position - current_position).abs();
+
+        // Tenth of millimetres per second
+        let speed = ((moved_height as f64 / elapsed_millis as f64) * 1000f64) as i16;
+
+        if remaining_distance <= 10 {
+            // Millimetre or less is good enough.
+            position_reached = true;
+            desk.stop().await?;
+        } else if going_up {
+            desk.up().await?;
+        } else if !going_up {
+            desk.down().await?;
+        }
+
+        // If we're either:
+        // * less than 5 millimetres, or:
+        // * less than half a second from target
+        // then we need to stop every iteration so that we don't overshoot
+        if remaining_distance < max(speed / 2, 50) {
+            desk.stop().await?;
+        }
+
+        // Read last_position again to avoid weird speed readings when switching direction
+        last_position = desk.position().await? as i16;
+        last_position_read_at = Instant::now();
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    // note: moves 1cm on each command and requests a stop once `stop_at` is reached
+    struct FakeDesk {
+        position: Cell<u16>,
+        stops: Cell<u32>,
+        stop_at: u16,
+        stop_requested: AtomicBool,
+    }
+
+    impl FakeDesk {
+        fn new(position: u16, stop_at: u16) -> Self {
+            Self {
+                position: Cell::new(position),
+                stops: Cell::new(0),
+                stop_at,
+                stop_requested: AtomicBool::new(false),
+            }
+        }
+
+        fn step(&self, delta: i32) {
+            let position = (self.position.get() as i32 + delta) as u16;
+            self.position.set(position);
+            if position == self.stop_at {
+                self.stop_requested.store(true, atomic::Ordering::SeqCst);
+            }
+        }
+    }
+
+    impl Controls for FakeDesk {
+        async fn position(&self) -> Result<u16, Error> {
+            Ok(self.position.get())
+        }
+
+        async fn up(&self) -> btleplug::Result<()> {
+            self.step(100);
+            Ok(())
+        }
+
+        async fn down(&self) -> btleplug::Result<()> {
+            self.step(-100);
+            Ok(())
+        }
+
+        async fn stop(&self) -> btleplug::Result<()> {
+            self.stops.set(self.stops.get() + 1);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn moves_to_the_target() {
+        let desk = FakeDesk::new(7000, 0);
+        move_to_target(&desk, 9000, &desk.stop_requested)
+            .await
+            .unwrap();
+        assert_eq!(desk.position.get(), 9000);
+    }
+
+    #[tokio::test]
+    async fn stops_a_move_in_progress_when_requested() {
+        let desk = FakeDesk::new(11000, 9000);
+        let result = move_to_target(&desk, 7000, &desk.stop_requested).await;
+        assert!(matches!(result, Err(Error::Stopped)));
+        assert_eq!(desk.position.get(), 9000);
+
+        // note: a move started while the stop is requested does not move the desk
+        let stops = desk.stops.get();
+        assert!(stops > 0);
+        let result = move_to_target(&desk, 7000, &desk.stop_requested).await;
+        assert!(matches!(result, Err(Error::Stopped)));
+        assert_eq!(desk.position.get(), 9000);
+        assert_eq!(desk.stops.get(), stops + 1);
+    }
+}
@@ -1,32 +1,81 @@
 mod app;
 mod bluetooth;
+mod cli;
 mod idasen;
+mod metrics;
 mod tui;
 
-use std::io;
+use std::{
+    env, io, process,
+    sync::{Arc, Mutex},
+};
 
 use app::{app::App, config::Config};
 use bluetooth::manager::BleManager;
+use cli::Command;
 use crossterm::{
+    event::{DisableMouseCapture, EnableMouseCapture},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use metrics::Metrics;
 use ratatui::{backend::CrosstermBackend, Terminal};
 
 #[tokio::main]
 async fn main() {
-    let ble_manager = BleManager::new().await.unwrap();
+    let cli = match cli::parse(env::args().skip(1)) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, cli::USAGE);
+            process::exit(2);
+        }
+    };
+
+    let config = Config::load().unwrap_or_else(|e| {
+        eprintln!("ignoring the configuration file: {}", e);
+        Config::fallback(&e)
+    });
+
+    let profile = cli.profile.or_else(|| config.default_profile.clone());
+    if let Some(ref name) = profile {
+        if !config.has_profile(name) {
+            eprintln!("unknown profile '{}'", name);
+            process::exit(2);
+        }
+    }
+
+    match cli.command {
+        Command::Tui => (),
+        Command::Help => return println!("{}", cli::USAGE),
+        Command::Export(options) => {
+            if let Err(e) = cli::run_export(options, &config, profile.as_deref()) {
+                eprintln!("export failed: {}", e);
+                process::exit(1);
+            }
+            return;
+        }
+    }
+
+    let metrics = Arc::new(Mutex::new(Metrics::new(config.standing_threshold)));
+    let ble_manager = BleManager::new(Arc::clone(&metrics)).await.unwrap();
 
+    let mouse = config.mouse;
     let mut stdout = &io::stdout();
     enable_raw_mode().unwrap();
     execute!(stdout, EnterAlternateScreen).unwrap();
+    if mouse {
+        execute!(stdout, EnableMouseCapture).unwrap();
+    }
 
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend).unwrap();
 
-    let app = App::new(ble_manager, Config::load());
+    let app = App::new(ble_manager, config, metrics, profile);
     app.run(&mut terminal).await.unwrap();
 
+    if mouse {
+        execute!(stdout, DisableMouseCapture).unwrap();
+    }
     disable_raw_mode().unwrap();
     execute!(stdout, LeaveAlternateScreen).unwrap();
     terminal.show_cursor().unwrap();
@@ -0,0 +1,353 @@
+use std::{
+    fmt::Write,
+    time::{Duration, Instant},
+};
+
+use crate::app::zone::HeightZone;
+
+#[derive(Default)]
+struct Summary {
+    count: u64,
+    sum: Duration,
+    last: Option<Duration>,
+}
+
+impl Summary {
+    fn observe(&mut self, duration: Duration) {
+        self.count += 1;
+        self.sum += duration;
+        self.last = Some(duration);
+    }
+}
+
+/// Desk usage counters collected by the jobs and exposed in the Prometheus
+/// text format by the `MetricsJob`.
+pub struct Metrics {
+    standing_threshold: u16,
+
+    height: Option<u16>,
+    last_observation: Instant,
+    is_moving: bool,
+    movements: u64,
+    sitting_time: Duration,
+    standing_time: Duration,
+
+    has_connected: bool,
+    ble_reconnects: u64,
+
+    move_to: Summary,
+    move_to_failures: u64,
+    move_to_last_error: Option<u16>,
+
+    scan: Summary,
+}
+
+impl Metrics {
+    pub fn new(standing_threshold: u16) -> Self {
+        Self {
+            standing_threshold,
+
+            height: None,
+            last_observation: Instant::now(),
+            is_moving: false,
+            movements: 0,
+            sitting_time: Duration::ZERO,
+            standing_time: Duration::ZERO,
+
+            has_connected: false,
+            ble_reconnects: 0,
+
+            move_to: Summary::default(),
+            move_to_failures: 0,
+            move_to_last_error: None,
+
+            scan: Summary::default(),
+        }
+    }
+
+    pub fn observe_position(&mut self, position: Option<u16>, speed: i16) {
+        self.observe_position_at(position, speed, Instant::now())
+    }
+
+    fn observe_position_at(&mut self, position: Option<u16>, speed: i16, now: Instant) {
+        self.accumulate_zone_time(now);
+
+        let is_moving = position.is_some() && speed != 0;
+        if is_moving && !self.is_moving {
+            self.movements += 1;
+        }
+        self.is_moving = is_moving;
+        self.height = position;
+    }
+
+    pub fn observe_connection(&mut self) {
+        if self.has_connected {
+            self.ble_reconnects += 1;
+        }
+        self.has_connected = true;
+    }
+
+    pub fn observe_move_to(&mut self, duration: Duration, final_error: Option<u16>) {
+        self.move_to.observe(duration);
+        match final_error {
+            Some(error) => self.move_to_last_error = Some(error),
+            None => self.move_to_failures += 1,
+        }
+    }
+
+    pub fn observe_scan(&mut self, duration: Duration) {
+        self.scan.observe(duration)
+    }
+
+    fn accumulate_zone_time(&mut self, now: Instant) {
+        if let Some(height) = self.height {
+            let elapsed = now.duration_since(self.last_observation);
+            match HeightZone::from_height(height, self.standing_threshold) {
+                HeightZone::Sitting => self.sitting_time += elapsed,
+                HeightZone::Standing => self.standing_time += elapsed,
+            }
+        }
+        self.last_observation = now;
+    }
+
+    pub fn render(&mut self) -> String {
+        self.render_at(Instant::now())
+    }
+
+    fn render_at(&mut self, now: Instant) -> String {
+        self.accumulate_zone_time(now);
+
+        let mut out = String::new();
+
+        write_header(&mut out, "idasen_height_meters", "gauge", "Current desk height.");
+        if let Some(height) = self.height {
+            let _ = writeln!(out, "idasen_height_meters {}", tenth_mm_to_meters(height));
+        }
+
+        write_header(
+            &mut out,
+            "idasen_movements_total",
+            "counter",
+            "Number of times the desk started moving.",
+        );
+        let _ = writeln!(out, "idasen_movements_total {}", self.movements);
+
+        write_header(
+            &mut out,
+            "idasen_zone_seconds_total",
+            "counter",
+            "Time spent in each height zone while connected.",
+        );
+        for (zone, time) in [
+            (HeightZone::Sitting, self.sitting_time),
+            (HeightZone::Standing, self.standing_time),
+        ] {
+            let _ = writeln!(
+                out,
+                "idasen_zone_seconds_total{{zone=\"{}\"}} {}",
+                zone.label(),
+                time.as_secs_f64()
+            );
+        }
+
+        write_header(
+            &mut out,
+            "idasen_ble_reconnects_total",
+            "counter",
+            "Number of connections established after the first one.",
+        );
+        let _ = writeln!(out, "idasen_ble_reconnects_total {}", self.ble_reconnects);
+
+        write_summary(
+            &mut out,
+            "idasen_move_to_duration_seconds",
+            "Time spent moving the desk to a target height.",
+            &self.move_to,
+        );
+
+        write_header(
+            &mut out,
+            "idasen_move_to_failures_total",
+            "counter",
+            "Number of moves to a target height that did not complete.",
+        );
+        let _ = writeln!(out, "idasen_move_to_failures_total {}", self.move_to_failures);
+
+        write_header(
+            &mut out,
+            "idasen_move_to_last_error_meters",
+            "gauge",
+            "Distance between the target and the final height of the last completed move.",
+        );
+        if let Some(error) = self.move_to_last_error {
+            let _ = writeln!(
+                out,
+                "idasen_move_to_last_error_meters {}",
+                tenth_mm_to_meters(error)
+            );
+        }
+
+        write_summary(
+            &mut out,
+            "idasen_scan_duration_seconds",
+            "Time spent scanning for bluetooth devices.",
+            &self.scan,
+        );
+
+        out
+    }
+}
+
+fn tenth_mm_to_meters(value: u16) -> f64 {
+    value as f64 / 10000.0
+}
+
+fn write_header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+fn write_summary(out: &mut String, name: &str, help: &str, summary: &Summary) {
+    write_header(out, name, "summary", help);
+    let _ = writeln!(out, "{}_sum {}", name, summary.sum.as_secs_f64());
+    let _ = writeln!(out, "{}_count {}", name, summary.count);
+
+    let last_name = name.replacen("_duration", "_last_duration", 1);
+    write_header(out, &last_name, "gauge", "Duration of the last observation.");
+    if let Some(last) = summary.last {
+        let _ = writeln!(out, "{} {}", last_name, last.as_secs_f64());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECOND: Duration = Duration::from_secs(1);
+
+    // note: the value of the given sample, None when it is missing
+    fn sample(rendered: &str, name: &str) -> Option<f64> {
+        rendered.lines().find_map(|line| {
+            let (sample, value) = line.rsplit_once(' ')?;
+            (sample == name).then(|| value.parse().unwrap())
+        })
+    }
+
+    #[test]
+    fn renders_the_text_exposition_format() {
+        let mut metrics = Metrics::new(10000);
+        let rendered = metrics.render();
+
+        // note: every metric has its help and type, samples follow their type
+        let mut declared = Vec::new();
+        for line in rendered.lines() {
+            if let Some(rest) = line.strip_prefix("# TYPE ") {
+                let (name, kind) = rest.split_once(' ').unwrap();
+                assert!(["gauge", "counter", "summary"].contains(&kind), "{}", line);
+                declared.push(name.to_string());
+            } else if let Some(rest) = line.strip_prefix("# HELP ") {
+                assert!(rest
+                    .split_once(' ')
+                    .is_some_and(|(_, help)| !help.is_empty()));
+            } else {
+                let name = line.split(['{', ' ']).next().unwrap();
+                assert!(
+                    declared.iter().any(|d| name == d
+                        || name
+                            .strip_prefix(d.as_str())
+                            .is_some_and(|s| ["_sum", "_count"].contains(&s))),
+                    "{}",
+                    line
+                );
+                assert!(
+                    line.rsplit_once(' ').unwrap().1.parse::<f64>().is_ok(),
+                    "{}",
+                    line
+                );
+            }
+        }
+        assert!(rendered.ends_with('\n'));
+
+        // note: gauges without a value yet have no sample
+        assert_eq!(sample(&rendered, "idasen_height_meters"), None);
+        assert_eq!(sample(&rendered, "idasen_movements_total"), Some(0.0));
+        assert_eq!(
+            sample(&rendered, "idasen_move_to_duration_seconds_count"),
+            Some(0.0)
+        );
+
+        metrics.observe_position(Some(7250), 0);
+        metrics.observe_move_to(Duration::from_millis(1500), Some(12));
+        metrics.observe_move_to(SECOND, None);
+        let rendered = metrics.render();
+        assert_eq!(sample(&rendered, "idasen_height_meters"), Some(0.725));
+        assert_eq!(
+            sample(&rendered, "idasen_move_to_duration_seconds_sum"),
+            Some(2.5)
+        );
+        assert_eq!(
+            sample(&rendered, "idasen_move_to_duration_seconds_count"),
+            Some(2.0)
+        );
+        assert_eq!(
+            sample(&rendered, "idasen_move_to_last_duration_seconds"),
+            Some(1.0)
+        );
+        assert_eq!(
+            sample(&rendered, "idasen_move_to_failures_total"),
+            Some(1.0)
+        );
+        assert_eq!(
+            sample(&rendered, "idasen_move_to_last_error_meters"),
+            Some(0.0012)
+        );
+    }
+
+    #[test]
+    fn accumulates_the_time_spent_in_each_zone() {
+        let start = Instant::now();
+        let mut metrics = Metrics::new(10000);
+        metrics.last_observation = start;
+
+        // note: the time before the first position is not counted
+        metrics.observe_position_at(Some(7000), 0, start + 5 * SECOND);
+        metrics.observe_position_at(Some(9000), 10, start + 15 * SECOND);
+        metrics.observe_position_at(Some(11000), 0, start + 20 * SECOND);
+        metrics.observe_position_at(None, 0, start + 50 * SECOND);
+        metrics.observe_position_at(Some(7000), 0, start + 60 * SECOND);
+        metrics.observe_position_at(Some(7000), 0, start + 60 * SECOND);
+
+        let rendered = metrics.render_at(start + 62 * SECOND);
+        assert_eq!(
+            sample(&rendered, "idasen_zone_seconds_total{zone=\"sitting\"}"),
+            Some(17.0)
+        );
+        assert_eq!(
+            sample(&rendered, "idasen_zone_seconds_total{zone=\"standing\"}"),
+            Some(30.0)
+        );
+        assert_eq!(sample(&rendered, "idasen_movements_total"), Some(1.0));
+    }
+
+    #[test]
+    fn counts_the_connections_after_the_first_one() {
+        let mut metrics = Metrics::new(10000);
+        assert_eq!(
+            sample(&metrics.render(), "idasen_ble_reconnects_total"),
+            Some(0.0)
+        );
+
+        metrics.observe_connection();
+        assert_eq!(
+            sample(&metrics.render(), "idasen_ble_reconnects_total"),
+            Some(0.0)
+        );
+
+        metrics.observe_connection();
+        metrics.observe_connection();
+        assert_eq!(
+            sample(&metrics.render(), "idasen_ble_reconnects_total"),
+            Some(2.0)
+        );
+    }
+}
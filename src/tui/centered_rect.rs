@@ -3,7 +3,6 @@ use ratatui::{
     text::Text,
 };
 
-#[allow(dead_code)]
 pub fn centered_rect(area: Rect, percent_x: u16, percent_y: u16) -> Rect {
     let outer = Layout::default()
         .direction(Direction::Vertical)
@@ -23,33 +22,65 @@ pub fn centered_rect(area: Rect, percent_x: u16, percent_y: u16) -> Rect {
         .split(outer[1])[1]
 }
 
+// note: the box is shrunk to the area when the content does not fit
 pub fn centered_rect_text(area: Rect, content: Text) -> Rect {
     let (content_height, content_width) = (content.height() + 2, content.width() + 2);
-    let (x, y) = (
-        usize::from(area.width / 2) - content_width / 2,
-        usize::from(area.height / 2) - content_height / 2,
-    );
-
-    Rect {
-        x: x as u16,
-        y: y as u16,
-        width: content_width as u16,
-        height: content_height as u16,
-    }
+    centered_rect_size(
+        area,
+        u16::try_from(content_width).unwrap_or(u16::MAX),
+        u16::try_from(content_height).unwrap_or(u16::MAX),
+    )
 }
 
 #[allow(dead_code)]
 pub fn centered_rect_area(area: Rect, content: Rect) -> Rect {
-    let (content_height, content_width) = (content.height + 2, content.width + 2);
-    let (x, y) = (
-        area.width / 2 - content_width / 2,
-        area.height / 2 - content_height / 2,
-    );
+    centered_rect_size(
+        area,
+        content.width.saturating_add(2),
+        content.height.saturating_add(2),
+    )
+}
+
+fn centered_rect_size(area: Rect, width: u16, height: u16) -> Rect {
+    let (width, height) = (width.min(area.width), height.min(area.height));
 
     Rect {
-        x,
-        y,
-        width: content_width,
-        height: content_height,
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shrinks_the_box_to_any_area() {
+        let contents = [
+            Text::default(),
+            Text::from("reason"),
+            Text::from("a much longer reason\nover\nthree lines"),
+            Text::from("x".repeat(300)),
+        ];
+
+        for width in 0..=24 {
+            for height in 0..=8 {
+                let area = Rect::new(3, 2, width, height);
+                for content in &contents {
+                    let rect = centered_rect_text(area, content.clone());
+                    assert!(rect.x >= area.x && rect.right() <= area.right());
+                    assert!(rect.y >= area.y && rect.bottom() <= area.bottom());
+                    assert_eq!(
+                        (rect.width, rect.height),
+                        (
+                            (content.width() as u16 + 2).min(width),
+                            (content.height() as u16 + 2).min(height)
+                        )
+                    );
+                }
+            }
+        }
     }
 }
@@ -0,0 +1,134 @@
+use ratatui::{buffer::Buffer, layout::Rect, style::Stylize, widgets::Widget};
+
+use crate::idasen::idasen::Direction;
+
+use super::theme::Theme;
+
+pub const HEIGHT_GAUGE_WIDTH: u16 = 3;
+
+const TICK_SYMBOL: &str = "─";
+const TARGET_SYMBOL: &str = "▶";
+const TRACK_SYMBOL: &str = "│";
+const FILLED_SYMBOL: &str = "█";
+const IDLE_SYMBOL: &str = "◀";
+const UP_FRAMES: [&str; 2] = ["▲", "△"];
+const DOWN_FRAMES: [&str; 2] = ["▼", "▽"];
+
+/// Vertical gauge of the desk height: saved positions and the target of the
+/// current move on the left, the direction of the movement on the right.
+pub struct HeightGauge<'a> {
+    pub min: u16,
+    pub max: u16,
+    pub current: Option<u16>,
+    pub direction: Direction,
+    pub target: Option<u16>,
+    pub ticks: Vec<u16>,
+    // note: advances the direction arrow animation
+    pub frame: usize,
+    pub theme: &'a Theme,
+}
+
+impl HeightGauge<'_> {
+    // note: the top row is the max height, out of range values are clamped
+    fn row_of(&self, height: u16, area: Rect) -> u16 {
+        let range = self.max.saturating_sub(self.min).max(1) as u32;
+        let above_min = height.clamp(self.min, self.max) - self.min;
+        let rows = area.height.saturating_sub(1) as u32;
+        area.bottom() - 1 - (above_min as u32 * rows / range) as u16
+    }
+}
+
+impl Widget for HeightGauge<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.width < HEIGHT_GAUGE_WIDTH || area.height == 0 {
+            return;
+        }
+
+        let (tick_x, track_x, arrow_x) = (area.x, area.x + 1, area.x + 2);
+        let current_row = self.current.map(|h| self.row_of(h, area));
+
+        for y in area.top()..area.bottom() {
+            let is_filled = current_row.is_some_and(|row| y >= row);
+            if is_filled {
+                buf.set_string(track_x, y, FILLED_SYMBOL, self.theme.accent());
+            } else {
+                buf.set_string(track_x, y, TRACK_SYMBOL, self.theme.accent().dim());
+            }
+        }
+
+        for tick in &self.ticks {
+            buf.set_string(
+                tick_x,
+                self.row_of(*tick, area),
+                TICK_SYMBOL,
+                self.theme.info(),
+            );
+        }
+
+        if let Some(target) = self.target {
+            buf.set_string(
+                tick_x,
+                self.row_of(target, area),
+                TARGET_SYMBOL,
+                self.theme.warning().bold(),
+            );
+        }
+
+        if let Some(row) = current_row {
+            let symbol = match self.direction {
+                Direction::Up => UP_FRAMES[self.frame % UP_FRAMES.len()],
+                Direction::Down => DOWN_FRAMES[self.frame % DOWN_FRAMES.len()],
+                Direction::Idle => IDLE_SYMBOL,
+            };
+            buf.set_string(arrow_x, row, symbol, self.theme.accent().bold());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gauge(theme: &Theme, min: u16, max: u16) -> HeightGauge<'_> {
+        HeightGauge {
+            min,
+            max,
+            current: None,
+            direction: Direction::Idle,
+            target: None,
+            ticks: Vec::new(),
+            frame: 0,
+            theme,
+        }
+    }
+
+    #[test]
+    fn maps_the_heights_to_rows_within_the_area() {
+        let theme = Theme::new(&Default::default(), false).0;
+        let gauge = gauge(&theme, 6000, 12000);
+        let area = Rect::new(4, 2, HEIGHT_GAUGE_WIDTH, 7);
+
+        assert_eq!(gauge.row_of(6000, area), 8);
+        assert_eq!(gauge.row_of(12000, area), 2);
+        assert_eq!(gauge.row_of(9000, area), 5);
+        // note: just below the next row
+        assert_eq!(gauge.row_of(6999, area), 8);
+        assert_eq!(gauge.row_of(7000, area), 7);
+        assert_eq!(gauge.row_of(0, area), 8);
+        assert_eq!(gauge.row_of(u16::MAX, area), 2);
+    }
+
+    #[test]
+    fn maps_to_the_bottom_row_when_there_is_no_range() {
+        let theme = Theme::new(&Default::default(), false).0;
+        let area = Rect::new(0, 0, HEIGHT_GAUGE_WIDTH, 5);
+        let flat = gauge(&theme, 7000, 7000);
+        assert_eq!(flat.row_of(6000, area), 4);
+        assert_eq!(flat.row_of(8000, area), 4);
+
+        let single_row = Rect::new(0, 3, HEIGHT_GAUGE_WIDTH, 1);
+        let gauge = gauge(&theme, 6000, 12000);
+        assert_eq!(gauge.row_of(6000, single_row), 3);
+        assert_eq!(gauge.row_of(12000, single_row), 3);
+    }
+}
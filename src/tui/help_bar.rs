@@ -3,19 +3,34 @@ use std::ops::Index;
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     text::Text,
-    widgets::{Block, BorderType, Borders, Paragraph},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
     Frame,
 };
 
 const HELP_LABEL_PADDING: u16 = 4;
+// note: below this, the bar is a single line without borders
+const BORDERED_HEIGHT: u16 = 3;
 
+// note: labels that do not fit are left out, the first ones are kept
 pub fn draw(frame: &mut Frame, area: Rect, labels: Vec<String>) {
-    let block = Block::default()
-        .borders(Borders::ALL)
-        .border_type(BorderType::Rounded);
+    let block = if area.height < BORDERED_HEIGHT {
+        Block::default()
+    } else {
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+    };
 
     let inner_area = block.inner(area);
-    let labels: Vec<Text> = labels.into_iter().map(|t| Text::from(t)).collect();
+    let mut width = 0;
+    let labels: Vec<Text> = labels
+        .into_iter()
+        .map(|t| Text::from(t))
+        .take_while(|l| {
+            width += l.width() as u16 + HELP_LABEL_PADDING;
+            width <= inner_area.width
+        })
+        .collect();
 
     let layout = Layout::new(
         Direction::Horizontal,
@@ -25,6 +40,8 @@ pub fn draw(frame: &mut Frame, area: Rect, labels: Vec<String>) {
     )
     .split(inner_area);
 
+    // note: overlays draw their bar over the one of the focused section
+    frame.render_widget(Clear, area);
     frame.render_widget(block, area);
 
     for (idx, label) in labels.iter().enumerate() {
@@ -2,23 +2,25 @@ use std::ops::Index;
 
 use ratatui::{
     layout::{Alignment, Rect},
-    style::{Modifier, Style, Stylize},
+    style::{Modifier, Style},
     widgets::{List, ListItem, ListState, Paragraph},
     Frame,
 };
 
+use super::theme::Theme;
+
 pub trait ListableItem {
     fn render_row(&self) -> String;
     fn is_highlighted(&self) -> bool;
 }
 
 #[derive(Debug, Clone)]
-pub struct StatefulList<T: ListableItem> {
+pub struct StatefulList<T> {
     items: Vec<T>,
     state: ListState,
 }
 
-impl<T: ListableItem> StatefulList<T> {
+impl<T> StatefulList<T> {
     pub fn with_items(items: Vec<T>) -> Self {
         let state = if items.is_empty() {
             ListState::default()
@@ -82,14 +84,59 @@ impl<T: ListableItem> StatefulList<T> {
         }
     }
 
-    pub fn is_current_selected_highlighted(&self) -> bool {
-        if let Some(idx) = self.state.selected() {
-            self.items.index(idx).is_highlighted()
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+
+    pub fn items_mut(&mut self) -> &mut [T] {
+        &mut self.items
+    }
+
+    pub fn selected_index(&self) -> Option<usize> {
+        self.state.selected()
+    }
+
+    pub fn select(&mut self, idx: usize) -> bool {
+        if idx < self.items.len() {
+            self.state.select(Some(idx));
+            true
         } else {
             false
         }
     }
 
+    // note: first visible row, as of the last draw
+    pub fn offset(&self) -> usize {
+        self.state.offset()
+    }
+
+    pub fn push(&mut self, item: T) {
+        self.items.push(item);
+        self.state.select(Some(self.items.len() - 1))
+    }
+
+    pub fn remove(&mut self, idx: usize) -> Option<T> {
+        if idx >= self.items.len() {
+            return None;
+        }
+
+        let item = self.items.remove(idx);
+        self.state.select(if self.items.is_empty() {
+            None
+        } else {
+            Some(idx.min(self.items.len() - 1))
+        });
+        Some(item)
+    }
+
+    // note: the selection follows the item moved from `from`
+    pub fn swap(&mut self, from: usize, to: usize) {
+        if from < self.items.len() && to < self.items.len() {
+            self.items.swap(from, to);
+            self.state.select(Some(to))
+        }
+    }
+
     pub fn set(&mut self, items: Vec<T>) {
         self.items = items;
         if self.items.is_empty() {
@@ -99,7 +146,15 @@ impl<T: ListableItem> StatefulList<T> {
         }
     }
 
-    pub fn draw(&mut self, frame: &mut Frame, area: Rect, is_highlighted: impl Fn(&T) -> bool) {
+    // note: like draw, but the rows are rendered by the caller, given their index
+    pub fn draw_rows(
+        &mut self,
+        frame: &mut Frame,
+        area: Rect,
+        theme: &Theme,
+        render_row: impl Fn(usize, &T) -> String,
+        is_highlighted: impl Fn(&T) -> bool,
+    ) {
         if self.items.is_empty() {
             frame.render_widget(
                 Paragraph::new("no items").alignment(Alignment::Center),
@@ -107,13 +162,14 @@ impl<T: ListableItem> StatefulList<T> {
             )
         } else {
             let item_style = Style::default();
-            let highlight_style = item_style.bold().black().on_green();
+            let highlight_style = theme.highlight();
 
             let items: Vec<ListItem> = self
                 .items
                 .iter()
-                .map(|i| {
-                    let row_str = i.render_row();
+                .enumerate()
+                .map(|(idx, i)| {
+                    let row_str = render_row(idx, i);
                     ListItem::new(row_str).style(if is_highlighted(i) {
                         highlight_style
                     } else {
@@ -134,3 +190,24 @@ impl<T: ListableItem> StatefulList<T> {
         }
     }
 }
+
+// note: items rendered on their own, without anything from the state
+impl<T: ListableItem> StatefulList<T> {
+    pub fn is_current_selected_highlighted(&self) -> bool {
+        if let Some(idx) = self.state.selected() {
+            self.items.index(idx).is_highlighted()
+        } else {
+            false
+        }
+    }
+
+    pub fn draw(
+        &mut self,
+        frame: &mut Frame,
+        area: Rect,
+        theme: &Theme,
+        is_highlighted: impl Fn(&T) -> bool,
+    ) {
+        self.draw_rows(frame, area, theme, |_, i| i.render_row(), is_highlighted)
+    }
+}
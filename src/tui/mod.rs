@@ -1,4 +1,7 @@
 pub mod centered_rect;
+pub mod height_gauge;
 pub mod help_bar;
 pub mod list;
+pub mod modal;
 pub mod notification_list;
+pub mod theme;
@@ -0,0 +1,363 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEventKind};
+use ratatui::{
+    style::Stylize,
+    text::{Line, Span, Text},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use crate::app::{
+    keymap::{DialogScope, Keymap},
+    ui_event_emitter::UIEvent,
+};
+
+use super::{centered_rect::centered_rect_text, theme::Theme};
+
+const INPUT_MIN_WIDTH: usize = 30;
+const INPUT_PREFIX: &str = "> ";
+
+/// What a modal made of an event: the owner closes it unless still pending.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModalOutcome {
+    Pending,
+    Confirmed,
+    Cancelled,
+}
+
+/// Single line of text with a cursor, edited with the usual keys.
+#[derive(Debug, Clone, Default)]
+pub struct TextInput {
+    value: String,
+    // note: in chars, from 0 to the length of the value
+    cursor: usize,
+}
+
+impl TextInput {
+    pub fn new(value: impl Into<String>) -> Self {
+        let value = value.into();
+        Self {
+            cursor: value.chars().count(),
+            value,
+        }
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    pub fn clear(&mut self) {
+        self.value.clear();
+        self.cursor = 0
+    }
+
+    fn byte_index(&self, cursor: usize) -> usize {
+        self.value
+            .char_indices()
+            .nth(cursor)
+            .map_or(self.value.len(), |(idx, _)| idx)
+    }
+
+    /// Applies an editing key, returning whether it was one.
+    pub fn handle_key(&mut self, ev: &KeyEvent) -> bool {
+        let len = self.value.chars().count();
+        match ev.code {
+            KeyCode::Char(c)
+                if !ev
+                    .modifiers
+                    .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                self.value.insert(self.byte_index(self.cursor), c);
+                self.cursor += 1
+            }
+            KeyCode::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                self.value.remove(self.byte_index(self.cursor));
+            }
+            KeyCode::Delete if self.cursor < len => {
+                self.value.remove(self.byte_index(self.cursor));
+            }
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(len),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = len,
+            KeyCode::Backspace | KeyCode::Delete => (),
+            _ => return false,
+        }
+        true
+    }
+
+    /// The input line, with the cursor shown as a reversed cell.
+    pub fn line(&self, min_width: usize) -> Line<'static> {
+        let (before, rest) = self.value.split_at(self.byte_index(self.cursor));
+        let mut chars = rest.chars();
+        let under_cursor = chars.next().map_or(" ".to_string(), |c| c.to_string());
+        let after: String = chars.collect();
+        let padding = min_width.saturating_sub(self.value.chars().count() + 1);
+
+        Line::from(vec![
+            Span::from(format!("{}{}", INPUT_PREFIX, before)),
+            Span::from(under_cursor).reversed(),
+            Span::from(format!("{}{}", after, " ".repeat(padding))),
+        ])
+    }
+}
+
+/// Selection among a few items.
+#[derive(Debug, Clone, Default)]
+pub struct Picker {
+    items: Vec<String>,
+    selected: usize,
+}
+
+impl Picker {
+    pub fn new(items: Vec<String>, selected: usize) -> Self {
+        let selected = selected.min(items.len().saturating_sub(1));
+        Self { items, selected }
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    fn select_prev(&mut self) {
+        if !self.items.is_empty() {
+            self.selected = (self.selected + self.items.len() - 1) % self.items.len()
+        }
+    }
+
+    fn select_next(&mut self) {
+        if !self.items.is_empty() {
+            self.selected = (self.selected + 1) % self.items.len()
+        }
+    }
+}
+
+fn draw_dialog(frame: &mut Frame, title: &str, text: Text, theme: &Theme) {
+    let area = centered_rect_text(frame.size(), text.clone());
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(text).block(
+            Block::default()
+                .title(title)
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(theme.focus_border()),
+        ),
+        area,
+    );
+}
+
+/// Yes/no question.
+pub struct ConfirmDialog<'a> {
+    pub title: &'a str,
+    pub lines: Vec<Line<'a>>,
+}
+
+impl ConfirmDialog<'_> {
+    pub fn handle_event(ev: &UIEvent) -> ModalOutcome {
+        match ev {
+            UIEvent::KeyPress(ev) => match ev.code {
+                KeyCode::Char('y') | KeyCode::Enter => ModalOutcome::Confirmed,
+                KeyCode::Char('n') | KeyCode::Esc => ModalOutcome::Cancelled,
+                _ => ModalOutcome::Pending,
+            },
+            _ => ModalOutcome::Pending,
+        }
+    }
+
+    pub fn help(confirm: &str, cancel: &str) -> Vec<String> {
+        Keymap::dialog_help(DialogScope::Confirm, &[confirm, cancel])
+    }
+
+    pub fn draw(self, frame: &mut Frame, theme: &Theme) {
+        draw_dialog(frame, self.title, Text::from(self.lines), theme)
+    }
+}
+
+/// Prompt followed by a text input.
+pub struct InputDialog<'a> {
+    pub title: &'a str,
+    pub lines: Vec<Line<'a>>,
+    pub input: &'a TextInput,
+}
+
+impl InputDialog<'_> {
+    pub fn handle_event(input: &mut TextInput, ev: &UIEvent) -> ModalOutcome {
+        match ev {
+            UIEvent::KeyPress(ev) => match ev.code {
+                KeyCode::Enter => ModalOutcome::Confirmed,
+                KeyCode::Esc => ModalOutcome::Cancelled,
+                _ => {
+                    input.handle_key(ev);
+                    ModalOutcome::Pending
+                }
+            },
+            _ => ModalOutcome::Pending,
+        }
+    }
+
+    pub fn help(confirm: &str, cancel: &str) -> Vec<String> {
+        Keymap::dialog_help(DialogScope::Input, &[confirm, cancel])
+    }
+
+    pub fn draw(self, frame: &mut Frame, theme: &Theme) {
+        let mut lines = self.lines;
+        lines.push(self.input.line(INPUT_MIN_WIDTH));
+        draw_dialog(frame, self.title, Text::from(lines), theme)
+    }
+}
+
+/// List to pick an item from.
+pub struct PickerDialog<'a> {
+    pub title: &'a str,
+    pub picker: &'a Picker,
+}
+
+impl PickerDialog<'_> {
+    pub fn handle_event(picker: &mut Picker, ev: &UIEvent) -> ModalOutcome {
+        match ev {
+            UIEvent::KeyPress(ev) => match ev.code {
+                KeyCode::Enter => ModalOutcome::Confirmed,
+                KeyCode::Esc => ModalOutcome::Cancelled,
+                KeyCode::Up | KeyCode::Char('k') => {
+                    picker.select_prev();
+                    ModalOutcome::Pending
+                }
+                KeyCode::Down | KeyCode::Char('j') => {
+                    picker.select_next();
+                    ModalOutcome::Pending
+                }
+                _ => ModalOutcome::Pending,
+            },
+            UIEvent::Click(ev) => {
+                match ev.kind {
+                    MouseEventKind::ScrollUp => picker.select_prev(),
+                    MouseEventKind::ScrollDown => picker.select_next(),
+                    _ => (),
+                }
+                ModalOutcome::Pending
+            }
+            _ => ModalOutcome::Pending,
+        }
+    }
+
+    pub fn help() -> Vec<String> {
+        Keymap::dialog_help(DialogScope::Picker, &[])
+    }
+
+    // note: only the rows that fit are drawn, scrolled to keep the selection visible
+    pub fn draw(self, frame: &mut Frame, theme: &Theme) {
+        let visible = frame.size().height.saturating_sub(2).max(1) as usize;
+        let offset = self.picker.selected.saturating_sub(visible - 1);
+        let lines: Vec<Line> = self
+            .picker
+            .items
+            .iter()
+            .enumerate()
+            .skip(offset)
+            .take(visible)
+            .map(|(idx, item)| {
+                if idx == self.picker.selected {
+                    Line::from(
+                        Span::from(format!("{}{}", INPUT_PREFIX, item))
+                            .bold()
+                            .reversed(),
+                    )
+                } else {
+                    Line::from(format!("  {}", item))
+                }
+            })
+            .collect();
+        draw_dialog(frame, self.title, Text::from(lines), theme)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(input: &mut TextInput, code: KeyCode) {
+        input.handle_key(&KeyEvent::new(code, KeyModifiers::NONE));
+    }
+
+    fn line_text(input: &TextInput) -> String {
+        input
+            .line(0)
+            .spans
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect()
+    }
+
+    #[test]
+    fn inserts_at_the_cursor_between_multi_byte_chars() {
+        let mut input = TextInput::new("é日");
+        press(&mut input, KeyCode::Left);
+        press(&mut input, KeyCode::Char('x'));
+        assert_eq!(input.value(), "éx日");
+
+        press(&mut input, KeyCode::Home);
+        press(&mut input, KeyCode::Char('ü'));
+        assert_eq!(input.value(), "üéx日");
+
+        press(&mut input, KeyCode::End);
+        press(&mut input, KeyCode::Char('!'));
+        assert_eq!(input.value(), "üéx日!");
+    }
+
+    #[test]
+    fn deletes_whole_chars_around_the_cursor() {
+        let mut input = TextInput::new("aé日b");
+        press(&mut input, KeyCode::Left);
+        press(&mut input, KeyCode::Backspace);
+        assert_eq!(input.value(), "aéb");
+
+        press(&mut input, KeyCode::Left);
+        press(&mut input, KeyCode::Delete);
+        assert_eq!(input.value(), "ab");
+    }
+
+    #[test]
+    fn ignores_deletes_at_the_ends() {
+        let mut input = TextInput::new("日");
+        press(&mut input, KeyCode::Delete);
+        assert_eq!(input.value(), "日");
+
+        press(&mut input, KeyCode::Home);
+        press(&mut input, KeyCode::Backspace);
+        assert_eq!(input.value(), "日");
+    }
+
+    #[test]
+    fn keeps_the_cursor_within_the_value() {
+        let mut input = TextInput::new("é");
+        press(&mut input, KeyCode::Right);
+        press(&mut input, KeyCode::Char('a'));
+        assert_eq!(input.value(), "éa");
+
+        press(&mut input, KeyCode::Home);
+        press(&mut input, KeyCode::Left);
+        press(&mut input, KeyCode::Char('b'));
+        assert_eq!(input.value(), "béa");
+    }
+
+    #[test]
+    fn shows_the_char_under_the_cursor() {
+        let mut input = TextInput::new("é日");
+        assert_eq!(line_text(&input), "> é日 ");
+
+        press(&mut input, KeyCode::Home);
+        let line = input.line(0);
+        assert_eq!(line.spans[0].content, "> ");
+        assert_eq!(line.spans[1].content, "é");
+        assert_eq!(line.spans[2].content, "日");
+    }
+
+    #[test]
+    fn leaves_control_keys_to_the_caller() {
+        let mut input = TextInput::new("a");
+        let handled = input.handle_key(&KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL));
+        assert!(!handled);
+        assert_eq!(input.value(), "a");
+    }
+}
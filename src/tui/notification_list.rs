@@ -2,7 +2,6 @@ use std::cmp;
 
 use ratatui::{
     layout::{Alignment, Rect},
-    style::{Style, Stylize},
     text::Text,
     widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
     Frame,
@@ -10,6 +9,8 @@ use ratatui::{
 
 use crate::app::jobs::notification_job::{Notification, NotificationType};
 
+use super::theme::Theme;
+
 const MAX_ITEM_HEIGHT: u16 = 5;
 const MAX_ITEM_WIDTH: u16 = 45;
 
@@ -19,49 +20,87 @@ const NOTIFICATION_PADDING: u16 = 4; // note: this could probably be calculated
 pub struct NotificationList;
 
 impl NotificationList {
-    pub fn draw(window_frame: &mut Frame, to_show: Vec<Notification>) {
-        let items: Vec<(Paragraph, Rect)> = to_show
-            .into_iter()
-            .enumerate()
-            .map(|(idx, notification)| {
-                let text = Text::from(notification.message);
-                let paragraph = Paragraph::new(text.clone())
-                    .alignment(Alignment::Center)
-                    .wrap(Wrap::default())
-                    .block(
-                        Block::default()
-                            .title(match notification.kind {
-                                NotificationType::Info => "info",
-                                NotificationType::Error => "error!",
-                                NotificationType::Warning => "warning!",
-                            })
-                            .border_type(BorderType::Rounded)
-                            .borders(Borders::ALL)
-                            .border_style(match notification.kind {
-                                NotificationType::Info => Style::default().blue(),
-                                NotificationType::Warning => Style::default().yellow(),
-                                NotificationType::Error => Style::default().red(),
-                            }),
-                    );
+    // note: stacked from the bottom right corner, the ones that do not fit are left out
+    pub fn draw(window_frame: &mut Frame, to_show: Vec<Notification>, theme: &Theme) {
+        let area = window_frame.size();
+        let max_width = cmp::min(
+            MAX_ITEM_WIDTH,
+            area.width.saturating_sub(NOTIFICATION_MARGIN),
+        );
+        let mut bottom = area.bottom();
 
-                let item_width =
-                    cmp::min(MAX_ITEM_WIDTH, (text.width() as u16) + NOTIFICATION_PADDING);
-                let item_height = cmp::min(MAX_ITEM_HEIGHT, (text.height() as u16) + NOTIFICATION_PADDING);
+        for notification in to_show.into_iter() {
+            let text = Text::from(notification.message);
+            let item_width = cmp::min(
+                max_width,
+                (text.width() as u16).saturating_add(NOTIFICATION_PADDING),
+            );
+            let item_height = cmp::min(
+                MAX_ITEM_HEIGHT,
+                (text.height() as u16).saturating_add(NOTIFICATION_PADDING),
+            );
+            if item_width == 0 || item_height > bottom - area.top() {
+                break;
+            }
 
-                let frame = Rect::new(
-                    window_frame.size().width - item_width - NOTIFICATION_MARGIN,
-                    window_frame.size().height
-                        - ((idx as u16) + NOTIFICATION_MARGIN) * (item_height),
-                    item_width,
-                    item_height,
+            let paragraph = Paragraph::new(text)
+                .alignment(Alignment::Center)
+                .wrap(Wrap::default())
+                .block(
+                    Block::default()
+                        .title(match notification.kind {
+                            NotificationType::Info => "info",
+                            NotificationType::Error => "error!",
+                            NotificationType::Warning => "warning!",
+                        })
+                        .border_type(BorderType::Rounded)
+                        .borders(Borders::ALL)
+                        .border_style(match notification.kind {
+                            NotificationType::Info => theme.info(),
+                            NotificationType::Warning => theme.warning(),
+                            NotificationType::Error => theme.error(),
+                        }),
                 );
-                (paragraph, frame)
-            })
-            .collect();
 
-        for (paragraph, frame) in items.into_iter() {
+            bottom -= item_height;
+            let frame = Rect::new(
+                area.right() - item_width - NOTIFICATION_MARGIN,
+                bottom,
+                item_width,
+                item_height,
+            );
             window_frame.render_widget(Clear, frame);
             window_frame.render_widget(paragraph, frame);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use ratatui::{backend::TestBackend, Terminal};
+
+    use super::*;
+
+    #[test]
+    fn draws_the_notifications_that_fit_at_any_size() {
+        let theme = Theme::new(&Default::default(), false).0;
+        let notifications = vec![
+            Notification::new(
+                NotificationType::Info,
+                "device succesfully connected".into(),
+            ),
+            Notification::new(NotificationType::Warning, "w".into()),
+            Notification::new(NotificationType::Error, "a long error ".repeat(20)),
+        ];
+        let mut terminal = Terminal::new(TestBackend::new(1, 1)).unwrap();
+
+        for width in 1..=MAX_ITEM_WIDTH + 10 {
+            for height in 1..=3 * MAX_ITEM_HEIGHT + 2 {
+                terminal.backend_mut().resize(width, height);
+                terminal
+                    .draw(|frame| NotificationList::draw(frame, notifications.clone(), &theme))
+                    .unwrap();
+            }
+        }
+    }
+}
@@ -0,0 +1,274 @@
+use std::{collections::HashMap, ffi::OsStr, str::FromStr};
+
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ThemeName {
+    #[default]
+    Dark,
+    Light,
+    HighContrast,
+}
+
+impl ThemeName {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(ThemeName::Dark),
+            "light" => Some(ThemeName::Light),
+            "high-contrast" => Some(ThemeName::HighContrast),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ThemeConfig {
+    // note: "dark", "light" or "high-contrast", the default one when unknown
+    #[serde(default)]
+    pub name: Option<String>,
+    // note: theme color to color name or hex code, e.g. {"focus": "magenta", "accent": "#268bd2"}
+    #[serde(default)]
+    pub colors: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Theme {
+    focus: Color,
+    accent: Color,
+    highlight_fg: Color,
+    highlight_bg: Color,
+    success: Color,
+    warning: Color,
+    error: Color,
+    info: Color,
+    // note: set by NO_COLOR, only text modifiers are used
+    monochrome: bool,
+}
+
+impl Theme {
+    fn builtin(name: ThemeName) -> Self {
+        match name {
+            ThemeName::Dark => Self {
+                focus: Color::LightRed,
+                accent: Color::Blue,
+                highlight_fg: Color::Black,
+                highlight_bg: Color::Green,
+                success: Color::Green,
+                warning: Color::Yellow,
+                error: Color::Red,
+                info: Color::Blue,
+                monochrome: false,
+            },
+            ThemeName::Light => Self {
+                focus: Color::Red,
+                accent: Color::Blue,
+                highlight_fg: Color::White,
+                highlight_bg: Color::Rgb(0x2e, 0x7d, 0x32),
+                success: Color::Rgb(0x2e, 0x7d, 0x32),
+                warning: Color::Rgb(0xb2, 0x6b, 0x00),
+                error: Color::Red,
+                info: Color::Blue,
+                monochrome: false,
+            },
+            ThemeName::HighContrast => Self {
+                focus: Color::LightYellow,
+                accent: Color::White,
+                highlight_fg: Color::Black,
+                highlight_bg: Color::White,
+                success: Color::LightGreen,
+                warning: Color::LightYellow,
+                error: Color::LightRed,
+                info: Color::LightCyan,
+                monochrome: false,
+            },
+        }
+    }
+
+    /// Whether the value of `NO_COLOR` asks for no colors, see
+    /// https://no-color.org: an empty value does not count.
+    pub fn is_no_color(value: Option<&OsStr>) -> bool {
+        value.is_some_and(|v| !v.is_empty())
+    }
+
+    /// Builds the configured theme. An unknown name and invalid overrides
+    /// are skipped and returned together with the theme.
+    pub fn new(config: &ThemeConfig, no_color: bool) -> (Self, Vec<String>) {
+        let mut errors = Vec::new();
+        let name = match config.name.as_deref() {
+            None => ThemeName::default(),
+            Some(name) => ThemeName::parse(name).unwrap_or_else(|| {
+                errors.push(format!("unknown theme '{}', using the dark one", name));
+                ThemeName::default()
+            }),
+        };
+
+        let mut theme = Self::builtin(name);
+        theme.monochrome = no_color;
+
+        for (name, value) in &config.colors {
+            match (theme.color_mut(name), Color::from_str(value)) {
+                (Some(color), Ok(value)) => *color = value,
+                (None, _) => errors.push(format!("unknown theme color '{}'", name)),
+                (_, Err(_)) => errors.push(format!("invalid color '{}' for '{}'", value, name)),
+            }
+        }
+
+        (theme, errors)
+    }
+
+    fn color_mut(&mut self, name: &str) -> Option<&mut Color> {
+        Some(match name {
+            "focus" => &mut self.focus,
+            "accent" => &mut self.accent,
+            "highlight_fg" => &mut self.highlight_fg,
+            "highlight_bg" => &mut self.highlight_bg,
+            "success" => &mut self.success,
+            "warning" => &mut self.warning,
+            "error" => &mut self.error,
+            "info" => &mut self.info,
+            _ => return None,
+        })
+    }
+
+    fn fg(&self, color: Color) -> Style {
+        if self.monochrome {
+            Style::new()
+        } else {
+            Style::new().fg(color)
+        }
+    }
+
+    /// Border of the focused section and of the popups.
+    pub fn focus_border(&self) -> Style {
+        self.fg(self.focus).bold()
+    }
+
+    pub fn accent(&self) -> Style {
+        self.fg(self.accent)
+    }
+
+    /// Rows standing out in lists, e.g. the connected device.
+    pub fn highlight(&self) -> Style {
+        if self.monochrome {
+            Style::new().add_modifier(Modifier::BOLD | Modifier::UNDERLINED)
+        } else {
+            Style::new()
+                .bold()
+                .fg(self.highlight_fg)
+                .bg(self.highlight_bg)
+        }
+    }
+
+    pub fn success(&self) -> Style {
+        self.fg(self.success)
+    }
+
+    pub fn warning(&self) -> Style {
+        self.fg(self.warning)
+    }
+
+    pub fn error(&self) -> Style {
+        if self.monochrome {
+            Style::new().bold()
+        } else {
+            self.fg(self.error)
+        }
+    }
+
+    pub fn info(&self) -> Style {
+        self.fg(self.info)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::OsString;
+
+    use super::*;
+
+    fn theme(config: serde_json::Value, no_color: bool) -> (Theme, Vec<String>) {
+        Theme::new(&serde_json::from_value(config).unwrap(), no_color)
+    }
+
+    #[test]
+    fn selects_the_theme_by_name() {
+        for (name, expected) in [
+            (None, ThemeName::Dark),
+            (Some("dark"), ThemeName::Dark),
+            (Some("light"), ThemeName::Light),
+            (Some("high-contrast"), ThemeName::HighContrast),
+        ] {
+            let (theme, errors) = theme(serde_json::json!({ "name": name }), false);
+            assert!(errors.is_empty(), "{:?}", errors);
+            assert_eq!(theme.focus, Theme::builtin(expected).focus, "{:?}", name);
+            assert_eq!(theme.highlight_bg, Theme::builtin(expected).highlight_bg);
+        }
+    }
+
+    #[test]
+    fn falls_back_to_the_dark_theme_on_unknown_names() {
+        let (theme, errors) = theme(
+            serde_json::json!({ "name": "solarized", "colors": { "focus": "magenta" } }),
+            false,
+        );
+        assert_eq!(
+            errors,
+            vec!["unknown theme 'solarized', using the dark one"]
+        );
+        assert_eq!(
+            theme.highlight_bg,
+            Theme::builtin(ThemeName::Dark).highlight_bg
+        );
+        assert_eq!(theme.focus, Color::Magenta);
+    }
+
+    #[test]
+    fn reports_the_invalid_color_overrides() {
+        let (theme, mut errors) = theme(
+            serde_json::json!({
+                "name": "light",
+                "colors": { "accent": "#268bd2", "error": "reddish", "border": "red" },
+            }),
+            false,
+        );
+        errors.sort();
+        assert_eq!(
+            errors,
+            vec![
+                "invalid color 'reddish' for 'error'",
+                "unknown theme color 'border'",
+            ]
+        );
+        assert_eq!(theme.accent, Color::Rgb(0x26, 0x8b, 0xd2));
+        assert_eq!(theme.error, Theme::builtin(ThemeName::Light).error);
+    }
+
+    #[test]
+    fn uses_no_color_at_all_with_no_color() {
+        assert!(Theme::is_no_color(Some(&OsString::from("1"))));
+        assert!(!Theme::is_no_color(Some(&OsString::new())));
+        assert!(!Theme::is_no_color(None));
+
+        let (theme, _) = theme(
+            serde_json::json!({ "name": "high-contrast", "colors": { "focus": "red" } }),
+            true,
+        );
+        for style in [
+            theme.focus_border(),
+            theme.accent(),
+            theme.highlight(),
+            theme.success(),
+            theme.warning(),
+            theme.error(),
+            theme.info(),
+        ] {
+            assert_eq!((style.fg, style.bg), (None, None), "{:?}", style);
+        }
+        assert!(theme
+            .highlight()
+            .add_modifier
+            .contains(Modifier::UNDERLINED));
+        assert!(theme.error().add_modifier.contains(Modifier::BOLD));
+    }
+}